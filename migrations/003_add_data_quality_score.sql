@@ -0,0 +1,9 @@
+-- Add per-file data quality score to processed_files table
+
+ALTER TABLE processed_files
+ADD COLUMN IF NOT EXISTS data_quality_score DOUBLE PRECISION;
+
+-- Add index for quality triage queries
+CREATE INDEX IF NOT EXISTS idx_processed_files_quality ON processed_files(data_quality_score);
+
+COMMENT ON COLUMN processed_files.data_quality_score IS 'Data quality from 0.0 (poor) to 1.0 (good), derived from parse failures and validation warnings';
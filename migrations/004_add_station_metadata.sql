@@ -0,0 +1,8 @@
+-- Add station metadata from the NOAA USCRN station list
+
+ALTER TABLE stations
+ADD COLUMN IF NOT EXISTS elevation DOUBLE PRECISION,
+ADD COLUMN IF NOT EXISTS commissioned_date DATE;
+
+COMMENT ON COLUMN stations.elevation IS 'Station elevation as published in the NOAA station list';
+COMMENT ON COLUMN stations.commissioned_date IS 'Date the station was commissioned';
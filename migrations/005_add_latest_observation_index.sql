@@ -0,0 +1,13 @@
+-- no-transaction
+-- Index for latest-observation-per-station lookups
+--
+-- Queries of the form
+--   SELECT DISTINCT ON (wbanno) ... ORDER BY wbanno, utc_datetime DESC
+-- can walk this index in order instead of scanning and sorting the table.
+-- The existing (wbanno, utc_datetime) indexes are ascending on both columns,
+-- so they cannot serve the mixed ASC/DESC ordering.
+--
+-- Built CONCURRENTLY so a large observations table stays writable; this
+-- cannot run inside a transaction, and must be the only statement here.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_observations_wbanno_utc_desc
+    ON observations(wbanno, utc_datetime DESC);
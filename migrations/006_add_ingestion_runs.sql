@@ -0,0 +1,16 @@
+-- Record each ingestion run and which run last wrote each observation
+
+CREATE TABLE IF NOT EXISTS ingestion_runs (
+    id SERIAL PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+ALTER TABLE observations
+ADD COLUMN IF NOT EXISTS ingestion_run_id INTEGER REFERENCES ingestion_runs(id);
+
+-- Add index for run-level auditing queries
+CREATE INDEX IF NOT EXISTS idx_observations_ingestion_run ON observations(ingestion_run_id);
+
+COMMENT ON COLUMN ingestion_runs.completed_at IS 'NULL while the run is in progress or if it was interrupted';
+COMMENT ON COLUMN observations.ingestion_run_id IS 'Ingestion run that inserted or last updated this observation';
@@ -0,0 +1,6 @@
+-- Track the logger firmware version each station currently reports
+
+ALTER TABLE stations
+ADD COLUMN IF NOT EXISTS current_crx_version VARCHAR(10);
+
+COMMENT ON COLUMN stations.current_crx_version IS 'Most common crx_version in the most recently processed file';
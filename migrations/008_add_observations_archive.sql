@@ -0,0 +1,10 @@
+-- Cold storage for old observations moved out by Repository::archive_observations
+--
+-- LIKE copies columns, defaults, constraints and indexes (but not foreign keys,
+-- so archived rows outlive their processed_files records). Columns added to
+-- observations later must be added here too, and to the column list archiving
+-- copies (ARCHIVED_OBSERVATION_COLUMNS); their order need not match.
+
+CREATE TABLE IF NOT EXISTS observations_archive (LIKE observations INCLUDING ALL);
+
+COMMENT ON TABLE observations_archive IS 'Observations moved out of the observations table by the archive command';
@@ -0,0 +1,13 @@
+-- Record when each observation row was last written
+--
+-- created_at keeps the time of the first insert; ingested_at is bumped every
+-- time a re-processed file upserts the row. The archive table gets the same
+-- column so archiving can copy it along with the rest of the row.
+
+ALTER TABLE observations
+ADD COLUMN IF NOT EXISTS ingested_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+ALTER TABLE observations_archive
+ADD COLUMN IF NOT EXISTS ingested_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+COMMENT ON COLUMN observations.ingested_at IS 'When the row was last inserted or updated by ingestion';
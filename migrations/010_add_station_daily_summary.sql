@@ -0,0 +1,22 @@
+-- Per-station daily rollup for dashboard queries
+--
+-- Days are UTC calendar days regardless of the session time zone. The view is
+-- not refreshed automatically; call Repository::refresh_daily_aggregates after
+-- ingesting. The unique index is required for REFRESH ... CONCURRENTLY.
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS station_daily_summary AS
+SELECT
+    wbanno,
+    (utc_datetime AT TIME ZONE 'UTC')::DATE AS obs_date,
+    AVG(t_hr_avg)::FLOAT4 AS avg_temp,
+    MIN(t_min)::FLOAT4 AS min_temp,
+    MAX(t_max)::FLOAT4 AS max_temp,
+    SUM(p_calc)::FLOAT4 AS total_precip,
+    AVG(soil_moisture_5)::FLOAT4 AS avg_soil_moisture
+FROM observations
+GROUP BY 1, 2;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_station_daily_summary_wbanno_date
+ON station_daily_summary(wbanno, obs_date);
+
+COMMENT ON MATERIALIZED VIEW station_daily_summary IS 'Daily per-station averages and totals; refreshed by Repository::refresh_daily_aggregates';
@@ -0,0 +1,18 @@
+-- Publish each newly inserted observation on the new_observation channel
+--
+-- Consumed by Repository::listen_observations. Only inserts notify; rows
+-- updated by re-processing a file do not. Notifications are delivered when
+-- the inserting transaction commits, one per row.
+
+CREATE OR REPLACE FUNCTION notify_new_observation() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('new_observation', row_to_json(NEW)::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS after_observation_insert ON observations;
+
+CREATE TRIGGER after_observation_insert
+AFTER INSERT ON observations
+FOR EACH ROW EXECUTE FUNCTION notify_new_observation();
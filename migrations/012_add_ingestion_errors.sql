@@ -0,0 +1,14 @@
+-- Record file processing errors so monitoring can query them without logs
+
+CREATE TABLE IF NOT EXISTS ingestion_errors (
+    id SERIAL PRIMARY KEY,
+    file_name TEXT NOT NULL,
+    error_message TEXT NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    retry_count INTEGER NOT NULL DEFAULT 0
+);
+
+-- Add index for "errors since" queries
+CREATE INDEX IF NOT EXISTS idx_ingestion_errors_occurred_at ON ingestion_errors(occurred_at);
+
+COMMENT ON COLUMN ingestion_errors.retry_count IS 'Number of earlier recorded errors for the same file';
@@ -0,0 +1,19 @@
+-- Quality control flags set by external QC systems
+--
+-- Flags are kept apart from the ingested values so re-processing a file never
+-- clears them. They are removed with their observation (e.g. when archived).
+
+CREATE TABLE IF NOT EXISTS observation_flags (
+    id BIGSERIAL PRIMARY KEY,
+    observation_id BIGINT NOT NULL REFERENCES observations(id) ON DELETE CASCADE,
+    flag_type TEXT NOT NULL,
+    severity INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    flagged_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_observation_flags_observation_id ON observation_flags(observation_id);
+
+COMMENT ON COLUMN observation_flags.flag_type IS 'QC check that raised the flag, e.g. spike or stuck_sensor';
+COMMENT ON COLUMN observation_flags.flagged_by IS 'System or person that set the flag';
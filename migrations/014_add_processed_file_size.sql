@@ -0,0 +1,6 @@
+-- Record downloaded file sizes to track NOAA data growth
+
+ALTER TABLE processed_files
+ADD COLUMN IF NOT EXISTS file_size_bytes BIGINT;
+
+COMMENT ON COLUMN processed_files.file_size_bytes IS 'Size of the file when last processed, from Content-Length or the downloaded body';
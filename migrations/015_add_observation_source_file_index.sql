@@ -0,0 +1,6 @@
+-- Index observations by source file
+--
+-- Repository::verify_file_observation_count counts each file's observations;
+-- without this index every count scans the whole table.
+
+CREATE INDEX IF NOT EXISTS idx_observations_source_file_id ON observations(source_file_id);
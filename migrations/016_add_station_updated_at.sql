@@ -0,0 +1,27 @@
+-- Track when each station's metadata last changed
+--
+-- Maintained by a trigger so every writer (upserts from ingestion, the
+-- station metadata loader, crx_version updates) is covered. Upserts that
+-- leave the row unchanged do not bump the timestamp.
+
+ALTER TABLE stations
+ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_stations_updated_at ON stations(updated_at);
+
+CREATE OR REPLACE FUNCTION touch_station_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW IS DISTINCT FROM OLD THEN
+        NEW.updated_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS before_station_update ON stations;
+
+CREATE TRIGGER before_station_update
+BEFORE UPDATE ON stations
+FOR EACH ROW EXECUTE FUNCTION touch_station_updated_at();
+
+COMMENT ON COLUMN stations.updated_at IS 'When the station row last changed';
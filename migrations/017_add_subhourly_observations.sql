@@ -0,0 +1,46 @@
+-- 5-minute observations from the subhourly01 product
+--
+-- Populated instead of observations when source.product is subhourly01.
+-- Column names follow the subhourly01 README rather than hourly02's.
+
+CREATE TABLE IF NOT EXISTS subhourly_observations (
+    id BIGSERIAL PRIMARY KEY,
+    wbanno INTEGER NOT NULL REFERENCES stations(wbanno),
+    utc_datetime TIMESTAMPTZ NOT NULL,
+    lst_datetime TIMESTAMPTZ NOT NULL,
+    crx_version VARCHAR(10),
+
+    air_temperature REAL,
+    precipitation REAL,
+
+    solar_radiation REAL,
+    sr_flag INTEGER,
+
+    surface_temperature REAL,
+    st_type CHAR(1),
+    st_flag INTEGER,
+
+    relative_humidity REAL,
+    rh_flag INTEGER,
+
+    soil_moisture_5 REAL,
+    soil_temperature_5 REAL,
+
+    wetness INTEGER,
+    wet_flag INTEGER,
+
+    wind_1_5 REAL,
+    wind_flag INTEGER,
+
+    source_file_id INTEGER REFERENCES processed_files(id),
+    ingested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (wbanno, utc_datetime)
+);
+
+CREATE INDEX IF NOT EXISTS idx_subhourly_observations_utc_datetime
+    ON subhourly_observations(utc_datetime);
+
+COMMENT ON TABLE subhourly_observations IS '5-minute USCRN observations (subhourly01 product)';
+COMMENT ON COLUMN subhourly_observations.utc_datetime IS 'End of the 5-minute period';
+COMMENT ON COLUMN subhourly_observations.wetness IS 'Leaf wetness sensor resistance in ohms; below 1000 indicates moisture';
@@ -0,0 +1,53 @@
+-- Daily summaries from the daily01 product
+--
+-- Populated instead of observations when source.product is daily01.
+-- Column names follow the daily01 README.
+
+CREATE TABLE IF NOT EXISTS daily_observations (
+    id BIGSERIAL PRIMARY KEY,
+    wbanno INTEGER NOT NULL REFERENCES stations(wbanno),
+    lst_date DATE NOT NULL,
+    crx_version VARCHAR(10),
+
+    t_daily_max REAL,
+    t_daily_min REAL,
+    t_daily_mean REAL,
+    t_daily_avg REAL,
+    p_daily_calc REAL,
+    solarad_daily REAL,
+
+    sur_temp_daily_type CHAR(1),
+    sur_temp_daily_max REAL,
+    sur_temp_daily_min REAL,
+    sur_temp_daily_avg REAL,
+
+    rh_daily_max REAL,
+    rh_daily_min REAL,
+    rh_daily_avg REAL,
+
+    soil_moisture_5_daily REAL,
+    soil_moisture_10_daily REAL,
+    soil_moisture_20_daily REAL,
+    soil_moisture_50_daily REAL,
+    soil_moisture_100_daily REAL,
+
+    soil_temp_5_daily REAL,
+    soil_temp_10_daily REAL,
+    soil_temp_20_daily REAL,
+    soil_temp_50_daily REAL,
+    soil_temp_100_daily REAL,
+
+    source_file_id INTEGER REFERENCES processed_files(id),
+    ingested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (wbanno, lst_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_observations_lst_date
+    ON daily_observations(lst_date);
+
+COMMENT ON TABLE daily_observations IS 'Daily USCRN summaries (daily01 product)';
+COMMENT ON COLUMN daily_observations.lst_date IS 'Local standard time day summarized';
+COMMENT ON COLUMN daily_observations.t_daily_mean IS '(T_DAILY_MAX + T_DAILY_MIN) / 2';
+COMMENT ON COLUMN daily_observations.t_daily_avg IS 'Average of the day''s 5-minute temperatures';
+COMMENT ON COLUMN daily_observations.solarad_daily IS 'Total solar energy in MJ/m²';
@@ -0,0 +1,9 @@
+-- Record how each processed file was ingested
+--
+-- Files re-ingested by the `backfill` command are marked 'backfill'; all
+-- others keep the default.
+
+ALTER TABLE processed_files
+ADD COLUMN IF NOT EXISTS source TEXT NOT NULL DEFAULT 'scheduler';
+
+COMMENT ON COLUMN processed_files.source IS 'scheduler, or backfill once re-ingested by the backfill command';
@@ -0,0 +1,26 @@
+-- Keep one ingestion error row per file
+--
+-- Every failure used to add a row, so a file failing each run grew the table
+-- without bound. Each file's rows are merged into its newest one, whose
+-- retry_count becomes the number of earlier errors, and later failures update
+-- that row in place.
+
+WITH counts AS (
+    SELECT id,
+           ROW_NUMBER() OVER (PARTITION BY file_name ORDER BY occurred_at DESC, id DESC) AS rank,
+           COUNT(*) OVER (PARTITION BY file_name) AS errors
+    FROM ingestion_errors
+)
+UPDATE ingestion_errors e
+SET retry_count = c.errors - 1
+FROM counts c
+WHERE e.id = c.id AND c.rank = 1;
+
+DELETE FROM ingestion_errors e
+USING ingestion_errors newer
+WHERE newer.file_name = e.file_name
+  AND (newer.occurred_at, newer.id) > (e.occurred_at, e.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ingestion_errors_file_name ON ingestion_errors(file_name);
+
+COMMENT ON COLUMN ingestion_errors.retry_count IS 'Number of earlier errors for the same file, whose latest error this row holds';
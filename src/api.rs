@@ -0,0 +1,225 @@
+use crate::config::ApiConfig;
+use crate::db::{DailyAggregate, HealthReport, Observation, Repository, Station, Wbanno};
+use crate::error::{AppError, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use futures::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Observations returned when `limit` is not given
+const DEFAULT_OBSERVATION_LIMIT: usize = 1000;
+
+/// Largest `limit` accepted for observations
+const MAX_OBSERVATION_LIMIT: usize = 10_000;
+
+/// Days queried when `start` is not given, counted back from `end`
+const DEFAULT_RANGE_DAYS: i64 = 7;
+
+/// Routes for the HTTP API, sharing `repository` with the scheduler
+pub fn router(repository: Arc<Repository>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/healthz", get(health))
+        .route("/api/v1/stations", get(list_stations))
+        .route("/api/v1/stations/{wbanno}", get(get_station))
+        .route(
+            "/api/v1/stations/{wbanno}/observations",
+            get(get_observations),
+        )
+        .route("/api/v1/stations/{wbanno}/daily", get(get_daily_aggregates))
+        .with_state(repository)
+}
+
+/// Serve the HTTP API on `config.bind_address` until `shutdown` is set
+///
+/// # Errors
+/// Returns `AppError::Config` if the address is invalid or cannot be bound
+pub async fn serve(
+    config: &ApiConfig,
+    repository: Arc<Repository>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<()> {
+    let address: SocketAddr = config.bind_address.parse().map_err(|e| {
+        AppError::Config(format!(
+            "Invalid api_server bind_address '{}': {}",
+            config.bind_address, e
+        ))
+    })?;
+    let listener = tokio::net::TcpListener::bind(address).await.map_err(|e| {
+        AppError::Config(format!("Failed to start API server on {}: {}", address, e))
+    })?;
+
+    info!("Serving HTTP API on http://{}", address);
+    axum::serve(listener, router(repository))
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// An error response, serialized as `{"error": "..."}`
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message,
+        }
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(e: AppError) -> Self {
+        match e {
+            AppError::InvalidData(message) => Self {
+                status: StatusCode::BAD_REQUEST,
+                message,
+            },
+            // Database and other internal errors are logged, not shown to clients
+            e => {
+                error!("API request failed: {}", e);
+                Self {
+                    status: StatusCode::INTERNAL_SERVER_ERROR,
+                    message: "Internal server error".to_string(),
+                }
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.message });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct StationsQuery {
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeQuery {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+}
+
+impl RangeQuery {
+    /// The requested range; `end` defaults to now and `start` to
+    /// `DEFAULT_RANGE_DAYS` before `end`
+    fn range(&self) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let end = self.end.unwrap_or_else(Utc::now);
+        let start = self
+            .start
+            .unwrap_or(end - Duration::days(DEFAULT_RANGE_DAYS));
+        if end < start {
+            return Err(AppError::InvalidData(format!(
+                "end ({}) is before start ({})",
+                end, start
+            )));
+        }
+        Ok((start, end))
+    }
+
+    fn limit(&self) -> Result<usize> {
+        match self.limit.unwrap_or(DEFAULT_OBSERVATION_LIMIT) {
+            limit @ 1..=MAX_OBSERVATION_LIMIT => Ok(limit),
+            limit => Err(AppError::InvalidData(format!(
+                "limit must be between 1 and {}, got {}",
+                MAX_OBSERVATION_LIMIT, limit
+            ))),
+        }
+    }
+}
+
+/// `GET /health` and `GET /healthz`: the health report, with 503 if the database is unreachable
+async fn health(State(repository): State<Arc<Repository>>) -> Response {
+    match repository.health_report().await {
+        Ok(report) => {
+            let status = if report.db_connected {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            (status, Json::<HealthReport>(report)).into_response()
+        }
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// `GET /api/v1/stations[?state=TX]`
+async fn list_stations(
+    State(repository): State<Arc<Repository>>,
+    Query(query): Query<StationsQuery>,
+) -> ApiResult<Vec<Station>> {
+    let stations = match query.state {
+        Some(state) => {
+            repository
+                .get_stations_by_state(&state.to_uppercase())
+                .await?
+        }
+        None => repository.list_stations().await?,
+    };
+    Ok(Json(stations))
+}
+
+/// `GET /api/v1/stations/{wbanno}`
+async fn get_station(
+    State(repository): State<Arc<Repository>>,
+    Path(wbanno): Path<Wbanno>,
+) -> ApiResult<Station> {
+    repository
+        .get_station(wbanno)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Station {} not found", wbanno)))
+}
+
+/// `GET /api/v1/stations/{wbanno}/observations?start=&end=&limit=`
+///
+/// Observations in `[start, end)`, oldest first.
+async fn get_observations(
+    State(repository): State<Arc<Repository>>,
+    Path(wbanno): Path<Wbanno>,
+    Query(query): Query<RangeQuery>,
+) -> ApiResult<Vec<Observation>> {
+    let (start, end) = query.range()?;
+    let limit = query.limit()?;
+    let observations = repository
+        .stream_observations(wbanno, start, end)
+        .take(limit)
+        .try_collect()
+        .await?;
+    Ok(Json(observations))
+}
+
+/// `GET /api/v1/stations/{wbanno}/daily?start=&end=`
+///
+/// Daily aggregates for `[start, end]`, oldest first.
+async fn get_daily_aggregates(
+    State(repository): State<Arc<Repository>>,
+    Path(wbanno): Path<Wbanno>,
+    Query(query): Query<RangeQuery>,
+) -> ApiResult<Vec<DailyAggregate>> {
+    let (start, end) = query.range()?;
+    let aggregates = repository.get_daily_aggregates(wbanno, start, end).await?;
+    Ok(Json(aggregates))
+}
@@ -0,0 +1,127 @@
+use crate::error::{AppError, Result};
+use crate::fetcher::{validate_url, Fetcher, FileInfo};
+use crate::scheduler::{FileTrigger, Scheduler};
+use std::path::Path;
+use tracing::{error, info};
+
+/// `processed_files.source` of files ingested by a backfill
+pub const BACKFILL_SOURCE: &str = "backfill";
+
+/// Re-ingests specific data files by URL, without scanning their years
+///
+/// For files a run skipped by mistake (e.g. a filter that was too narrow).
+/// Location filters and `skip_files` do not apply.
+#[derive(Debug, Clone)]
+pub struct BackfillJob {
+    urls: Vec<String>,
+}
+
+/// Outcome of one file in a `BackfillReport`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillFileResult {
+    pub url: String,
+    /// Observations stored, or why the file failed
+    pub result: std::result::Result<usize, String>,
+}
+
+/// Per-file results of `BackfillJob::run`, in input order
+#[derive(Debug, Clone, Default)]
+pub struct BackfillReport {
+    pub files: Vec<BackfillFileResult>,
+}
+
+impl BackfillReport {
+    pub fn succeeded(&self) -> usize {
+        self.files.iter().filter(|f| f.result.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.files.len() - self.succeeded()
+    }
+}
+
+impl BackfillJob {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self { urls }
+    }
+
+    /// Read URLs from a file, one per line
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn from_urls_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let urls = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(Self::new(urls))
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Download and store each file, recorded as one ingestion run
+    ///
+    /// Files are stored even if already processed and unchanged. A failed
+    /// file is reported and does not stop the others.
+    ///
+    /// # Errors
+    /// Returns error only if the ingestion run cannot be recorded
+    pub async fn run(&self, scheduler: &Scheduler) -> Result<BackfillReport> {
+        let fetcher = Fetcher::from_config(&scheduler.config().source)?;
+        let run = scheduler.repository().start_ingestion_run().await?;
+
+        let mut report = BackfillReport::default();
+        for url in &self.urls {
+            let result = backfill_file(scheduler, &fetcher, url, run.id).await;
+            match &result {
+                Ok(rows) => info!("Backfilled {} observations from {}", rows, url),
+                Err(e) => error!("Backfill of {} failed: {}", url, e),
+            }
+            report.files.push(BackfillFileResult {
+                url: url.clone(),
+                result: result.map_err(|e| e.to_string()),
+            });
+        }
+
+        scheduler
+            .repository()
+            .complete_ingestion_run(run.id)
+            .await?;
+        Ok(report)
+    }
+}
+
+async fn backfill_file(
+    scheduler: &Scheduler,
+    fetcher: &Fetcher,
+    url: &str,
+    run_id: i32,
+) -> Result<usize> {
+    let source = &scheduler.config().source;
+    // Checked up front; a rejected download would otherwise be recorded as skipped
+    validate_url(url, source.allow_http)?;
+    let file_info = FileInfo::from_url(url)
+        .filter(|f| source.product.is_data_file(&f.name))
+        .ok_or_else(|| {
+            AppError::InvalidData(format!(
+                "{} is not a {} data file URL",
+                url,
+                source.product.directory()
+            ))
+        })?;
+
+    // Without the stored record, a changed historical file is stored rather
+    // than rejected as suspicious
+    let rows = scheduler
+        .ingest_file_info(fetcher, &file_info, None, FileTrigger::Explicit, run_id)
+        .await?;
+    scheduler
+        .repository()
+        .set_processed_file_source(&file_info.name, BACKFILL_SOURCE)
+        .await?;
+    Ok(rows)
+}
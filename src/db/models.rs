@@ -1,6 +1,40 @@
-use chrono::{DateTime, Utc};
+use crate::error::{AppError, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Value of `processed_files.processing_status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingStatus {
+    /// Record created; observations not yet fully inserted
+    Processing,
+    Completed,
+    Failed,
+    /// File was not ingested (e.g. over the size limit, or no rows for the configured stations)
+    Skipped,
+}
+
+impl ProcessingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessingStatus::Processing => "processing",
+            ProcessingStatus::Completed => "completed",
+            ProcessingStatus::Failed => "failed",
+            ProcessingStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Final file status for `Repository::bulk_update_file_statuses`:
+/// (file_name, status, observations_inserted, observations_updated, parse_failures)
+pub type FileStatusUpdate = (String, ProcessingStatus, i32, i32, i32);
+
 #[derive(Debug, Clone, FromRow)]
 pub struct ProcessedFile {
     pub id: i32,
@@ -17,6 +51,34 @@ pub struct ProcessedFile {
     pub observations_updated: Option<i32>,
     pub parse_failures: Option<i32>,
     pub processing_status: Option<String>,
+    pub data_quality_score: Option<f64>,
+    pub file_size_bytes: Option<i64>,
+    /// `scheduler`, or `backfill` once re-ingested by a backfill
+    pub source: String,
+}
+
+/// A file processing failure recorded by the scheduler
+#[derive(Debug, Clone, FromRow)]
+pub struct IngestionError {
+    pub id: i32,
+    pub file_name: String,
+    pub error_message: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Number of earlier errors recorded for the same file; only the latest
+    /// error is kept
+    pub retry_count: i32,
+}
+
+/// A quality control flag set on an observation by an external system
+#[derive(Debug, Clone, FromRow)]
+pub struct ObservationFlag {
+    pub id: i64,
+    pub observation_id: i64,
+    pub flag_type: String,
+    pub severity: i32,
+    pub reason: String,
+    pub flagged_by: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +95,16 @@ pub struct NewProcessedFile {
     pub observations_updated: i32,
     pub parse_failures: i32,
     pub processing_status: String,
+    pub data_quality_score: Option<f64>,
+    pub file_size_bytes: Option<i64>,
+}
+
+/// One execution of `Scheduler::run_ingestion`
+#[derive(Debug, Clone, FromRow)]
+pub struct IngestionRun {
+    pub id: i32,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,29 +114,260 @@ pub struct InsertResult {
     pub total_rows_affected: usize,
 }
 
+/// Referential integrity counts from `Repository::check_foreign_key_integrity`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub orphaned_observations: i64,
+    pub orphaned_processed_files: i64,
+    pub stations_without_observations: i64,
+}
+
+impl IntegrityReport {
+    /// True when no integrity problems were found
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_observations == 0
+            && self.orphaned_processed_files == 0
+            && self.stations_without_observations == 0
+    }
+}
+
+/// Processed file counts and totals for one year and state
+#[derive(Debug, Clone, FromRow)]
+pub struct ProcessedFileSummary {
+    pub year: i32,
+    pub state: String,
+    pub total_files: i64,
+    pub completed_files: i64,
+    pub failed_files: i64,
+    pub skipped_files: i64,
+    /// Sum of `rows_processed`
+    pub total_observations: i64,
+    pub total_parse_failures: i64,
+}
+
+/// One station's observations summarized over a UTC day
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DailyAggregate {
+    pub date: NaiveDate,
+    /// Mean of `t_hr_avg`
+    pub t_avg: Option<f32>,
+    /// Highest `t_max`
+    pub t_max: Option<f32>,
+    /// Lowest `t_min`
+    pub t_min: Option<f32>,
+    /// Sum of `p_calc`
+    pub precip_total: Option<f32>,
+    /// Mean of `rh_hr_avg`
+    pub avg_rh: Option<f32>,
+    /// Mean of `solarad`
+    pub avg_solarad: Option<f32>,
+}
+
+/// A file's recorded row count compared with its stored observations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCountVerification {
+    /// `processed_files.rows_processed`
+    pub expected: i32,
+    /// Observations whose `source_file_id` is the file
+    pub actual: i64,
+    pub matches: bool,
+}
+
+/// A completed file whose recorded row count differs from its stored
+/// observations, from `Repository::find_file_count_mismatches`
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct FileCountMismatch {
+    pub file_name: String,
+    /// `processed_files.rows_processed`
+    pub expected: i32,
+    /// Observations, live or archived, whose `source_file_id` is the file
+    pub actual: i64,
+}
+
+/// NOAA station identifier (WBANNO)
+///
+/// Wraps the raw integer so station IDs can't be passed where a file ID or
+/// count is expected (or vice versa).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct Wbanno(pub i32);
+
+impl From<i32> for Wbanno {
+    fn from(value: i32) -> Self {
+        Wbanno(value)
+    }
+}
+
+impl From<Wbanno> for i32 {
+    fn from(value: Wbanno) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Wbanno {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for Wbanno {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(Wbanno)
+    }
+}
+
+/// System status summary from `Repository::health_report`
+///
+/// Serialized as JSON by the `health` command; field names are part of that
+/// output's contract with monitoring tools, so rename with care.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub db_connected: bool,
+    pub schema_version: Option<i64>,
+    pub total_stations: i64,
+    pub total_observations: i64,
+    pub latest_observation_at: Option<DateTime<Utc>>,
+    /// Oldest file still in the `processing` state
+    pub oldest_unprocessed_file: Option<String>,
+    pub files_in_error_state: i64,
+    pub pool_idle: u32,
+    pub pool_active: u32,
+}
+
+/// Aggregate statistics for one station from `Repository::get_observation_stats`
+#[derive(Debug, Clone, FromRow)]
+pub struct ObservationStats {
+    pub wbanno: Wbanno,
+    pub total_count: i64,
+    /// Fraction (0.0-1.0) of observations with a 5 cm soil moisture reading
+    pub complete_soil_pct: f64,
+    pub avg_t_hr_avg: Option<f32>,
+    pub min_t_min: Option<f32>,
+    pub max_t_max: Option<f32>,
+    pub total_precip: Option<f64>,
+    /// Whole days between the first and last observation
+    pub observation_span_days: Option<i64>,
+}
+
+/// Stored observations for one station from `Repository::get_data_coverage_summary`
+#[derive(Debug, Clone, FromRow)]
+pub struct StationCoverage {
+    pub wbanno: Wbanno,
+    pub station_name: Option<String>,
+    pub state: String,
+    pub observation_count: i64,
+    /// `None` if the station has no observations
+    pub earliest_utc: Option<DateTime<Utc>>,
+    pub latest_utc: Option<DateTime<Utc>>,
+}
+
+/// Fraction (0.0-1.0) of a station's observations with each sensor reporting
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorAvailability {
+    /// Hourly average air temperature (`t_hr_avg`)
+    pub temperature: f64,
+    /// Average solar radiation (`solarad`)
+    pub solar: f64,
+    /// Precipitation (`p_calc`)
+    pub precip: f64,
+    /// Average relative humidity (`rh_hr_avg`)
+    pub rh: f64,
+    /// 5 cm soil moisture
+    pub soil_moisture: f64,
+    /// 5 cm soil temperature
+    pub soil_temp: f64,
+}
+
+/// Health summary of a station's observation record
+#[derive(Debug, Clone)]
+pub struct StationStatistics {
+    pub wbanno: Wbanno,
+    pub total_records: i64,
+    /// First and last UTC year with observations
+    pub year_range: (i32, i32),
+    /// Fraction (0.0-1.0) of hours between the first and last observation
+    /// that have one
+    pub uptime_pct: f64,
+    pub sensors: SensorAvailability,
+}
+
+/// A jump in soil moisture between consecutive observations
+#[derive(Debug, Clone, FromRow)]
+pub struct AnomalyEvent {
+    pub utc_datetime: DateTime<Utc>,
+    pub previous: f32,
+    pub current: f32,
+    /// Magnitude of the change; compare `previous` and `current` for direction
+    pub delta: f32,
+}
+
+/// A run of missing hourly observations between two recorded observations
 #[derive(Debug, Clone, FromRow)]
+pub struct ObservationGap {
+    pub wbanno: Wbanno,
+    /// Last observation before the gap
+    pub gap_start: DateTime<Utc>,
+    /// First observation after the gap
+    pub gap_end: DateTime<Utc>,
+    /// Hourly observations missing between `gap_start` and `gap_end`
+    pub missing_hours: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Station {
-    pub wbanno: i32,
+    pub wbanno: Wbanno,
     pub name: Option<String>,
     pub state: String,
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
     pub first_seen: DateTime<Utc>,
+    pub elevation: Option<f64>,
+    pub commissioned_date: Option<NaiveDate>,
+    pub current_crx_version: Option<String>,
+    /// When the row last changed, maintained by a database trigger
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Mean Earth radius used for great-circle distances
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+impl Station {
+    /// Haversine distance in kilometres from this station to a point
+    ///
+    /// Returns None if the station has no recorded coordinates.
+    pub fn distance_km(&self, lat: f64, lon: f64) -> Option<f64> {
+        let (station_lat, station_lon) = (self.latitude?, self.longitude?);
+
+        let d_lat = (lat - station_lat).to_radians();
+        let d_lon = (lon - station_lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2)
+            + station_lat.to_radians().cos() * lat.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+        Some(2.0 * EARTH_RADIUS_KM * a.sqrt().asin())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct NewStation {
-    pub wbanno: i32,
+    pub wbanno: Wbanno,
     pub name: Option<String>,
     pub state: String,
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
+    pub elevation: Option<f64>,
+    pub commissioned_date: Option<NaiveDate>,
+    pub current_crx_version: Option<String>,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Observation {
     pub id: i64,
-    pub wbanno: i32,
+    pub wbanno: Wbanno,
     pub utc_datetime: DateTime<Utc>,
     pub lst_datetime: DateTime<Utc>,
     pub crx_version: Option<String>,
@@ -108,11 +411,175 @@ pub struct Observation {
 
     pub source_file_id: Option<i32>,
     pub created_at: DateTime<Utc>,
+    pub ingestion_run_id: Option<i32>,
+    pub ingested_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+/// Depths (cm) at which USCRN measures soil moisture and temperature
+pub const SOIL_DEPTHS_CM: [u8; 5] = [5, 10, 20, 50, 100];
+
+impl Observation {
+    /// Soil moisture (m^3/m^3) at `depth` cm
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if `depth` is not one of `SOIL_DEPTHS_CM`
+    pub fn soil_moisture_at_depth_cm(&self, depth: u8) -> Result<Option<f32>> {
+        match depth {
+            5 => Ok(self.soil_moisture_5),
+            10 => Ok(self.soil_moisture_10),
+            20 => Ok(self.soil_moisture_20),
+            50 => Ok(self.soil_moisture_50),
+            100 => Ok(self.soil_moisture_100),
+            _ => Err(unsupported_depth(depth)),
+        }
+    }
+
+    /// Soil temperature (Celsius) at `depth` cm
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if `depth` is not one of `SOIL_DEPTHS_CM`
+    pub fn soil_temp_at_depth_cm(&self, depth: u8) -> Result<Option<f32>> {
+        match depth {
+            5 => Ok(self.soil_temp_5),
+            10 => Ok(self.soil_temp_10),
+            20 => Ok(self.soil_temp_20),
+            50 => Ok(self.soil_temp_50),
+            100 => Ok(self.soil_temp_100),
+            _ => Err(unsupported_depth(depth)),
+        }
+    }
+}
+
+/// Observations column holding soil moisture at `depth` cm
+///
+/// # Errors
+/// Returns `AppError::InvalidData` if `depth` is not one of `SOIL_DEPTHS_CM`
+pub(crate) fn soil_moisture_column(depth: u8) -> Result<&'static str> {
+    match depth {
+        5 => Ok("soil_moisture_5"),
+        10 => Ok("soil_moisture_10"),
+        20 => Ok("soil_moisture_20"),
+        50 => Ok("soil_moisture_50"),
+        100 => Ok("soil_moisture_100"),
+        _ => Err(unsupported_depth(depth)),
+    }
+}
+
+fn unsupported_depth(depth: u8) -> AppError {
+    AppError::InvalidData(format!(
+        "Unsupported soil depth {} cm; expected one of {:?}",
+        depth, SOIL_DEPTHS_CM
+    ))
+}
+
+/// Column groups written by `Repository::export_observations_csv`
+///
+/// The default selects every group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportColumns {
+    /// `utc_datetime` and `lst_datetime`; required
+    pub timestamp: bool,
+    pub temperature: bool,
+    pub precipitation: bool,
+    pub solar: bool,
+    pub surface_temp: bool,
+    pub relative_humidity: bool,
+    pub soil_moisture: bool,
+    pub soil_temp: bool,
+}
+
+impl Default for ExportColumns {
+    fn default() -> Self {
+        Self {
+            timestamp: true,
+            temperature: true,
+            precipitation: true,
+            solar: true,
+            surface_temp: true,
+            relative_humidity: true,
+            soil_moisture: true,
+            soil_temp: true,
+        }
+    }
+}
+
+impl ExportColumns {
+    /// Observations columns for the selected groups, in table order
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if `timestamp` is not selected
+    pub fn column_names(&self) -> Result<Vec<&'static str>> {
+        if !self.timestamp {
+            return Err(AppError::InvalidData(
+                "Export must include the timestamp columns".to_string(),
+            ));
+        }
+
+        let groups: [(bool, &[&'static str]); 8] = [
+            (self.timestamp, &["utc_datetime", "lst_datetime"]),
+            (self.temperature, &["t_calc", "t_hr_avg", "t_max", "t_min"]),
+            (self.precipitation, &["p_calc"]),
+            (
+                self.solar,
+                &[
+                    "solarad",
+                    "solarad_flag",
+                    "solarad_max",
+                    "solarad_max_flag",
+                    "solarad_min",
+                    "solarad_min_flag",
+                ],
+            ),
+            (
+                self.surface_temp,
+                &[
+                    "sur_temp_type",
+                    "sur_temp",
+                    "sur_temp_flag",
+                    "sur_temp_max",
+                    "sur_temp_max_flag",
+                    "sur_temp_min",
+                    "sur_temp_min_flag",
+                ],
+            ),
+            (self.relative_humidity, &["rh_hr_avg", "rh_hr_avg_flag"]),
+            (
+                self.soil_moisture,
+                &[
+                    "soil_moisture_5",
+                    "soil_moisture_10",
+                    "soil_moisture_20",
+                    "soil_moisture_50",
+                    "soil_moisture_100",
+                ],
+            ),
+            (
+                self.soil_temp,
+                &[
+                    "soil_temp_5",
+                    "soil_temp_10",
+                    "soil_temp_20",
+                    "soil_temp_50",
+                    "soil_temp_100",
+                ],
+            ),
+        ];
+
+        Ok(groups
+            .into_iter()
+            .filter(|(selected, _)| *selected)
+            .flat_map(|(_, columns)| columns.iter().copied())
+            .collect())
+    }
+}
+
+/// An observation to insert
+///
+/// Also decoded from the row JSON published by the `notify_new_observation`
+/// trigger (see `Repository::listen_observations`).
+#[derive(Debug, Clone, Deserialize)]
 pub struct NewObservation {
-    pub wbanno: i32,
+    pub wbanno: Wbanno,
     pub utc_datetime: DateTime<Utc>,
     pub lst_datetime: DateTime<Utc>,
     pub crx_version: Option<String>,
@@ -154,5 +621,116 @@ pub struct NewObservation {
     pub soil_temp_50: Option<f32>,
     pub soil_temp_100: Option<f32>,
 
+    pub source_file_id: Option<i32>,
+    pub ingestion_run_id: Option<i32>,
+}
+
+impl NewObservation {
+    /// Whether every core meteorological reading is present
+    ///
+    /// Checks `t_hr_avg`, `t_max`, `t_min`, `rh_hr_avg`, `solarad` and `p_calc`.
+    pub fn is_complete(&self) -> bool {
+        self.t_hr_avg.is_some()
+            && self.t_max.is_some()
+            && self.t_min.is_some()
+            && self.rh_hr_avg.is_some()
+            && self.solarad.is_some()
+            && self.p_calc.is_some()
+    }
+
+    /// Whether soil moisture and temperature are present at all five depths
+    pub fn is_soil_complete(&self) -> bool {
+        [
+            self.soil_moisture_5,
+            self.soil_moisture_10,
+            self.soil_moisture_20,
+            self.soil_moisture_50,
+            self.soil_moisture_100,
+            self.soil_temp_5,
+            self.soil_temp_10,
+            self.soil_temp_20,
+            self.soil_temp_50,
+            self.soil_temp_100,
+        ]
+        .iter()
+        .all(Option::is_some)
+    }
+}
+
+/// A 5-minute observation from the subhourly01 product to insert
+#[derive(Debug, Clone)]
+pub struct NewSubhourlyObservation {
+    pub wbanno: Wbanno,
+    /// End of the 5-minute period
+    pub utc_datetime: DateTime<Utc>,
+    pub lst_datetime: DateTime<Utc>,
+    pub crx_version: Option<String>,
+
+    pub air_temperature: Option<f32>,
+    pub precipitation: Option<f32>,
+
+    pub solar_radiation: Option<f32>,
+    pub sr_flag: Option<i32>,
+
+    pub surface_temperature: Option<f32>,
+    pub st_type: Option<String>,
+    pub st_flag: Option<i32>,
+
+    pub relative_humidity: Option<f32>,
+    pub rh_flag: Option<i32>,
+
+    pub soil_moisture_5: Option<f32>,
+    pub soil_temperature_5: Option<f32>,
+
+    /// Leaf wetness sensor resistance in ohms; below 1000 indicates moisture
+    pub wetness: Option<i32>,
+    pub wet_flag: Option<i32>,
+
+    /// Wind speed at 1.5 m (m/s)
+    pub wind_1_5: Option<f32>,
+    pub wind_flag: Option<i32>,
+
+    pub source_file_id: Option<i32>,
+}
+
+/// A daily summary from the daily01 product to insert
+///
+/// Days are local standard time. `t_daily_mean` is (max + min) / 2, while
+/// `t_daily_avg` averages the day's 5-minute readings.
+#[derive(Debug, Clone)]
+pub struct NewDailyObservation {
+    pub wbanno: Wbanno,
+    pub lst_date: NaiveDate,
+    pub crx_version: Option<String>,
+
+    pub t_daily_max: Option<f32>,
+    pub t_daily_min: Option<f32>,
+    pub t_daily_mean: Option<f32>,
+    pub t_daily_avg: Option<f32>,
+    pub p_daily_calc: Option<f32>,
+    /// Total solar energy (MJ/m²)
+    pub solarad_daily: Option<f32>,
+
+    pub sur_temp_daily_type: Option<String>,
+    pub sur_temp_daily_max: Option<f32>,
+    pub sur_temp_daily_min: Option<f32>,
+    pub sur_temp_daily_avg: Option<f32>,
+
+    pub rh_daily_max: Option<f32>,
+    pub rh_daily_min: Option<f32>,
+    pub rh_daily_avg: Option<f32>,
+
+    pub soil_moisture_5_daily: Option<f32>,
+    pub soil_moisture_10_daily: Option<f32>,
+    pub soil_moisture_20_daily: Option<f32>,
+    pub soil_moisture_50_daily: Option<f32>,
+    pub soil_moisture_100_daily: Option<f32>,
+
+    pub soil_temp_5_daily: Option<f32>,
+    pub soil_temp_10_daily: Option<f32>,
+    pub soil_temp_20_daily: Option<f32>,
+    pub soil_temp_50_daily: Option<f32>,
+    pub soil_temp_100_daily: Option<f32>,
+
     pub source_file_id: Option<i32>,
 }
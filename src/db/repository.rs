@@ -1,26 +1,878 @@
+use crate::config::ConflictStrategy;
 use crate::db::models::{
-    InsertResult, NewObservation, NewProcessedFile, NewStation, ProcessedFile,
+    soil_moisture_column, AnomalyEvent, DailyAggregate, ExportColumns, FileCountMismatch,
+    FileCountVerification, FileStatusUpdate, HealthReport, IngestionError, IngestionRun,
+    InsertResult, IntegrityReport, NewDailyObservation, NewObservation, NewProcessedFile,
+    NewStation, NewSubhourlyObservation, Observation, ObservationFlag, ObservationGap,
+    ObservationStats, ProcessedFile, ProcessedFileSummary, SensorAvailability, Station,
+    StationCoverage, StationStatistics, Wbanno,
 };
-use crate::error::Result;
-use sqlx::PgPool;
-use tracing::{debug, info};
+use crate::error::{AppError, Result};
+use crate::metrics;
+use chrono::{DateTime, Duration, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgListener;
+use sqlx::{FromRow, PgPool, Row};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
+use tokio::sync::OnceCell;
+use tracing::{debug, info, warn};
+
+/// Migrations embedded from `./migrations` at compile time
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Default rows per round-trip for `stream_observations` and CSV export
+pub(crate) const DEFAULT_STREAM_CHUNK_SIZE: usize = 500;
+
+/// One keyset-paginated chunk of a station's observations, oldest first
+///
+/// Binds: $1 wbanno, $2 cursor time, $3 exclusive end, $4 whether the cursor
+/// time itself is included (true only for the first chunk), $5 chunk size,
+/// $6 highest quality flag accepted or NULL for any. `GREATEST` skips NULLs,
+/// so missing flags count as good (0) as in `get_observations_by_quality_flag`.
+const OBSERVATION_CHUNK_FILTER: &str = "\
+    WHERE wbanno = $1 AND utc_datetime < $3 \
+      AND (utc_datetime > $2 OR ($4 AND utc_datetime = $2)) \
+      AND ($6::INTEGER IS NULL OR COALESCE(GREATEST( \
+          solarad_flag, solarad_max_flag, solarad_min_flag, \
+          sur_temp_flag, sur_temp_max_flag, sur_temp_min_flag, rh_hr_avg_flag), 0) <= $6) \
+    ORDER BY utc_datetime \
+    LIMIT $5";
+
+/// Columns copied by `archive_observations`; `observations_archive` has the
+/// same columns, though not necessarily in the same order
+const ARCHIVED_OBSERVATION_COLUMNS: &[&str] = &[
+    "id",
+    "wbanno",
+    "utc_datetime",
+    "lst_datetime",
+    "crx_version",
+    "t_calc",
+    "t_hr_avg",
+    "t_max",
+    "t_min",
+    "p_calc",
+    "solarad",
+    "solarad_flag",
+    "solarad_max",
+    "solarad_max_flag",
+    "solarad_min",
+    "solarad_min_flag",
+    "sur_temp_type",
+    "sur_temp",
+    "sur_temp_flag",
+    "sur_temp_max",
+    "sur_temp_max_flag",
+    "sur_temp_min",
+    "sur_temp_min_flag",
+    "rh_hr_avg",
+    "rh_hr_avg_flag",
+    "soil_moisture_5",
+    "soil_moisture_10",
+    "soil_moisture_20",
+    "soil_moisture_50",
+    "soil_moisture_100",
+    "soil_temp_5",
+    "soil_temp_10",
+    "soil_temp_20",
+    "soil_temp_50",
+    "soil_temp_100",
+    "source_file_id",
+    "created_at",
+    "ingestion_run_id",
+    "ingested_at",
+];
+
+/// Channel the `notify_new_observation` trigger publishes inserted rows on
+const NEW_OBSERVATION_CHANNEL: &str = "new_observation";
+
+/// Default age after which cached years and states with data are queried again
+const DEFAULT_COVERAGE_CACHE_TTL_SECS: u64 = 300;
+
+/// Coverage listing and when it was queried, filled at most once
+type CoverageCacheCell<T> = OnceCell<(Instant, Vec<T>)>;
 
 pub struct Repository {
     pool: PgPool,
+    schema: Option<String>,
+    conflict_strategy: ConflictStrategy,
+    stream_chunk_size: usize,
+    /// Cached by `list_years_with_data`, swapped for a fresh cell once
+    /// older than `coverage_cache_ttl`
+    years_with_data: Mutex<Arc<CoverageCacheCell<i32>>>,
+    /// Cached by `list_states_with_data`, expiring like `years_with_data`
+    states_with_data: Mutex<Arc<CoverageCacheCell<String>>>,
+    /// How long years and states with data are served from cache
+    coverage_cache_ttl: StdDuration,
 }
 
 impl Repository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            schema: None,
+            conflict_strategy: ConflictStrategy::default(),
+            stream_chunk_size: DEFAULT_STREAM_CHUNK_SIZE,
+            years_with_data: Mutex::new(Arc::new(OnceCell::new())),
+            states_with_data: Mutex::new(Arc::new(OnceCell::new())),
+            coverage_cache_ttl: StdDuration::from_secs(DEFAULT_COVERAGE_CACHE_TTL_SECS),
+        }
+    }
+
+    /// Create `schema` before running migrations
+    ///
+    /// The pool's connections must already use it as their `search_path`
+    /// (see `DatabaseConfig::schema`) so unqualified table names resolve there.
+    /// The name must be a validated identifier; it is interpolated into SQL.
+    pub fn with_schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Handle observations already stored according to `strategy`
+    pub fn with_conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
+
+    /// Set how long years and states with data are cached before being queried again
+    pub fn with_coverage_cache_ttl(mut self, ttl: StdDuration) -> Self {
+        self.coverage_cache_ttl = ttl;
+        self
+    }
+
+    /// Fetch `chunk_size` rows per round-trip when streaming observations
+    pub fn with_stream_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.stream_chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Check the database is reachable
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
     }
 
     pub async fn run_migrations(&self) -> Result<()> {
         info!("Running database migrations...");
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
+        if let Some(schema) = &self.schema {
+            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", schema))
+                .execute(&self.pool)
+                .await?;
+        }
+        MIGRATOR.run(&self.pool).await?;
         info!("Database migrations completed");
         Ok(())
     }
 
+    /// Latest migration version applied to the database
+    ///
+    /// Returns None if migrations have never been run.
+    pub async fn get_applied_migration_version(&self) -> Result<Option<i64>> {
+        let table_exists =
+            sqlx::query_scalar::<_, bool>("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+
+        if !table_exists {
+            return Ok(None);
+        }
+
+        let version =
+            sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(version)
+    }
+
+    /// Latest migration version embedded in this binary
+    pub fn latest_local_migration_version() -> Option<i64> {
+        MIGRATOR.iter().map(|m| m.version).max()
+    }
+
+    /// Compare the database schema version with the embedded migrations
+    ///
+    /// Warns if the database is behind (e.g. migrations skipped on a read-only
+    /// replica). Errors if the database is ahead, since this binary's models
+    /// may not match a newer schema.
+    pub async fn check_migration_version(&self) -> Result<()> {
+        let applied = self.get_applied_migration_version().await?;
+        let local = Self::latest_local_migration_version();
+
+        match (applied, local) {
+            (Some(applied), Some(local)) if applied > local => {
+                Err(AppError::Database(sqlx::Error::Protocol(format!(
+                    "Database schema version {} is newer than this binary's latest migration {}. \
+                     Upgrade the ingestor before running against this database.",
+                    applied, local
+                ))))
+            }
+            (applied, Some(local)) if applied.unwrap_or(0) < local => {
+                warn!(
+                    "Database schema version {:?} is behind latest migration {}; \
+                     some queries may fail until migrations are applied",
+                    applied, local
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Summarize database connectivity, data volume and processing state
+    ///
+    /// An unreachable database is reported as `db_connected: false` with
+    /// zeroed counts rather than an error, so callers can always print a report.
+    pub async fn health_report(&self) -> Result<HealthReport> {
+        let pool_idle = self.pool.num_idle() as u32;
+        let pool_active = self.pool.size().saturating_sub(pool_idle);
+
+        if let Err(e) = self.ping().await {
+            warn!("Health check could not reach the database: {}", e);
+            return Ok(HealthReport {
+                pool_idle,
+                pool_active,
+                ..HealthReport::default()
+            });
+        }
+
+        let (
+            schema_version,
+            total_stations,
+            observations,
+            oldest_unprocessed_file,
+            files_in_error_state,
+        ) = tokio::join!(
+            self.get_applied_migration_version(),
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM stations").fetch_one(&self.pool),
+            sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+                "SELECT COUNT(*), MAX(utc_datetime) FROM observations",
+            )
+            .fetch_one(&self.pool),
+            sqlx::query_scalar::<_, String>(
+                "SELECT file_name FROM processed_files WHERE processing_status = 'processing' \
+                 ORDER BY processed_at LIMIT 1",
+            )
+            .fetch_optional(&self.pool),
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM processed_files WHERE processing_status = 'failed'",
+            )
+            .fetch_one(&self.pool),
+        );
+        let (total_observations, latest_observation_at) = observations?;
+
+        Ok(HealthReport {
+            db_connected: true,
+            schema_version: schema_version?,
+            total_stations: total_stations?,
+            total_observations,
+            latest_observation_at,
+            oldest_unprocessed_file: oldest_unprocessed_file?,
+            files_in_error_state: files_in_error_state?,
+            pool_idle,
+            pool_active,
+        })
+    }
+
+    /// Check referential integrity between observations, stations and processed files
+    ///
+    /// Intended for post-migration validation. Counts:
+    /// - observations whose station or source file no longer exists
+    /// - completed files with rows but no observations referencing them
+    /// - stations that have no observations
+    pub async fn check_foreign_key_integrity(&self) -> Result<IntegrityReport> {
+        let orphaned_observations = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM observations o
+            WHERE NOT EXISTS (SELECT 1 FROM stations s WHERE s.wbanno = o.wbanno)
+               OR (o.source_file_id IS NOT NULL
+                   AND NOT EXISTS (SELECT 1 FROM processed_files f WHERE f.id = o.source_file_id))
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let orphaned_processed_files = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM processed_files f
+            WHERE f.processing_status = 'completed'
+              AND f.rows_processed > 0
+              AND NOT EXISTS (SELECT 1 FROM observations o WHERE o.source_file_id = f.id)
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let stations_without_observations = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM stations s
+            WHERE NOT EXISTS (SELECT 1 FROM observations o WHERE o.wbanno = s.wbanno)
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(IntegrityReport {
+            orphaned_observations,
+            orphaned_processed_files,
+            stations_without_observations,
+        })
+    }
+
+    /// Get aggregate observation statistics for a station
+    ///
+    /// A station with no observations returns a zero count and `None` aggregates.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to summarize
+    pub async fn get_observation_stats(&self, wbanno: Wbanno) -> Result<ObservationStats> {
+        let stats = sqlx::query_as::<_, ObservationStats>(
+            r#"
+            SELECT
+                $1::int AS wbanno,
+                COUNT(*) AS total_count,
+                COALESCE(
+                    COUNT(*) FILTER (WHERE soil_moisture_5 IS NOT NULL)
+                        / NULLIF(COUNT(*), 0)::float,
+                    0.0
+                ) AS complete_soil_pct,
+                AVG(t_hr_avg)::real AS avg_t_hr_avg,
+                MIN(t_min) AS min_t_min,
+                MAX(t_max) AS max_t_max,
+                SUM(p_calc)::float8 AS total_precip,
+                FLOOR(EXTRACT(EPOCH FROM (MAX(utc_datetime) - MIN(utc_datetime))) / 86400)::bigint
+                    AS observation_span_days
+            FROM observations
+            WHERE wbanno = $1
+            "#,
+        )
+        .bind(wbanno)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Summarize the observations stored for every station, ordered by WBANNO
+    ///
+    /// Stations without observations are included with a zero count.
+    pub async fn get_data_coverage_summary(&self) -> Result<Vec<StationCoverage>> {
+        let coverage = sqlx::query_as::<_, StationCoverage>(
+            r#"
+            SELECT
+                s.wbanno,
+                s.name AS station_name,
+                s.state,
+                COUNT(o.id) AS observation_count,
+                MIN(o.utc_datetime) AS earliest_utc,
+                MAX(o.utc_datetime) AS latest_utc
+            FROM stations s
+            LEFT JOIN observations o ON o.wbanno = s.wbanno
+            GROUP BY s.wbanno
+            ORDER BY s.wbanno
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(coverage)
+    }
+
+    /// Summarize a station's uptime and per-sensor data availability
+    ///
+    /// Uptime is estimated as `total_records / (hour_span + 1)`, where
+    /// `hour_span` is the number of hours between the first and last
+    /// observation.
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if the station has no observations
+    pub async fn compute_station_statistics(&self, wbanno: Wbanno) -> Result<StationStatistics> {
+        type Row = (
+            i64,
+            Option<i32>,
+            Option<i32>,
+            Option<i64>,
+            f64,
+            f64,
+            f64,
+            f64,
+            f64,
+            f64,
+        );
+
+        let row = sqlx::query_as::<_, Row>(
+            r#"
+            SELECT
+                COUNT(*),
+                MIN(EXTRACT(YEAR FROM utc_datetime AT TIME ZONE 'UTC'))::INT,
+                MAX(EXTRACT(YEAR FROM utc_datetime AT TIME ZONE 'UTC'))::INT,
+                (EXTRACT(EPOCH FROM (MAX(utc_datetime) - MIN(utc_datetime))) / 3600)::bigint,
+                COALESCE(COUNT(t_hr_avg) / NULLIF(COUNT(*), 0)::float, 0.0),
+                COALESCE(COUNT(solarad) / NULLIF(COUNT(*), 0)::float, 0.0),
+                COALESCE(COUNT(p_calc) / NULLIF(COUNT(*), 0)::float, 0.0),
+                COALESCE(COUNT(rh_hr_avg) / NULLIF(COUNT(*), 0)::float, 0.0),
+                COALESCE(COUNT(soil_moisture_5) / NULLIF(COUNT(*), 0)::float, 0.0),
+                COALESCE(COUNT(soil_temp_5) / NULLIF(COUNT(*), 0)::float, 0.0)
+            FROM observations
+            WHERE wbanno = $1
+            "#,
+        )
+        .bind(wbanno)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (total_records, first_year, last_year, hour_span, temp, solar, precip, rh, sm, st) =
+            row;
+        let (Some(first_year), Some(last_year), Some(hour_span)) =
+            (first_year, last_year, hour_span)
+        else {
+            return Err(AppError::InvalidData(format!(
+                "No observations for station {}",
+                wbanno
+            )));
+        };
+
+        Ok(StationStatistics {
+            wbanno,
+            total_records,
+            year_range: (first_year, last_year),
+            uptime_pct: total_records as f64 / (hour_span + 1) as f64,
+            sensors: SensorAvailability {
+                temperature: temp,
+                solar,
+                precip,
+                rh,
+                soil_moisture: sm,
+                soil_temp: st,
+            },
+        })
+    }
+
+    /// UTC years that have at least one observation, ascending
+    ///
+    /// Cached for `coverage_cache_ttl` (five minutes by default), since stored
+    /// years rarely change but a long-lived repository should still see new ones.
+    pub async fn list_years_with_data(&self) -> Result<Vec<i32>> {
+        let cache = self.current_coverage_cell(&self.years_with_data);
+        let (_, years) = cache
+            .get_or_try_init(|| async {
+                let years = sqlx::query_scalar::<_, i32>(
+                    r#"
+                    SELECT DISTINCT EXTRACT(YEAR FROM utc_datetime AT TIME ZONE 'UTC')::INT
+                    FROM observations
+                    ORDER BY 1
+                    "#,
+                )
+                .fetch_all(&self.pool)
+                .await?;
+                Ok::<_, AppError>((Instant::now(), years))
+            })
+            .await?;
+
+        Ok(years.clone())
+    }
+
+    /// States with at least one observed station, alphabetically
+    ///
+    /// Cached like `list_years_with_data`.
+    pub async fn list_states_with_data(&self) -> Result<Vec<String>> {
+        let cache = self.current_coverage_cell(&self.states_with_data);
+        let (_, states) = cache
+            .get_or_try_init(|| async {
+                let states = sqlx::query_scalar::<_, String>(
+                    r#"
+                    SELECT DISTINCT s.state
+                    FROM stations s
+                    WHERE EXISTS (SELECT 1 FROM observations o WHERE o.wbanno = s.wbanno)
+                    ORDER BY 1
+                    "#,
+                )
+                .fetch_all(&self.pool)
+                .await?;
+                Ok::<_, AppError>((Instant::now(), states))
+            })
+            .await?;
+
+        Ok(states.clone())
+    }
+
+    /// The cache's current cell, replaced with an empty one once expired
+    fn current_coverage_cell<T>(
+        &self,
+        cache: &Mutex<Arc<CoverageCacheCell<T>>>,
+    ) -> Arc<CoverageCacheCell<T>> {
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache
+            .get()
+            .is_some_and(|(queried_at, _)| queried_at.elapsed() >= self.coverage_cache_ttl)
+        {
+            *cache = Arc::new(OnceCell::new());
+        }
+        cache.clone()
+    }
+
+    /// Observation counts per UTC year and station state
+    ///
+    /// # Returns
+    /// (year, state, count) for every combination with data, ordered by year then state
+    pub async fn count_observations_by_year_and_state(&self) -> Result<Vec<(i32, String, i64)>> {
+        let counts = sqlx::query_as::<_, (i32, String, i64)>(
+            r#"
+            SELECT EXTRACT(YEAR FROM o.utc_datetime AT TIME ZONE 'UTC')::INT AS year,
+                   s.state,
+                   COUNT(*)
+            FROM observations o
+            JOIN stations s ON s.wbanno = o.wbanno
+            GROUP BY 1, 2
+            ORDER BY 1, 2
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Find consecutive observations where soil moisture changed sharply
+    ///
+    /// Compares each reading at `depth_cm` with the station's previous
+    /// observation, largest changes first.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to scan
+    /// * `depth_cm` - One of `SOIL_DEPTHS_CM`
+    /// * `threshold_delta` - Minimum absolute change (m^3/m^3) to report
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if `depth_cm` is not a measured depth
+    pub async fn detect_soil_moisture_anomalies(
+        &self,
+        wbanno: Wbanno,
+        depth_cm: u8,
+        threshold_delta: f32,
+    ) -> Result<Vec<AnomalyEvent>> {
+        // The column name comes from a fixed whitelist, never user input
+        let column = soil_moisture_column(depth_cm)?;
+
+        let events = sqlx::query_as::<_, AnomalyEvent>(&format!(
+            r#"
+            SELECT utc_datetime, previous, current, ABS(current - previous) AS delta
+            FROM (
+                SELECT
+                    utc_datetime,
+                    {column} AS current,
+                    LAG({column}) OVER (ORDER BY utc_datetime) AS previous
+                FROM observations
+                WHERE wbanno = $1
+            ) readings
+            WHERE previous IS NOT NULL
+              AND current IS NOT NULL
+              AND ABS(current - previous) > $2
+            ORDER BY delta DESC
+            "#
+        ))
+        .bind(wbanno)
+        .bind(threshold_delta)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Find runs of missing hourly observations within a UTC year
+    ///
+    /// Compares each observation with the station's previous one in the same
+    /// year, so gaps before the first or after the last observation are not
+    /// reported.
+    ///
+    /// # Arguments
+    /// * `year` - UTC year to scan
+    /// * `min_missing_hours` - Only gaps with more missing hours than this are returned
+    ///
+    /// # Returns
+    /// Gaps ordered by station, then start time
+    pub async fn find_observation_gaps(
+        &self,
+        year: i32,
+        min_missing_hours: u32,
+    ) -> Result<Vec<ObservationGap>> {
+        let gaps = sqlx::query_as::<_, ObservationGap>(
+            r#"
+            SELECT wbanno, gap_start, gap_end, missing_hours
+            FROM (
+                SELECT
+                    wbanno,
+                    LAG(utc_datetime) OVER (PARTITION BY wbanno ORDER BY utc_datetime) AS gap_start,
+                    utc_datetime AS gap_end,
+                    (EXTRACT(EPOCH FROM utc_datetime - LAG(utc_datetime)
+                        OVER (PARTITION BY wbanno ORDER BY utc_datetime)) / 3600)::BIGINT - 1
+                        AS missing_hours
+                FROM observations
+                WHERE utc_datetime >= make_timestamptz($1, 1, 1, 0, 0, 0, 'UTC')
+                  AND utc_datetime < make_timestamptz($1 + 1, 1, 1, 0, 0, 0, 'UTC')
+            ) consecutive
+            WHERE gap_start IS NOT NULL
+              AND missing_hours > $2
+            ORDER BY wbanno, gap_start
+            "#,
+        )
+        .bind(year)
+        .bind(min_missing_hours as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(gaps)
+    }
+
+    /// Get the times from `start` to `end`, `interval` apart, at which a
+    /// station has no observation
+    ///
+    /// Both bounds are inclusive. See `gap_detector::detect_gaps`, which
+    /// groups these into gaps.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to check
+    /// * `start` / `end` - The UTC time range to check
+    /// * `interval` - Time between expected observations; must be positive
+    pub async fn find_missing_observation_times(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Duration,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        let missing = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+            SELECT slot
+            FROM generate_series($2::TIMESTAMPTZ, $3::TIMESTAMPTZ, make_interval(secs => $4)) AS slot
+            LEFT JOIN observations o ON o.wbanno = $1 AND o.utc_datetime = slot
+            WHERE o.id IS NULL
+            ORDER BY slot
+            "#,
+        )
+        .bind(wbanno)
+        .bind(start)
+        .bind(end)
+        .bind(interval.num_milliseconds() as f64 / 1000.0)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(missing)
+    }
+
+    /// Write a station's observations in `[start, end)` as CSV
+    ///
+    /// The header row names the selected columns. Timestamps are written in
+    /// RFC 3339 (UTC) and missing values as empty fields. Rows are fetched
+    /// `stream_chunk_size` at a time, so large ranges are not held in memory.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to export
+    /// * `start` - Inclusive start of the UTC range
+    /// * `end` - Exclusive end of the UTC range
+    /// * `columns` - Column groups to include
+    /// * `writer` - Destination for the CSV text
+    ///
+    /// # Returns
+    /// Number of observation rows written, excluding the header
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if `columns` omits the timestamp
+    pub async fn export_observations_csv<W: std::io::Write>(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        columns: ExportColumns,
+        writer: &mut W,
+    ) -> Result<usize> {
+        // Column names come from ExportColumns' fixed list, never user input
+        let names = columns.column_names()?;
+        let select_list = names
+            .iter()
+            .map(|name| match *name {
+                "utc_datetime" | "lst_datetime" => {
+                    format!("to_char({name} AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"')")
+                }
+                _ => format!("{name}::TEXT"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(writer, "{}", names.join(","))?;
+
+        // The raw utc_datetime is selected last as the keyset cursor
+        let query = format!(
+            "SELECT {select_list}, utc_datetime FROM observations {OBSERVATION_CHUNK_FILTER}"
+        );
+
+        let mut written = 0;
+        let mut cursor = (start, true);
+        loop {
+            let rows = sqlx::query(&query)
+                .bind(wbanno)
+                .bind(cursor.0)
+                .bind(end)
+                .bind(cursor.1)
+                .bind(self.stream_chunk_size as i64)
+                .bind(None::<i32>)
+                .fetch_all(&self.pool)
+                .await?;
+
+            for row in &rows {
+                let fields = (0..names.len())
+                    .map(|i| row.try_get::<Option<String>, _>(i))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                let line = fields
+                    .into_iter()
+                    .map(Option::unwrap_or_default)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                writeln!(writer, "{}", line)?;
+            }
+            written += rows.len();
+
+            match rows.last() {
+                Some(last) if rows.len() == self.stream_chunk_size => {
+                    cursor = (last.try_get(names.len())?, false);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Stream a station's observations in `[start, end)`, oldest first
+    ///
+    /// Rows are fetched `stream_chunk_size` at a time (see
+    /// `with_stream_chunk_size`), so only one chunk is held in memory.
+    pub fn stream_observations(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Observation>> + '_ {
+        self.stream_observations_by_quality_flag(wbanno, None, start, end)
+    }
+
+    /// Stream a station's observations in `[start, end)`, oldest first,
+    /// keeping those whose quality flags are all at or below `max_flag`
+    ///
+    /// Flags are filtered as in `get_observations_by_quality_flag`, and rows
+    /// are fetched in chunks as in `stream_observations`. Without `max_flag`,
+    /// every observation is returned.
+    pub fn stream_observations_by_quality_flag(
+        &self,
+        wbanno: Wbanno,
+        max_flag: Option<i32>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Observation>> + '_ {
+        let query = format!("SELECT * FROM observations {OBSERVATION_CHUNK_FILTER}");
+
+        futures::stream::try_unfold(Some((start, true)), move |cursor| {
+            let query = query.clone();
+            async move {
+                let Some((after, inclusive)) = cursor else {
+                    return Ok(None);
+                };
+                let chunk = sqlx::query_as::<_, Observation>(&query)
+                    .bind(wbanno)
+                    .bind(after)
+                    .bind(end)
+                    .bind(inclusive)
+                    .bind(self.stream_chunk_size as i64)
+                    .bind(max_flag)
+                    .fetch_all(&self.pool)
+                    .await?;
+
+                let next = match chunk.last() {
+                    Some(last) if chunk.len() == self.stream_chunk_size => {
+                        Some((last.utc_datetime, false))
+                    }
+                    _ => None,
+                };
+                Ok::<_, AppError>(Some((chunk, next)))
+            }
+        })
+        .map_ok(|chunk| futures::stream::iter(chunk.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Backfill `source_file_id` for observations inserted before provenance tracking
+    ///
+    /// Each hourly data file holds one station's observations for one UTC
+    /// year, hour-ending, so from 01:00 on January 1 through 00:00 on the next
+    /// January 1. A file's station is the WBANNO of observations already
+    /// linked to it, or else the only station with the file's state and
+    /// station name. Unlinked observations of that station within the file's
+    /// year are assigned to it; when several files share a station and year,
+    /// the most recently processed one wins.
+    ///
+    /// # Arguments
+    /// * `dry_run` - Only count matching observations without updating them
+    ///
+    /// # Returns
+    /// The number of observations updated (or that would be updated)
+    pub async fn backfill_source_file_ids(&self, dry_run: bool) -> Result<u64> {
+        const FILE_STATIONS: &str = r#"
+            file_stations AS (
+                SELECT DISTINCT ON (wbanno, year) id, wbanno, year
+                FROM (
+                    SELECT f.id, f.year, f.processed_at, COALESCE(
+                        (SELECT o.wbanno FROM observations o WHERE o.source_file_id = f.id LIMIT 1),
+                        (SELECT MIN(s.wbanno) FROM stations s
+                         WHERE s.state = f.state AND s.name = f.station_name
+                         HAVING COUNT(*) = 1)
+                    ) AS wbanno
+                    FROM processed_files f
+                    WHERE f.file_name LIKE 'CRNH%'
+                ) files
+                WHERE wbanno IS NOT NULL
+                ORDER BY wbanno, year, processed_at DESC
+            )
+        "#;
+        const MATCHES: &str = r#"
+            o.source_file_id IS NULL
+            AND o.wbanno = f.wbanno
+            AND o.utc_datetime > make_timestamptz(f.year, 1, 1, 0, 0, 0, 'UTC')
+            AND o.utc_datetime <= make_timestamptz(f.year + 1, 1, 1, 0, 0, 0, 'UTC')
+        "#;
+
+        let count = if dry_run {
+            sqlx::query_scalar::<_, i64>(&format!(
+                "WITH {} SELECT COUNT(*) FROM observations o \
+                 WHERE EXISTS (SELECT 1 FROM file_stations f WHERE {})",
+                FILE_STATIONS, MATCHES
+            ))
+            .fetch_one(&self.pool)
+            .await?
+        } else {
+            sqlx::query_scalar::<_, i64>(&format!(
+                "WITH {}, updated AS (
+                    UPDATE observations o SET source_file_id = f.id
+                    FROM file_stations f WHERE {} RETURNING o.id
+                 )
+                 SELECT COUNT(*) FROM updated",
+                FILE_STATIONS, MATCHES
+            ))
+            .fetch_one(&self.pool)
+            .await?
+        };
+
+        info!(
+            "Provenance backfill{}: {} observations matched to source files",
+            if dry_run { " (dry run)" } else { "" },
+            count
+        );
+
+        Ok(count as u64)
+    }
+
     pub async fn is_file_processed(&self, file_name: &str) -> Result<bool> {
         let result = sqlx::query_scalar::<_, i64>(
             "SELECT COUNT(*) FROM processed_files WHERE file_name = $1",
@@ -43,22 +895,91 @@ impl Repository {
         Ok(file_names)
     }
 
+    /// Get the processed records for a set of files in one query
+    ///
+    /// Files with no record are absent from the returned map.
+    ///
+    /// # Arguments
+    /// * `file_names` - File names to look up
+    pub async fn batch_get_processed_files(
+        &self,
+        file_names: &[String],
+    ) -> Result<HashMap<String, ProcessedFile>> {
+        if file_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let files = sqlx::query_as::<_, ProcessedFile>(
+            "SELECT * FROM processed_files WHERE file_name = ANY($1::text[])",
+        )
+        .bind(file_names)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|f| (f.file_name.clone(), f))
+            .collect())
+    }
+
+    /// Record how a processed file was ingested, e.g. `backfill::BACKFILL_SOURCE`
+    ///
+    /// Later runs that re-process the file leave the source alone.
+    pub async fn set_processed_file_source(&self, file_name: &str, source: &str) -> Result<()> {
+        sqlx::query("UPDATE processed_files SET source = $2 WHERE file_name = $1")
+            .bind(file_name)
+            .bind(source)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get processed files for a year that are missing from the remote listing
+    ///
+    /// # Arguments
+    /// * `year` - The year to check
+    /// * `remote_file_names` - File names currently listed on the NOAA server
+    pub async fn get_files_not_in_remote(
+        &self,
+        year: i32,
+        remote_file_names: &[String],
+    ) -> Result<Vec<ProcessedFile>> {
+        let files = sqlx::query_as::<_, ProcessedFile>(
+            r#"
+            SELECT * FROM processed_files
+            WHERE year = $1
+              AND file_name NOT IN (SELECT UNNEST($2::text[]))
+            ORDER BY file_name
+            "#,
+        )
+        .bind(year)
+        .bind(remote_file_names)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
     pub async fn mark_file_processed(&self, file: NewProcessedFile) -> Result<i32> {
         let id = sqlx::query_scalar::<_, i32>(
             r#"
             INSERT INTO processed_files
                 (file_name, file_url, year, state, station_name, last_modified,
                  rows_processed, file_hash, observations_inserted, observations_updated,
-                 parse_failures, processing_status)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                 parse_failures, processing_status, data_quality_score, file_size_bytes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             ON CONFLICT (file_name) DO UPDATE SET
                 rows_processed = EXCLUDED.rows_processed,
                 observations_inserted = EXCLUDED.observations_inserted,
                 observations_updated = EXCLUDED.observations_updated,
                 parse_failures = EXCLUDED.parse_failures,
                 processing_status = EXCLUDED.processing_status,
+                data_quality_score = EXCLUDED.data_quality_score,
                 processed_at = NOW(),
-                file_hash = EXCLUDED.file_hash
+                file_hash = EXCLUDED.file_hash,
+                file_size_bytes = EXCLUDED.file_size_bytes,
+                last_modified = EXCLUDED.last_modified
             RETURNING id
             "#,
         )
@@ -74,21 +995,394 @@ impl Repository {
         .bind(file.observations_updated)
         .bind(file.parse_failures)
         .bind(&file.processing_status)
+        .bind(file.data_quality_score)
+        .bind(file.file_size_bytes)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Check whether a previously processed file had different content
+    ///
+    /// # Returns
+    /// The stored hash if it differs from `expected_hash`, or None if the file
+    /// is new, has no stored hash, or matches
+    pub async fn check_file_hash_collision(
+        &self,
+        file_name: &str,
+        expected_hash: &str,
+    ) -> Result<Option<String>> {
+        let stored = sqlx::query_scalar::<_, String>(
+            "SELECT file_hash FROM processed_files \
+             WHERE file_name = $1 AND file_hash IS NOT NULL AND file_hash <> $2",
+        )
+        .bind(file_name)
+        .bind(expected_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(stored)
+    }
+
+    /// Apply final statuses and counts to many processed files in one statement
+    ///
+    /// Files without an existing `processed_files` row are ignored.
+    ///
+    /// # Arguments
+    /// * `updates` - (file_name, status, observations_inserted, observations_updated, parse_failures)
+    pub async fn bulk_update_file_statuses(&self, updates: &[FileStatusUpdate]) -> Result<()> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let mut file_names = Vec::with_capacity(updates.len());
+        let mut statuses = Vec::with_capacity(updates.len());
+        let mut inserted = Vec::with_capacity(updates.len());
+        let mut updated = Vec::with_capacity(updates.len());
+        let mut parse_failures = Vec::with_capacity(updates.len());
+        for (file_name, status, ins, upd, failures) in updates {
+            file_names.push(file_name.as_str());
+            statuses.push(status.as_str());
+            inserted.push(*ins);
+            updated.push(*upd);
+            parse_failures.push(*failures);
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE processed_files AS f SET
+                processing_status = u.status,
+                observations_inserted = u.inserted,
+                observations_updated = u.updated,
+                parse_failures = u.parse_failures,
+                processed_at = NOW()
+            FROM UNNEST($1::text[], $2::text[], $3::int[], $4::int[], $5::int[])
+                AS u(file_name, status, inserted, updated, parse_failures)
+            WHERE f.file_name = u.file_name
+            "#,
+        )
+        .bind(&file_names)
+        .bind(&statuses)
+        .bind(&inserted)
+        .bind(&updated)
+        .bind(&parse_failures)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "Updated status for {} of {} processed files",
+            result.rows_affected(),
+            updates.len()
+        );
+
+        Ok(())
+    }
+
+    pub async fn get_processed_file(&self, file_name: &str) -> Result<Option<ProcessedFile>> {
+        let result = sqlx::query_as::<_, ProcessedFile>(
+            "SELECT * FROM processed_files WHERE file_name = $1",
+        )
+        .bind(file_name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Names of every processed file, alphabetically
+    pub async fn list_processed_file_names(&self) -> Result<Vec<String>> {
+        let file_names =
+            sqlx::query_scalar::<_, String>("SELECT file_name FROM processed_files ORDER BY 1")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(file_names)
+    }
+
+    /// Compare a file's `rows_processed` with the observations stored from it
+    ///
+    /// A mismatch means rows were lost or overwritten after the file was
+    /// recorded. Current-year files processed with `reprocess_window_hours`
+    /// record only the rows in the window, so they can hold more observations
+    /// than `rows_processed`.
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if no file is recorded as `file_name`
+    pub async fn verify_file_observation_count(
+        &self,
+        file_name: &str,
+    ) -> Result<FileCountVerification> {
+        let (file_id, expected) = sqlx::query_as::<_, (i32, i32)>(
+            "SELECT id, rows_processed FROM processed_files WHERE file_name = $1",
+        )
+        .bind(file_name)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::InvalidData(format!("No processed file named {}", file_name)))?;
+
+        let actual = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM observations WHERE source_file_id = $1",
+        )
+        .bind(file_id)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(id)
-    }
+        Ok(FileCountVerification {
+            expected,
+            actual,
+            matches: i64::from(expected) == actual,
+        })
+    }
+
+    /// Find completed hourly02 files whose `rows_processed` differs from the
+    /// observations stored from them, in one query
+    ///
+    /// Archived observations are counted. Files whose rows may have been
+    /// removed by retention (any part of their year before `retention_cutoff`)
+    /// are not checked. With `reprocess_window`, current-year files record
+    /// only the rows in the window, so holding more observations than that is
+    /// not a mismatch. subhourly01 and daily01 files store their rows in other
+    /// tables and are not checked.
+    ///
+    /// # Returns
+    /// Mismatched files by name
+    pub async fn find_file_count_mismatches(
+        &self,
+        retention_cutoff: Option<DateTime<Utc>>,
+        reprocess_window: bool,
+    ) -> Result<Vec<FileCountMismatch>> {
+        let mismatches = sqlx::query_as::<_, FileCountMismatch>(
+            r#"
+            WITH counts AS (
+                SELECT source_file_id, COUNT(*) AS stored
+                FROM (
+                    SELECT source_file_id FROM observations
+                    UNION ALL
+                    SELECT source_file_id FROM observations_archive
+                ) o
+                WHERE source_file_id IS NOT NULL
+                GROUP BY source_file_id
+            )
+            SELECT f.file_name, f.rows_processed AS expected, COALESCE(c.stored, 0) AS actual
+            FROM processed_files f
+            LEFT JOIN counts c ON c.source_file_id = f.id
+            WHERE f.processing_status = 'completed'
+              AND f.file_name LIKE 'CRNH%'
+              -- A year's file starts with the last hours of the previous UTC year
+              AND ($1::timestamptz IS NULL
+                   OR make_timestamptz(f.year, 1, 1, 0, 0, 0, 'UTC') - INTERVAL '1 day' >= $1)
+              AND COALESCE(c.stored, 0) <> f.rows_processed
+              AND NOT ($2 AND f.year = EXTRACT(YEAR FROM NOW())::INT
+                       AND COALESCE(c.stored, 0) > f.rows_processed)
+            ORDER BY f.file_name
+            "#,
+        )
+        .bind(retention_cutoff)
+        .bind(reprocess_window)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(mismatches)
+    }
+
+    /// Get files whose processing failed, oldest first
+    ///
+    /// Files recorded as `skipped` (too large, or no rows for the configured
+    /// stations) are not failures and are excluded.
+    pub async fn get_failed_processed_files(&self) -> Result<Vec<ProcessedFile>> {
+        let files = sqlx::query_as::<_, ProcessedFile>(
+            "SELECT * FROM processed_files WHERE processing_status = 'failed' ORDER BY processed_at",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// Get every file not recorded as completed, oldest first
+    ///
+    /// Unlike `get_failed_processed_files`, this includes skipped files and
+    /// files left `processing` by an interrupted run. With `since`, only files
+    /// processed after it are returned.
+    pub async fn get_failed_files(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ProcessedFile>> {
+        let files = sqlx::query_as::<_, ProcessedFile>(
+            r#"
+            SELECT * FROM processed_files
+            WHERE processing_status IS DISTINCT FROM 'completed'
+              AND ($1::timestamptz IS NULL OR processed_at > $1)
+            ORDER BY processed_at
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// Names of the files whose processing failed, to re-attempt on the next run
+    ///
+    /// Skipped files are excluded since re-attempting them would skip them again.
+    pub async fn retry_failed_files(&self) -> Result<Vec<String>> {
+        let file_names = sqlx::query_scalar::<_, String>(
+            "SELECT file_name FROM processed_files WHERE processing_status = 'failed' \
+             ORDER BY file_name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(file_names)
+    }
+
+    /// Summarize processed files by year and state, newest year first
+    ///
+    /// Files still marked `processing` count towards `total_files` only.
+    pub async fn get_processed_files_summary(&self) -> Result<Vec<ProcessedFileSummary>> {
+        let summary = sqlx::query_as::<_, ProcessedFileSummary>(
+            r#"
+            SELECT
+                year,
+                state,
+                COUNT(*) AS total_files,
+                COUNT(*) FILTER (WHERE processing_status = 'completed') AS completed_files,
+                COUNT(*) FILTER (WHERE processing_status = 'failed') AS failed_files,
+                COUNT(*) FILTER (WHERE processing_status = 'skipped') AS skipped_files,
+                COALESCE(SUM(rows_processed), 0)::bigint AS total_observations,
+                COALESCE(SUM(parse_failures), 0)::bigint AS total_parse_failures
+            FROM processed_files
+            GROUP BY year, state
+            ORDER BY year DESC, state
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summary)
+    }
+
+    /// Get the recorded sizes of a year's files, largest first
+    ///
+    /// Files processed before sizes were tracked are omitted.
+    ///
+    /// # Returns
+    /// (file_name, file_size_bytes) pairs
+    pub async fn get_file_size_trends(&self, year: i32) -> Result<Vec<(String, i64)>> {
+        let sizes = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT file_name, file_size_bytes FROM processed_files
+            WHERE year = $1 AND file_size_bytes IS NOT NULL
+            ORDER BY file_size_bytes DESC, file_name
+            "#,
+        )
+        .bind(year)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sizes)
+    }
+
+    /// Get the files with the lowest data quality score for triage
+    ///
+    /// Files without a score are listed last.
+    pub async fn get_lowest_quality_files(&self, limit: i64) -> Result<Vec<ProcessedFile>> {
+        let files = sqlx::query_as::<_, ProcessedFile>(
+            "SELECT * FROM processed_files ORDER BY data_quality_score ASC NULLS LAST LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// Get a single station by WBANNO
+    ///
+    /// # Returns
+    /// The station, or None if it has never been recorded
+    pub async fn get_station(&self, wbanno: Wbanno) -> Result<Option<Station>> {
+        let station = sqlx::query_as::<_, Station>("SELECT * FROM stations WHERE wbanno = $1")
+            .bind(wbanno)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(station)
+    }
+
+    /// Get all stations, ordered by WBANNO
+    pub async fn list_stations(&self) -> Result<Vec<Station>> {
+        let stations = sqlx::query_as::<_, Station>("SELECT * FROM stations ORDER BY wbanno")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(stations)
+    }
+
+    /// Get all stations in a state, ordered by WBANNO
+    ///
+    /// # Arguments
+    /// * `state` - Two-letter state code as it appears in file names (e.g. "NC")
+    pub async fn get_stations_by_state(&self, state: &str) -> Result<Vec<Station>> {
+        let stations =
+            sqlx::query_as::<_, Station>("SELECT * FROM stations WHERE state = $1 ORDER BY wbanno")
+                .bind(state)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(stations)
+    }
+
+    /// Get stations whose metadata changed after `since`, oldest change first
+    ///
+    /// Upserts that leave a station unchanged do not count as changes.
+    pub async fn get_stations_updated_since(&self, since: DateTime<Utc>) -> Result<Vec<Station>> {
+        let stations = sqlx::query_as::<_, Station>(
+            "SELECT * FROM stations WHERE updated_at > $1 ORDER BY updated_at, wbanno",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stations)
+    }
+
+    /// Find stations within `radius_km` of a point, nearest first
+    ///
+    /// # Arguments
+    /// * `lat` / `lon` - The point to search from, in decimal degrees
+    /// * `radius_km` - Maximum distance to include
+    /// * `limit` - Maximum number of stations to return
+    ///
+    /// # Returns
+    /// Each matching station with its distance in kilometres
+    pub async fn find_nearest_stations(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+        limit: usize,
+    ) -> Result<Vec<(Station, f64)>> {
+        let stations = sqlx::query_as::<_, Station>(
+            "SELECT * FROM stations WHERE latitude IS NOT NULL AND longitude IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut nearby: Vec<(Station, f64)> = stations
+            .into_iter()
+            .filter_map(|station| {
+                let distance = station.distance_km(lat, lon)?;
+                (distance <= radius_km).then_some((station, distance))
+            })
+            .collect();
 
-    pub async fn get_processed_file(&self, file_name: &str) -> Result<Option<ProcessedFile>> {
-        let result = sqlx::query_as::<_, ProcessedFile>(
-            "SELECT * FROM processed_files WHERE file_name = $1",
-        )
-        .bind(file_name)
-        .fetch_optional(&self.pool)
-        .await?;
+        nearby.sort_by(|a, b| a.1.total_cmp(&b.1));
+        nearby.truncate(limit);
 
-        Ok(result)
+        Ok(nearby)
     }
 
     /// Upsert a single station into the database
@@ -97,12 +1391,18 @@ impl Repository {
     pub async fn upsert_station(&self, station: NewStation) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO stations (wbanno, name, state, latitude, longitude)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO stations (
+                wbanno, name, state, latitude, longitude, elevation, commissioned_date,
+                current_crx_version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             ON CONFLICT (wbanno) DO UPDATE SET
                 name = COALESCE(EXCLUDED.name, stations.name),
                 latitude = COALESCE(EXCLUDED.latitude, stations.latitude),
-                longitude = COALESCE(EXCLUDED.longitude, stations.longitude)
+                longitude = COALESCE(EXCLUDED.longitude, stations.longitude),
+                elevation = COALESCE(EXCLUDED.elevation, stations.elevation),
+                commissioned_date = COALESCE(EXCLUDED.commissioned_date, stations.commissioned_date),
+                current_crx_version = COALESCE(EXCLUDED.current_crx_version, stations.current_crx_version)
             "#,
         )
         .bind(station.wbanno)
@@ -110,6 +1410,39 @@ impl Repository {
         .bind(&station.state)
         .bind(station.latitude)
         .bind(station.longitude)
+        .bind(station.elevation)
+        .bind(station.commissioned_date)
+        .bind(&station.current_crx_version)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the logger firmware version a station currently reports
+    ///
+    /// The version is left unchanged if the station already has observations
+    /// after `as_of`, so re-ingesting an older file cannot roll it back.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to update
+    /// * `version` - The station's `crx_version` as of `as_of`
+    /// * `as_of` - Time of the latest observation reporting `version`
+    pub async fn update_station_crx_version(
+        &self,
+        wbanno: Wbanno,
+        version: &str,
+        as_of: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE stations SET current_crx_version = $2 \
+             WHERE wbanno = $1 AND NOT EXISTS ( \
+                 SELECT 1 FROM observations WHERE wbanno = $1 AND utc_datetime > $3 \
+             )",
+        )
+        .bind(wbanno)
+        .bind(version)
+        .bind(as_of)
         .execute(&self.pool)
         .await?;
 
@@ -126,7 +1459,8 @@ impl Repository {
         }
 
         let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT INTO stations (wbanno, name, state, latitude, longitude) ",
+            "INSERT INTO stations (wbanno, name, state, latitude, longitude, elevation, \
+             commissioned_date, current_crx_version) ",
         );
 
         query_builder.push_values(stations, |mut b, station| {
@@ -134,14 +1468,20 @@ impl Repository {
                 .push_bind(&station.name)
                 .push_bind(&station.state)
                 .push_bind(station.latitude)
-                .push_bind(station.longitude);
+                .push_bind(station.longitude)
+                .push_bind(station.elevation)
+                .push_bind(station.commissioned_date)
+                .push_bind(&station.current_crx_version);
         });
 
         query_builder.push(
             " ON CONFLICT (wbanno) DO UPDATE SET \
             name = COALESCE(EXCLUDED.name, stations.name), \
             latitude = COALESCE(EXCLUDED.latitude, stations.latitude), \
-            longitude = COALESCE(EXCLUDED.longitude, stations.longitude)",
+            longitude = COALESCE(EXCLUDED.longitude, stations.longitude), \
+            elevation = COALESCE(EXCLUDED.elevation, stations.elevation), \
+            commissioned_date = COALESCE(EXCLUDED.commissioned_date, stations.commissioned_date), \
+            current_crx_version = COALESCE(EXCLUDED.current_crx_version, stations.current_crx_version)",
         );
 
         query_builder.build().execute(&self.pool).await?;
@@ -149,24 +1489,40 @@ impl Repository {
         Ok(())
     }
 
+    /// Set `source_file_id` on each observation before insertion
+    ///
+    /// Keeps the in-memory observations consistent with what
+    /// `insert_observations` will store for them.
+    pub fn prepare_observations(
+        mut observations: Vec<NewObservation>,
+        source_file_id: i32,
+    ) -> Vec<NewObservation> {
+        for obs in &mut observations {
+            obs.source_file_id = Some(source_file_id);
+        }
+        observations
+    }
+
     /// Insert or update observations in batch
     ///
-    /// Uses PostgreSQL's ON CONFLICT to upsert observations efficiently.
+    /// Uses PostgreSQL's ON CONFLICT to upsert observations efficiently, with
+    /// the conflict target and action chosen by the repository's
+    /// `ConflictStrategy`.
     /// Processes in batches of 1000 to avoid query size limits.
     ///
     /// # Arguments
     /// * `observations` - Slice of observations to insert/update
     /// * `source_file_id` - ID of the processed file these observations came from
+    /// * `ingestion_run_id` - ID of the ingestion run writing these observations, if any
     ///
     /// # Returns
-    /// InsertResult with the total number of rows affected
-    ///
-    /// Note: PostgreSQL's ON CONFLICT doesn't distinguish between inserts and updates
-    /// in rows_affected, so we report total_rows_affected for both fields.
+    /// InsertResult with the rows newly inserted, the existing rows updated,
+    /// and their total. Rows an `InsertIgnore` strategy skipped are in neither.
     pub async fn insert_observations(
         &self,
         observations: &[NewObservation],
         source_file_id: i32,
+        ingestion_run_id: Option<i32>,
     ) -> Result<InsertResult> {
         if observations.is_empty() {
             return Ok(InsertResult {
@@ -176,18 +1532,30 @@ impl Repository {
             });
         }
 
-        let mut total_rows_affected = 0;
+        let mut inserted = 0;
+        let mut updated = 0;
         let mut tx = self.pool.begin().await?;
 
         // Process in batches of 1000 to avoid query size limits
         const BATCH_SIZE: usize = 1000;
+        let batch_count = observations.len().div_ceil(BATCH_SIZE);
+        let started = std::time::Instant::now();
 
         for (batch_idx, chunk) in observations.chunks(BATCH_SIZE).enumerate() {
+            // Estimate from the average time of the batches already inserted
+            let elapsed = started.elapsed();
+            let remaining = if batch_idx == 0 {
+                std::time::Duration::ZERO
+            } else {
+                elapsed / batch_idx as u32 * (batch_count - batch_idx) as u32
+            };
             debug!(
-                "Inserting batch {}/{} ({} observations)",
+                "Inserting batch {}/{}: {} obs, elapsed={:.1}s, est remaining={:.1}s",
                 batch_idx + 1,
-                observations.len().div_ceil(BATCH_SIZE),
-                chunk.len()
+                batch_count,
+                chunk.len(),
+                elapsed.as_secs_f64(),
+                remaining.as_secs_f64()
             );
 
             let mut query_builder = sqlx::QueryBuilder::new(
@@ -200,7 +1568,7 @@ impl Repository {
                     rh_hr_avg, rh_hr_avg_flag,
                     soil_moisture_5, soil_moisture_10, soil_moisture_20, soil_moisture_50, soil_moisture_100,
                     soil_temp_5, soil_temp_10, soil_temp_20, soil_temp_50, soil_temp_100,
-                    source_file_id
+                    source_file_id, ingestion_run_id
                 ) "
             );
 
@@ -239,6 +1607,119 @@ impl Repository {
                     .push_bind(obs.soil_temp_20)
                     .push_bind(obs.soil_temp_50)
                     .push_bind(obs.soil_temp_100)
+                    .push_bind(source_file_id)
+                    .push_bind(ingestion_run_id);
+            });
+
+            query_builder.push(self.conflict_strategy.on_conflict_clause());
+            if self.conflict_strategy != ConflictStrategy::InsertIgnore {
+                query_builder.push(
+                    "lst_datetime = EXCLUDED.lst_datetime, \
+                    crx_version = EXCLUDED.crx_version, \
+                    t_calc = EXCLUDED.t_calc, \
+                    t_hr_avg = EXCLUDED.t_hr_avg, \
+                    t_max = EXCLUDED.t_max, \
+                    t_min = EXCLUDED.t_min, \
+                    p_calc = EXCLUDED.p_calc, \
+                    solarad = EXCLUDED.solarad, \
+                    solarad_flag = EXCLUDED.solarad_flag, \
+                    solarad_max = EXCLUDED.solarad_max, \
+                    solarad_max_flag = EXCLUDED.solarad_max_flag, \
+                    solarad_min = EXCLUDED.solarad_min, \
+                    solarad_min_flag = EXCLUDED.solarad_min_flag, \
+                    sur_temp_type = EXCLUDED.sur_temp_type, \
+                    sur_temp = EXCLUDED.sur_temp, \
+                    sur_temp_flag = EXCLUDED.sur_temp_flag, \
+                    sur_temp_max = EXCLUDED.sur_temp_max, \
+                    sur_temp_max_flag = EXCLUDED.sur_temp_max_flag, \
+                    sur_temp_min = EXCLUDED.sur_temp_min, \
+                    sur_temp_min_flag = EXCLUDED.sur_temp_min_flag, \
+                    rh_hr_avg = EXCLUDED.rh_hr_avg, \
+                    rh_hr_avg_flag = EXCLUDED.rh_hr_avg_flag, \
+                    soil_moisture_5 = EXCLUDED.soil_moisture_5, \
+                    soil_moisture_10 = EXCLUDED.soil_moisture_10, \
+                    soil_moisture_20 = EXCLUDED.soil_moisture_20, \
+                    soil_moisture_50 = EXCLUDED.soil_moisture_50, \
+                    soil_moisture_100 = EXCLUDED.soil_moisture_100, \
+                    soil_temp_5 = EXCLUDED.soil_temp_5, \
+                    soil_temp_10 = EXCLUDED.soil_temp_10, \
+                    soil_temp_20 = EXCLUDED.soil_temp_20, \
+                    soil_temp_50 = EXCLUDED.soil_temp_50, \
+                    soil_temp_100 = EXCLUDED.soil_temp_100, \
+                    source_file_id = EXCLUDED.source_file_id, \
+                    ingestion_run_id = EXCLUDED.ingestion_run_id, \
+                    ingested_at = NOW()",
+                );
+            }
+
+            let (batch_inserted, batch_updated) =
+                execute_counted_upsert(&mut query_builder, &mut tx).await?;
+            inserted += batch_inserted;
+            updated += batch_updated;
+        }
+
+        tx.commit().await?;
+        metrics::record_insert(started.elapsed(), inserted, updated);
+
+        Ok(InsertResult {
+            inserted,
+            updated,
+            total_rows_affected: inserted + updated,
+        })
+    }
+
+    /// Insert or update 5-minute observations in batch
+    ///
+    /// Upserts on (wbanno, utc_datetime) in batches of 1000, in a single
+    /// transaction. Every row is attributed to `source_file_id`.
+    ///
+    /// # Returns
+    /// The number of rows inserted or updated
+    pub async fn insert_subhourly_observations(
+        &self,
+        observations: &[NewSubhourlyObservation],
+        source_file_id: i32,
+    ) -> Result<usize> {
+        let mut inserted = 0;
+        let mut updated = 0;
+        let mut tx = self.pool.begin().await?;
+        let started = std::time::Instant::now();
+
+        for chunk in observations.chunks(1000) {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO subhourly_observations (
+                    wbanno, utc_datetime, lst_datetime, crx_version,
+                    air_temperature, precipitation,
+                    solar_radiation, sr_flag,
+                    surface_temperature, st_type, st_flag,
+                    relative_humidity, rh_flag,
+                    soil_moisture_5, soil_temperature_5,
+                    wetness, wet_flag,
+                    wind_1_5, wind_flag,
+                    source_file_id
+                ) ",
+            );
+
+            query_builder.push_values(chunk, |mut b, obs| {
+                b.push_bind(obs.wbanno)
+                    .push_bind(obs.utc_datetime)
+                    .push_bind(obs.lst_datetime)
+                    .push_bind(&obs.crx_version)
+                    .push_bind(obs.air_temperature)
+                    .push_bind(obs.precipitation)
+                    .push_bind(obs.solar_radiation)
+                    .push_bind(obs.sr_flag)
+                    .push_bind(obs.surface_temperature)
+                    .push_bind(&obs.st_type)
+                    .push_bind(obs.st_flag)
+                    .push_bind(obs.relative_humidity)
+                    .push_bind(obs.rh_flag)
+                    .push_bind(obs.soil_moisture_5)
+                    .push_bind(obs.soil_temperature_5)
+                    .push_bind(obs.wetness)
+                    .push_bind(obs.wet_flag)
+                    .push_bind(obs.wind_1_5)
+                    .push_bind(obs.wind_flag)
                     .push_bind(source_file_id);
             });
 
@@ -246,53 +1727,606 @@ impl Repository {
                 " ON CONFLICT (wbanno, utc_datetime) DO UPDATE SET \
                 lst_datetime = EXCLUDED.lst_datetime, \
                 crx_version = EXCLUDED.crx_version, \
-                t_calc = EXCLUDED.t_calc, \
-                t_hr_avg = EXCLUDED.t_hr_avg, \
-                t_max = EXCLUDED.t_max, \
-                t_min = EXCLUDED.t_min, \
-                p_calc = EXCLUDED.p_calc, \
-                solarad = EXCLUDED.solarad, \
-                solarad_flag = EXCLUDED.solarad_flag, \
-                solarad_max = EXCLUDED.solarad_max, \
-                solarad_max_flag = EXCLUDED.solarad_max_flag, \
-                solarad_min = EXCLUDED.solarad_min, \
-                solarad_min_flag = EXCLUDED.solarad_min_flag, \
-                sur_temp_type = EXCLUDED.sur_temp_type, \
-                sur_temp = EXCLUDED.sur_temp, \
-                sur_temp_flag = EXCLUDED.sur_temp_flag, \
-                sur_temp_max = EXCLUDED.sur_temp_max, \
-                sur_temp_max_flag = EXCLUDED.sur_temp_max_flag, \
-                sur_temp_min = EXCLUDED.sur_temp_min, \
-                sur_temp_min_flag = EXCLUDED.sur_temp_min_flag, \
-                rh_hr_avg = EXCLUDED.rh_hr_avg, \
-                rh_hr_avg_flag = EXCLUDED.rh_hr_avg_flag, \
+                air_temperature = EXCLUDED.air_temperature, \
+                precipitation = EXCLUDED.precipitation, \
+                solar_radiation = EXCLUDED.solar_radiation, \
+                sr_flag = EXCLUDED.sr_flag, \
+                surface_temperature = EXCLUDED.surface_temperature, \
+                st_type = EXCLUDED.st_type, \
+                st_flag = EXCLUDED.st_flag, \
+                relative_humidity = EXCLUDED.relative_humidity, \
+                rh_flag = EXCLUDED.rh_flag, \
                 soil_moisture_5 = EXCLUDED.soil_moisture_5, \
-                soil_moisture_10 = EXCLUDED.soil_moisture_10, \
-                soil_moisture_20 = EXCLUDED.soil_moisture_20, \
-                soil_moisture_50 = EXCLUDED.soil_moisture_50, \
-                soil_moisture_100 = EXCLUDED.soil_moisture_100, \
-                soil_temp_5 = EXCLUDED.soil_temp_5, \
-                soil_temp_10 = EXCLUDED.soil_temp_10, \
-                soil_temp_20 = EXCLUDED.soil_temp_20, \
-                soil_temp_50 = EXCLUDED.soil_temp_50, \
-                soil_temp_100 = EXCLUDED.soil_temp_100, \
-                source_file_id = EXCLUDED.source_file_id",
+                soil_temperature_5 = EXCLUDED.soil_temperature_5, \
+                wetness = EXCLUDED.wetness, \
+                wet_flag = EXCLUDED.wet_flag, \
+                wind_1_5 = EXCLUDED.wind_1_5, \
+                wind_flag = EXCLUDED.wind_flag, \
+                source_file_id = EXCLUDED.source_file_id, \
+                ingested_at = NOW()",
+            );
+
+            let (batch_inserted, batch_updated) =
+                execute_counted_upsert(&mut query_builder, &mut tx).await?;
+            inserted += batch_inserted;
+            updated += batch_updated;
+        }
+
+        tx.commit().await?;
+        metrics::record_insert(started.elapsed(), inserted, updated);
+        Ok(inserted + updated)
+    }
+
+    /// Insert or update daily summaries in batch
+    ///
+    /// Upserts on (wbanno, lst_date) in batches of 1000, in a single
+    /// transaction. Every row is attributed to `source_file_id`.
+    ///
+    /// # Returns
+    /// The number of rows inserted or updated
+    pub async fn insert_daily_observations(
+        &self,
+        observations: &[NewDailyObservation],
+        source_file_id: i32,
+    ) -> Result<usize> {
+        let mut inserted = 0;
+        let mut updated = 0;
+        let mut tx = self.pool.begin().await?;
+        let started = std::time::Instant::now();
+
+        for chunk in observations.chunks(1000) {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO daily_observations (
+                    wbanno, lst_date, crx_version,
+                    t_daily_max, t_daily_min, t_daily_mean, t_daily_avg,
+                    p_daily_calc, solarad_daily,
+                    sur_temp_daily_type, sur_temp_daily_max, sur_temp_daily_min, sur_temp_daily_avg,
+                    rh_daily_max, rh_daily_min, rh_daily_avg,
+                    soil_moisture_5_daily, soil_moisture_10_daily, soil_moisture_20_daily,
+                    soil_moisture_50_daily, soil_moisture_100_daily,
+                    soil_temp_5_daily, soil_temp_10_daily, soil_temp_20_daily,
+                    soil_temp_50_daily, soil_temp_100_daily,
+                    source_file_id
+                ) ",
             );
 
-            let result = query_builder.build().execute(&mut *tx).await?;
+            query_builder.push_values(chunk, |mut b, obs| {
+                b.push_bind(obs.wbanno)
+                    .push_bind(obs.lst_date)
+                    .push_bind(&obs.crx_version)
+                    .push_bind(obs.t_daily_max)
+                    .push_bind(obs.t_daily_min)
+                    .push_bind(obs.t_daily_mean)
+                    .push_bind(obs.t_daily_avg)
+                    .push_bind(obs.p_daily_calc)
+                    .push_bind(obs.solarad_daily)
+                    .push_bind(&obs.sur_temp_daily_type)
+                    .push_bind(obs.sur_temp_daily_max)
+                    .push_bind(obs.sur_temp_daily_min)
+                    .push_bind(obs.sur_temp_daily_avg)
+                    .push_bind(obs.rh_daily_max)
+                    .push_bind(obs.rh_daily_min)
+                    .push_bind(obs.rh_daily_avg)
+                    .push_bind(obs.soil_moisture_5_daily)
+                    .push_bind(obs.soil_moisture_10_daily)
+                    .push_bind(obs.soil_moisture_20_daily)
+                    .push_bind(obs.soil_moisture_50_daily)
+                    .push_bind(obs.soil_moisture_100_daily)
+                    .push_bind(obs.soil_temp_5_daily)
+                    .push_bind(obs.soil_temp_10_daily)
+                    .push_bind(obs.soil_temp_20_daily)
+                    .push_bind(obs.soil_temp_50_daily)
+                    .push_bind(obs.soil_temp_100_daily)
+                    .push_bind(source_file_id);
+            });
+
+            query_builder.push(
+                " ON CONFLICT (wbanno, lst_date) DO UPDATE SET \
+                crx_version = EXCLUDED.crx_version, \
+                t_daily_max = EXCLUDED.t_daily_max, \
+                t_daily_min = EXCLUDED.t_daily_min, \
+                t_daily_mean = EXCLUDED.t_daily_mean, \
+                t_daily_avg = EXCLUDED.t_daily_avg, \
+                p_daily_calc = EXCLUDED.p_daily_calc, \
+                solarad_daily = EXCLUDED.solarad_daily, \
+                sur_temp_daily_type = EXCLUDED.sur_temp_daily_type, \
+                sur_temp_daily_max = EXCLUDED.sur_temp_daily_max, \
+                sur_temp_daily_min = EXCLUDED.sur_temp_daily_min, \
+                sur_temp_daily_avg = EXCLUDED.sur_temp_daily_avg, \
+                rh_daily_max = EXCLUDED.rh_daily_max, \
+                rh_daily_min = EXCLUDED.rh_daily_min, \
+                rh_daily_avg = EXCLUDED.rh_daily_avg, \
+                soil_moisture_5_daily = EXCLUDED.soil_moisture_5_daily, \
+                soil_moisture_10_daily = EXCLUDED.soil_moisture_10_daily, \
+                soil_moisture_20_daily = EXCLUDED.soil_moisture_20_daily, \
+                soil_moisture_50_daily = EXCLUDED.soil_moisture_50_daily, \
+                soil_moisture_100_daily = EXCLUDED.soil_moisture_100_daily, \
+                soil_temp_5_daily = EXCLUDED.soil_temp_5_daily, \
+                soil_temp_10_daily = EXCLUDED.soil_temp_10_daily, \
+                soil_temp_20_daily = EXCLUDED.soil_temp_20_daily, \
+                soil_temp_50_daily = EXCLUDED.soil_temp_50_daily, \
+                soil_temp_100_daily = EXCLUDED.soil_temp_100_daily, \
+                source_file_id = EXCLUDED.source_file_id, \
+                ingested_at = NOW()",
+            );
 
-            total_rows_affected += result.rows_affected() as usize;
+            let (batch_inserted, batch_updated) =
+                execute_counted_upsert(&mut query_builder, &mut tx).await?;
+            inserted += batch_inserted;
+            updated += batch_updated;
         }
 
         tx.commit().await?;
+        metrics::record_insert(started.elapsed(), inserted, updated);
+        Ok(inserted + updated)
+    }
 
-        // Note: We don't distinguish between inserts and updates as PostgreSQL's
-        // ON CONFLICT doesn't provide this information in rows_affected.
-        // Both values are set to total_rows_affected for backward compatibility.
-        Ok(InsertResult {
-            inserted: total_rows_affected,
-            updated: 0, // Not tracked separately
-            total_rows_affected,
-        })
+    /// Record the start of an ingestion run
+    pub async fn start_ingestion_run(&self) -> Result<IngestionRun> {
+        let run = sqlx::query_as::<_, IngestionRun>(
+            "INSERT INTO ingestion_runs DEFAULT VALUES RETURNING *",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Record a failure to process a file
+    ///
+    /// Each file keeps one row holding its latest error; `retry_count` is the
+    /// number of errors recorded for the file before it.
+    pub async fn record_ingestion_error(&self, file_name: &str, error_message: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ingestion_errors (file_name, error_message)
+            VALUES ($1, $2)
+            ON CONFLICT (file_name) DO UPDATE SET
+                error_message = EXCLUDED.error_message,
+                occurred_at = NOW(),
+                retry_count = ingestion_errors.retry_count + 1
+            "#,
+        )
+        .bind(file_name)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get file processing errors recorded since a point in time, newest first
+    ///
+    /// # Arguments
+    /// * `since` - Only errors at or after this time
+    /// * `limit` - Maximum number of errors to return
+    pub async fn get_recent_ingestion_errors(
+        &self,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<IngestionError>> {
+        let errors = sqlx::query_as::<_, IngestionError>(
+            r#"
+            SELECT * FROM ingestion_errors
+            WHERE occurred_at >= $1
+            ORDER BY occurred_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(errors)
+    }
+
+    /// Flag an observation for quality control
+    ///
+    /// Flags are independent of ingestion: re-processing the observation's
+    /// file leaves them in place.
+    ///
+    /// # Arguments
+    /// * `id` - The `observations.id` to flag
+    /// * `flag_type` - The QC check raising the flag (e.g. "spike")
+    /// * `severity` - Caller-defined severity level
+    /// * `reason` - Human-readable explanation
+    /// * `flagged_by` - The system or person setting the flag
+    ///
+    /// # Errors
+    /// Returns `AppError::Database` if no observation has `id`
+    pub async fn flag_observation(
+        &self,
+        id: i64,
+        flag_type: &str,
+        severity: i32,
+        reason: &str,
+        flagged_by: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO observation_flags (observation_id, flag_type, severity, reason, flagged_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(id)
+        .bind(flag_type)
+        .bind(severity)
+        .bind(reason)
+        .bind(flagged_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the QC flags set on an observation, oldest first
+    pub async fn get_observation_flags(&self, observation_id: i64) -> Result<Vec<ObservationFlag>> {
+        let flags = sqlx::query_as::<_, ObservationFlag>(
+            "SELECT * FROM observation_flags WHERE observation_id = $1 ORDER BY created_at, id",
+        )
+        .bind(observation_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(flags)
+    }
+
+    /// Remove a QC flag
+    ///
+    /// Removing a flag that does not exist is not an error.
+    pub async fn unflag_observation(&self, flag_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM observation_flags WHERE id = $1")
+            .bind(flag_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mark an ingestion run as completed
+    pub async fn complete_ingestion_run(&self, run_id: i32) -> Result<()> {
+        sqlx::query("UPDATE ingestion_runs SET completed_at = NOW() WHERE id = $1")
+            .bind(run_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the observations inserted or last updated by an ingestion run
+    ///
+    /// # Arguments
+    /// * `run_id` - The `ingestion_runs.id` to audit
+    pub async fn get_observations_from_run(&self, run_id: i32) -> Result<Vec<Observation>> {
+        let observations = sqlx::query_as::<_, Observation>(
+            "SELECT * FROM observations WHERE ingestion_run_id = $1 ORDER BY wbanno, utc_datetime",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(observations)
+    }
+
+    /// Get a station's observations between two times, oldest first
+    ///
+    /// Both bounds are inclusive. For long ranges, `stream_observations`
+    /// avoids holding every row in memory.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to read
+    /// * `start` / `end` - The UTC time range to include
+    pub async fn get_observations_for_station_in_range(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Observation>> {
+        let observations = sqlx::query_as::<_, Observation>(
+            r#"
+            SELECT * FROM observations
+            WHERE wbanno = $1 AND utc_datetime >= $2 AND utc_datetime <= $3
+            ORDER BY utc_datetime
+            "#,
+        )
+        .bind(wbanno)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(observations)
+    }
+
+    /// Get the most recent observation from every station, ordered by WBANNO
+    ///
+    /// Stations without observations, and observations from stations missing
+    /// from the `stations` table, are omitted.
+    pub async fn get_latest_observation_per_station(&self) -> Result<Vec<(Station, Observation)>> {
+        // Station columns are aliased so they don't shadow the observation's
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (o.wbanno) o.*,
+                   s.name AS station_name,
+                   s.state AS station_state,
+                   s.latitude AS station_latitude,
+                   s.longitude AS station_longitude,
+                   s.first_seen AS station_first_seen,
+                   s.elevation AS station_elevation,
+                   s.commissioned_date AS station_commissioned_date,
+                   s.current_crx_version AS station_current_crx_version,
+                   s.updated_at AS station_updated_at
+            FROM observations o
+            JOIN stations s ON s.wbanno = o.wbanno
+            ORDER BY o.wbanno, o.utc_datetime DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let observation = Observation::from_row(row)?;
+                let station = Station {
+                    wbanno: observation.wbanno,
+                    name: row.try_get("station_name")?,
+                    state: row.try_get("station_state")?,
+                    latitude: row.try_get("station_latitude")?,
+                    longitude: row.try_get("station_longitude")?,
+                    first_seen: row.try_get("station_first_seen")?,
+                    elevation: row.try_get("station_elevation")?,
+                    commissioned_date: row.try_get("station_commissioned_date")?,
+                    current_crx_version: row.try_get("station_current_crx_version")?,
+                    updated_at: row.try_get("station_updated_at")?,
+                };
+                Ok((station, observation))
+            })
+            .collect()
+    }
+
+    /// Summarize a station's observations by UTC day, oldest first
+    ///
+    /// Both bounds are inclusive. Days without observations are omitted.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to summarize
+    /// * `start` / `end` - The UTC time range to include
+    pub async fn get_daily_aggregates(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DailyAggregate>> {
+        let aggregates = sqlx::query_as::<_, DailyAggregate>(
+            r#"
+            SELECT
+                DATE_TRUNC('day', utc_datetime AT TIME ZONE 'UTC')::DATE AS date,
+                AVG(t_hr_avg)::REAL AS t_avg,
+                MAX(t_max) AS t_max,
+                MIN(t_min) AS t_min,
+                SUM(p_calc) AS precip_total,
+                AVG(rh_hr_avg)::REAL AS avg_rh,
+                AVG(solarad)::REAL AS avg_solarad
+            FROM observations
+            WHERE wbanno = $1 AND utc_datetime >= $2 AND utc_datetime <= $3
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(wbanno)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(aggregates)
+    }
+
+    /// Get when an observation was first inserted
+    ///
+    /// Re-processing a file updates the row (and its `ingested_at`) but
+    /// leaves `created_at` alone.
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station that recorded the observation
+    /// * `utc_datetime` - The observation's UTC timestamp
+    ///
+    /// # Returns
+    /// `None` if no such observation exists
+    pub async fn get_observation_created_at(
+        &self,
+        wbanno: Wbanno,
+        utc_datetime: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let created_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT created_at FROM observations WHERE wbanno = $1 AND utc_datetime = $2",
+        )
+        .bind(wbanno)
+        .bind(utc_datetime)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(created_at)
+    }
+
+    /// Subscribe to observations as they are inserted
+    ///
+    /// Holds a dedicated connection for as long as the stream is alive. Only
+    /// new rows are delivered; updates to existing observations are not.
+    ///
+    /// # Errors
+    /// The stream yields `AppError::Database` if the connection is lost and
+    /// `AppError::Parse` for a payload that is not an observation.
+    pub async fn listen_observations(&self) -> Result<impl Stream<Item = Result<NewObservation>>> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(NEW_OBSERVATION_CHANNEL).await?;
+
+        Ok(listener.into_stream().map(|notification| {
+            let notification = notification?;
+            serde_json::from_str::<NewObservation>(notification.payload()).map_err(|e| {
+                AppError::Parse(format!(
+                    "Invalid {} payload: {}",
+                    NEW_OBSERVATION_CHANNEL, e
+                ))
+            })
+        }))
+    }
+
+    /// Rebuild the `station_daily_summary` materialized view
+    ///
+    /// Refreshes concurrently, so dashboard reads are not blocked while the
+    /// view is recomputed.
+    pub async fn refresh_daily_aggregates(&self) -> Result<()> {
+        sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY station_daily_summary")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Move observations from before `before_year` into `observations_archive`
+    ///
+    /// Copies and deletes in one transaction. An archived row for the same
+    /// station and time is replaced by the live row, which is newer.
+    ///
+    /// # Returns
+    /// The number of observations removed from `observations`
+    pub async fn archive_observations(&self, before_year: i32) -> Result<u64> {
+        let columns = ARCHIVED_OBSERVATION_COLUMNS.join(", ");
+        // The archived row keeps its own id
+        let updates = ARCHIVED_OBSERVATION_COLUMNS
+            .iter()
+            .filter(|c| !matches!(**c, "id" | "wbanno" | "utc_datetime"))
+            .map(|c| format!("{c} = EXCLUDED.{c}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut tx = self.pool.begin().await?;
+
+        // A range on utc_datetime (rather than EXTRACT(YEAR ...)) can use its indexes
+        sqlx::query(&format!(
+            "INSERT INTO observations_archive ({columns}) \
+             SELECT {columns} FROM observations \
+             WHERE utc_datetime < make_timestamptz($1, 1, 1, 0, 0, 0, 'UTC') \
+             ON CONFLICT (wbanno, utc_datetime) DO UPDATE SET {updates}"
+        ))
+        .bind(before_year)
+        .execute(&mut *tx)
+        .await?;
+
+        let deleted = sqlx::query(
+            "DELETE FROM observations WHERE utc_datetime < make_timestamptz($1, 1, 1, 0, 0, 0, 'UTC')",
+        )
+        .bind(before_year)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(deleted)
     }
+
+    /// Delete observations from before `cutoff`, for data retention
+    ///
+    /// Unlike `archive_observations`, the rows are not kept anywhere. Their
+    /// flags are deleted with them.
+    ///
+    /// # Returns
+    /// The number of observations deleted
+    pub async fn delete_observations_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let deleted = sqlx::query("DELETE FROM observations WHERE utc_datetime < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(deleted)
+    }
+
+    /// Get archived observations for a station within a time range
+    ///
+    /// # Arguments
+    /// * `wbanno` - The station to read
+    /// * `start` - Inclusive start of the range
+    /// * `end` - Exclusive end of the range
+    pub async fn get_from_archive(
+        &self,
+        wbanno: Wbanno,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Observation>> {
+        let observations = sqlx::query_as::<_, Observation>(
+            r#"
+            SELECT * FROM observations_archive
+            WHERE wbanno = $1 AND utc_datetime >= $2 AND utc_datetime < $3
+            ORDER BY utc_datetime
+            "#,
+        )
+        .bind(wbanno)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(observations)
+    }
+
+    /// Get observations whose quality flags are all at or below `max_flag`
+    ///
+    /// Missing flags count as good (0), so `max_flag = 0` returns only
+    /// observations with no flagged measurements.
+    ///
+    /// # Arguments
+    /// * `wbanno` - Restrict to one station, or None for all stations
+    /// * `max_flag` - Highest flag value to accept
+    /// * `start` / `end` - UTC time range (start inclusive, end exclusive)
+    pub async fn get_observations_by_quality_flag(
+        &self,
+        wbanno: Option<Wbanno>,
+        max_flag: i32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Observation>> {
+        let observations = sqlx::query_as::<_, Observation>(
+            r#"
+            SELECT * FROM observations
+            WHERE ($2::INTEGER IS NULL OR wbanno = $2)
+              AND utc_datetime >= $3
+              AND utc_datetime < $4
+              AND COALESCE(solarad_flag, 0) <= $1
+              AND COALESCE(solarad_max_flag, 0) <= $1
+              AND COALESCE(solarad_min_flag, 0) <= $1
+              AND COALESCE(sur_temp_flag, 0) <= $1
+              AND COALESCE(sur_temp_max_flag, 0) <= $1
+              AND COALESCE(sur_temp_min_flag, 0) <= $1
+              AND COALESCE(rh_hr_avg_flag, 0) <= $1
+            ORDER BY wbanno, utc_datetime
+            "#,
+        )
+        .bind(max_flag)
+        .bind(wbanno)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(observations)
+    }
+}
+
+/// Run a batch upsert, returning the number of rows (inserted, updated)
+///
+/// Appends `RETURNING (xmax = 0)`, which is true for a newly inserted row and
+/// false for one its ON CONFLICT clause updated.
+async fn execute_counted_upsert(
+    query_builder: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>,
+    conn: &mut sqlx::PgConnection,
+) -> Result<(usize, usize)> {
+    query_builder.push(" RETURNING (xmax = 0)");
+    let rows: Vec<bool> = query_builder.build_query_scalar().fetch_all(conn).await?;
+
+    let inserted = rows.iter().filter(|&&inserted| inserted).count();
+    Ok((inserted, rows.len() - inserted))
 }
@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,6 +18,9 @@ pub enum AppError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    #[error("Parse error: {0}")]
+    ParseLine(#[from] ParseError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -24,4 +28,105 @@ pub enum AppError {
     InvalidData(String),
 }
 
+impl AppError {
+    /// Process exit code for this error category, for use by CLI commands
+    ///
+    /// 1 = config, 2 = database/migration, 3 = HTTP, 4 = parse, 5 = IO, 6 = invalid data
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Config(_) => 1,
+            AppError::Database(_) | AppError::Migration(_) => 2,
+            AppError::Http(_) => 3,
+            AppError::Parse(_) | AppError::ParseLine(_) => 4,
+            AppError::Io(_) => 5,
+            AppError::InvalidData(_) => 6,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, AppError>;
+
+/// Why a single data line failed to parse
+///
+/// `field_index` and `field_name` are set when one column was at fault and
+/// unset for problems with the whole line, such as too few fields.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line in the file; 1 for a line parsed on its own
+    pub line_number: usize,
+    /// Zero-based column index, as in `parser::columns`
+    pub field_index: Option<usize>,
+    /// Column name, e.g. `t_hr_avg`
+    pub field_name: Option<&'static str>,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}", self.line_number)?;
+        match (self.field_name, self.field_index) {
+            (Some(name), _) => write!(f, ", field {}", name)?,
+            (None, Some(index)) => write!(f, ", field {}", index)?,
+            (None, None) => {}
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_config() {
+        assert_eq!(AppError::Config("bad".to_string()).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_exit_code_database() {
+        assert_eq!(AppError::Database(sqlx::Error::RowNotFound).exit_code(), 2);
+        let migrate_err = sqlx::migrate::MigrateError::VersionMissing(1);
+        assert_eq!(AppError::Migration(migrate_err).exit_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_http() {
+        let err = reqwest::Client::new()
+            .get("not a url")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(AppError::Http(err).exit_code(), 3);
+    }
+
+    #[test]
+    fn test_exit_code_parse() {
+        assert_eq!(AppError::Parse("bad".to_string()).exit_code(), 4);
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let err = ParseError {
+            line_number: 3,
+            field_index: Some(9),
+            field_name: Some("t_hr_avg"),
+            reason: "invalid float 'abc'".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Line 3, field t_hr_avg: invalid float 'abc'"
+        );
+        assert_eq!(AppError::ParseLine(err).exit_code(), 4);
+    }
+
+    #[test]
+    fn test_exit_code_io() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert_eq!(AppError::Io(err).exit_code(), 5);
+    }
+
+    #[test]
+    fn test_exit_code_invalid_data() {
+        assert_eq!(AppError::InvalidData("bad".to_string()).exit_code(), 6);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::db::{ExportColumns, Observation, Repository, Wbanno};
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Write observations in `[start, end)` as JSON Lines, one object per line
+///
+/// Each line is a serialized `Observation`, with timestamps in RFC 3339.
+/// Without `wbanno`, every station's observations are written, station by
+/// station in WBANNO order. Rows are streamed `stream_chunk_size` at a time,
+/// so large ranges are not held in memory. With `max_flag`, only
+/// observations whose quality flags are all at or below it are written (see
+/// `Repository::stream_observations_by_quality_flag`).
+///
+/// # Returns
+/// Number of observations written
+pub async fn export_observations_jsonl<W: AsyncWrite + Unpin>(
+    repo: &Repository,
+    wbanno: Option<Wbanno>,
+    max_flag: Option<i32>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    writer: &mut W,
+) -> Result<usize> {
+    let mut written = 0;
+    for wbanno in stations_to_export(repo, wbanno).await? {
+        let mut observations =
+            Box::pin(repo.stream_observations_by_quality_flag(wbanno, max_flag, start, end));
+        while let Some(observation) = observations.try_next().await? {
+            let mut line = serde_json::to_string(&observation).map_err(std::io::Error::from)?;
+            line.push('\n');
+            writer.write_all(line.as_bytes()).await?;
+            written += 1;
+        }
+    }
+
+    writer.flush().await?;
+    Ok(written)
+}
+
+/// Write observations in `[start, end)` as CSV with the selected columns
+///
+/// The header row names `wbanno` followed by the `columns` groups, as in
+/// `Repository::export_observations_csv`; missing values are empty fields.
+/// Stations and `max_flag` are handled as for `export_observations_jsonl`.
+///
+/// # Returns
+/// Number of observation rows written, excluding the header
+///
+/// # Errors
+/// Returns `AppError::InvalidData` if `columns` omits the timestamp
+pub async fn export_observations_csv<W: AsyncWrite + Unpin>(
+    repo: &Repository,
+    wbanno: Option<Wbanno>,
+    max_flag: Option<i32>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    columns: ExportColumns,
+    writer: &mut W,
+) -> Result<usize> {
+    let names = columns.column_names()?;
+    let header = format!("wbanno,{}\n", names.join(","));
+    writer.write_all(header.as_bytes()).await?;
+
+    let mut written = 0;
+    for wbanno in stations_to_export(repo, wbanno).await? {
+        let mut observations =
+            Box::pin(repo.stream_observations_by_quality_flag(wbanno, max_flag, start, end));
+        while let Some(observation) = observations.try_next().await? {
+            writer
+                .write_all(csv_row(&observation, &names)?.as_bytes())
+                .await?;
+            written += 1;
+        }
+    }
+
+    writer.flush().await?;
+    Ok(written)
+}
+
+/// Column groups named in a comma-separated list, e.g. `temperature,soil_moisture`
+///
+/// The timestamp columns are always included. Group names match the
+/// `ExportColumns` fields.
+///
+/// # Errors
+/// Returns `AppError::InvalidData` for an unknown group name
+pub fn parse_columns(groups: &str) -> Result<ExportColumns> {
+    let mut columns = ExportColumns {
+        timestamp: true,
+        temperature: false,
+        precipitation: false,
+        solar: false,
+        surface_temp: false,
+        relative_humidity: false,
+        soil_moisture: false,
+        soil_temp: false,
+    };
+
+    for group in groups.split(',').map(str::trim).filter(|g| !g.is_empty()) {
+        match group {
+            "timestamp" => {}
+            "temperature" => columns.temperature = true,
+            "precipitation" => columns.precipitation = true,
+            "solar" => columns.solar = true,
+            "surface_temp" => columns.surface_temp = true,
+            "relative_humidity" => columns.relative_humidity = true,
+            "soil_moisture" => columns.soil_moisture = true,
+            "soil_temp" => columns.soil_temp = true,
+            other => {
+                return Err(AppError::InvalidData(format!(
+                    "Unknown export column group '{}'; expected timestamp, temperature, \
+                     precipitation, solar, surface_temp, relative_humidity, soil_moisture \
+                     or soil_temp",
+                    other
+                )))
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+/// The requested station, or every station when `wbanno` is None
+async fn stations_to_export(repo: &Repository, wbanno: Option<Wbanno>) -> Result<Vec<Wbanno>> {
+    match wbanno {
+        Some(wbanno) => Ok(vec![wbanno]),
+        None => Ok(repo
+            .list_stations()
+            .await?
+            .into_iter()
+            .map(|s| s.wbanno)
+            .collect()),
+    }
+}
+
+/// One CSV line: the observation's `wbanno` and its `names` fields
+fn csv_row(observation: &Observation, names: &[&str]) -> Result<String> {
+    let value = serde_json::to_value(observation).map_err(std::io::Error::from)?;
+
+    let mut fields = vec![observation.wbanno.to_string()];
+    fields.extend(names.iter().map(|name| match &value[*name] {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }));
+
+    let mut line = fields.join(",");
+    line.push('\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        let columns = parse_columns("temperature, soil_moisture").unwrap();
+        assert_eq!(
+            columns.column_names().unwrap(),
+            vec![
+                "utc_datetime",
+                "lst_datetime",
+                "t_calc",
+                "t_hr_avg",
+                "t_max",
+                "t_min",
+                "soil_moisture_5",
+                "soil_moisture_10",
+                "soil_moisture_20",
+                "soil_moisture_50",
+                "soil_moisture_100",
+            ]
+        );
+
+        assert!(matches!(
+            parse_columns("temperature,wind"),
+            Err(AppError::InvalidData(_))
+        ));
+    }
+}
@@ -1,8 +1,18 @@
-use crate::config::LocationFilter;
+use crate::config::{split_data_file_name, DataProduct, LocationFilter, RetryConfig, SourceConfig};
 use crate::error::{AppError, Result};
+use crate::metrics;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use lru::LruCache;
 use reqwest::Client;
 use scraper::{Html, Selector};
-use std::time::Duration;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
 use tracing::{debug, info, warn};
 
 /// Allowed hostnames for NOAA data fetching (prevents SSRF attacks)
@@ -13,8 +23,23 @@ const ALLOWED_HOSTS: &[&str] = &[
     "ncdc.noaa.gov",
 ];
 
+/// Default timeout for directory listing requests
+pub(crate) const DEFAULT_LISTING_TIMEOUT_SECS: u64 = 30;
+
+/// Default timeout for file downloads
+pub(crate) const DEFAULT_DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+
+/// Default age after which the cached year listing is fetched again
+const DEFAULT_YEARS_CACHE_TTL_SECS: u64 = 3600;
+
+/// Loopback hosts permitted when `allow_http` is enabled for local testing
+const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1"];
+
 /// Validate that a URL is from an allowed NOAA host
-fn validate_url(url: &str) -> Result<()> {
+///
+/// When `allow_http` is set, plain HTTP and loopback hosts are also accepted
+/// so the fetcher can run against a local mock server.
+pub(crate) fn validate_url(url: &str, allow_http: bool) -> Result<()> {
     let parsed =
         url::Url::parse(url).map_err(|e| AppError::InvalidData(format!("Invalid URL: {}", e)))?;
 
@@ -22,7 +47,8 @@ fn validate_url(url: &str) -> Result<()> {
         .host_str()
         .ok_or_else(|| AppError::InvalidData("URL missing host".to_string()))?;
 
-    if !ALLOWED_HOSTS.contains(&host) {
+    let is_allowed_local = allow_http && LOCAL_HOSTS.contains(&host);
+    if !ALLOWED_HOSTS.contains(&host) && !is_allowed_local {
         return Err(AppError::InvalidData(format!(
             "URL host '{}' not in allowed list. Expected one of: {}",
             host,
@@ -31,7 +57,7 @@ fn validate_url(url: &str) -> Result<()> {
     }
 
     // Ensure HTTPS
-    if parsed.scheme() != "https" {
+    if parsed.scheme() != "https" && !allow_http {
         return Err(AppError::InvalidData(format!(
             "URL must use HTTPS, got: {}",
             parsed.scheme()
@@ -41,9 +67,154 @@ fn validate_url(url: &str) -> Result<()> {
     Ok(())
 }
 
+/// Downloaded file content and its `Last-Modified` time keyed by URL
+type ContentCache = LruCache<String, (Arc<String>, Option<DateTime<Utc>>)>;
+
+/// Result of a conditional download with `Fetcher::download_file_if_modified`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadResult {
+    /// The file was downloaded
+    Downloaded {
+        content: String,
+        /// `Content-Length` when sent, the file size for mirrors, and
+        /// otherwise the length of the content
+        size: u64,
+        /// `Last-Modified` header, or the file's mtime for mirrors
+        last_modified: Option<DateTime<Utc>>,
+        /// SHA-256 of the content; see `content_hash`
+        hash: String,
+    },
+    /// The file has not changed since the given time
+    NotModified,
+}
+
+/// SHA-256 of file content as lowercase hex, as stored in `processed_files.file_hash`
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Format a time as an HTTP date for `If-Modified-Since`
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse a `Last-Modified` header value
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Whether a file modified at `modified` is unchanged since `since`
+///
+/// HTTP dates have one-second precision, so sub-second parts are ignored.
+fn unchanged_since(modified: Option<DateTime<Utc>>, since: Option<DateTime<Utc>>) -> bool {
+    match (modified, since) {
+        (Some(modified), Some(since)) => modified.timestamp() <= since.timestamp(),
+        _ => false,
+    }
+}
+
+/// Year listing and when it was fetched, filled at most once
+type YearsCacheCell = OnceCell<(Instant, Vec<i32>)>;
+
 pub struct Fetcher {
     client: Client,
     base_url: String,
+    /// Year listing cache with its fetch time, swapped for a fresh cell on
+    /// forced refresh or once older than `years_cache_ttl`
+    years_cache: Mutex<Arc<YearsCacheCell>>,
+    /// How long a year listing is served from cache
+    years_cache_ttl: Duration,
+    /// Local mirror to read from instead of HTTP, when set
+    mirror: Option<MirrorIndex>,
+    /// Accept plain HTTP and loopback hosts (local testing only)
+    allow_http: bool,
+    /// Reject downloads larger than this many bytes
+    max_file_size_bytes: Option<u64>,
+    /// Per-request timeout for year and file listings
+    listing_timeout: Duration,
+    /// Per-request timeout for file downloads
+    download_timeout: Duration,
+    /// Recently downloaded content by URL, when `content_cache_size` > 0
+    content_cache: Option<Arc<Mutex<ContentCache>>>,
+    /// Fewest files an unfiltered year listing should contain
+    min_expected_files_per_year: Option<usize>,
+    /// Fail short listings instead of warning
+    strict_file_count: bool,
+    /// Product whose data files are listed
+    product: DataProduct,
+    /// Backoff policy for transient HTTP failures
+    retry: RetryConfig,
+}
+
+/// In-memory index of a local `wget --mirror` copy of the NOAA data
+struct MirrorIndex {
+    files_by_year: BTreeMap<i32, Vec<FileInfo>>,
+    /// Indexed file URL -> path on disk
+    paths: HashMap<String, PathBuf>,
+    /// Product whose data files are indexed
+    product: DataProduct,
+}
+
+impl MirrorIndex {
+    /// Scan `root` recursively for `product`'s data files
+    ///
+    /// The year is taken from the filename, so any directory layout works.
+    fn build(root: &Path, product: DataProduct) -> Result<Self> {
+        let mut index = Self {
+            files_by_year: BTreeMap::new(),
+            paths: HashMap::new(),
+            product,
+        };
+        index.scan(root)?;
+
+        for files in index.files_by_year.values_mut() {
+            files.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        Ok(index)
+    }
+
+    fn scan(&mut self, dir: &Path) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.scan(&path)?;
+                continue;
+            }
+
+            self.insert(&path);
+        }
+        Ok(())
+    }
+
+    /// Index a single data file, returning its info
+    ///
+    /// Files already in the index are returned without being added again.
+    fn insert(&mut self, path: &Path) -> Option<FileInfo> {
+        let name = path.file_name().and_then(|n| n.to_str())?;
+        if !self.product.is_data_file(name) {
+            return None;
+        }
+
+        let (year, _) = split_data_file_name(name)?;
+
+        let url = url::Url::from_file_path(path)
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| path.display().to_string());
+
+        let mut file_info = parse_filename(name, year, "")?;
+        file_info.url = url.clone();
+
+        if self.paths.insert(url, path.to_path_buf()).is_none() {
+            self.files_by_year
+                .entry(year)
+                .or_default()
+                .push(file_info.clone());
+        }
+        Some(file_info)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -55,19 +226,167 @@ pub struct FileInfo {
     pub station_name: String,
 }
 
+impl FileInfo {
+    /// File details for a data file URL, from the name in its last path segment
+    ///
+    /// Returns None if the URL does not end in a data file name.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url).ok()?;
+        let name = parsed.path_segments()?.next_back()?;
+        let (year, _) = split_data_file_name(name)?;
+        let mut file_info = parse_filename(name, year, "")?;
+        file_info.url = url.to_string();
+        Some(file_info)
+    }
+}
+
 impl Fetcher {
     pub fn new(base_url: &str) -> Result<Self> {
-        let client = Client::builder()
-            .user_agent("uscrn-ingest/0.1.0")
-            .timeout(std::time::Duration::from_secs(60))
-            .build()?;
+        // Timeouts are applied per request since listings and downloads differ
+        let client = Client::builder().user_agent("uscrn-ingest/0.1.0").build()?;
 
         Ok(Self {
             client,
             base_url: base_url.trim_end_matches('/').to_string(),
+            years_cache: Mutex::new(Arc::new(OnceCell::new())),
+            years_cache_ttl: Duration::from_secs(DEFAULT_YEARS_CACHE_TTL_SECS),
+            mirror: None,
+            allow_http: false,
+            max_file_size_bytes: None,
+            listing_timeout: Duration::from_secs(DEFAULT_LISTING_TIMEOUT_SECS),
+            download_timeout: Duration::from_secs(DEFAULT_DOWNLOAD_TIMEOUT_SECS),
+            content_cache: None,
+            min_expected_files_per_year: None,
+            strict_file_count: false,
+            product: DataProduct::default(),
+            retry: RetryConfig::default(),
         })
     }
 
+    /// Create a fetcher for the configured data source
+    ///
+    /// The base URL is pointed at the configured product's directory.
+    pub fn from_config(source: &SourceConfig) -> Result<Self> {
+        Ok(Self::new(&source.product.base_url(&source.base_url))?
+            .product(source.product)
+            .allow_http(source.allow_http)
+            .max_file_size_bytes(source.max_file_size_bytes)
+            .listing_timeout(Duration::from_secs(source.listing_timeout_secs))
+            .download_timeout(Duration::from_secs(source.download_timeout_secs))
+            .content_cache_size(source.content_cache_size)
+            .min_expected_files_per_year(source.min_expected_files_per_year)
+            .strict_file_count(source.strict_file_count)
+            .retry(source.retry))
+    }
+
+    /// Accept plain HTTP URLs and loopback hosts, for testing against a local mock server
+    pub fn allow_http(mut self, allow_http: bool) -> Self {
+        self.allow_http = allow_http;
+        self
+    }
+
+    /// Reject downloads whose size exceeds `limit` bytes
+    pub fn max_file_size_bytes(mut self, limit: Option<u64>) -> Self {
+        self.max_file_size_bytes = limit;
+        self
+    }
+
+    /// Set the timeout for year and file listing requests
+    pub fn listing_timeout(mut self, timeout: Duration) -> Self {
+        self.listing_timeout = timeout;
+        self
+    }
+
+    /// Set how long the year listing is cached before it is fetched again
+    pub fn years_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.years_cache_ttl = ttl;
+        self
+    }
+
+    /// Set the retry policy for listing and download requests
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the timeout for file downloads
+    pub fn download_timeout(mut self, timeout: Duration) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    /// Keep the content of the last `size` downloaded files in memory
+    ///
+    /// Repeated downloads of a cached URL are served without an HTTP request.
+    /// A size of 0 disables the cache.
+    pub fn content_cache_size(mut self, size: usize) -> Self {
+        self.content_cache =
+            NonZeroUsize::new(size).map(|size| Arc::new(Mutex::new(LruCache::new(size))));
+        self
+    }
+
+    /// Check unfiltered year listings hold at least `min` files
+    ///
+    /// A short listing usually means a truncated response or an HTML parsing
+    /// problem. It is logged unless `strict_file_count` is set.
+    pub fn min_expected_files_per_year(mut self, min: Option<usize>) -> Self {
+        self.min_expected_files_per_year = min;
+        self
+    }
+
+    /// Fail year listings below `min_expected_files_per_year` with `AppError::InvalidData`
+    pub fn strict_file_count(mut self, strict: bool) -> Self {
+        self.strict_file_count = strict;
+        self
+    }
+
+    /// List `product`'s data files instead of hourly02's
+    ///
+    /// Only selects which files are listed; the base URL is used as given.
+    pub fn product(mut self, product: DataProduct) -> Self {
+        self.product = product;
+        self
+    }
+
+    /// Create a fetcher that reads from a local `wget --mirror` directory
+    ///
+    /// The mirror is scanned once for `CRNH0203-*.txt` files; listings and
+    /// downloads are then served from that index instead of HTTP.
+    pub fn from_wget_mirror(mirror_root: &Path) -> Result<Self> {
+        Self::from_wget_mirror_with_product(mirror_root, DataProduct::Hourly02)
+    }
+
+    /// Create a fetcher that reads `product`'s files from a local mirror
+    pub fn from_wget_mirror_with_product(mirror_root: &Path, product: DataProduct) -> Result<Self> {
+        if !mirror_root.is_dir() {
+            return Err(AppError::Config(format!(
+                "Mirror root '{}' is not a directory",
+                mirror_root.display()
+            )));
+        }
+
+        let index = MirrorIndex::build(mirror_root, product)?;
+        info!(
+            "Indexed {} files across {} years from mirror {}",
+            index.paths.len(),
+            index.files_by_year.len(),
+            mirror_root.display()
+        );
+
+        let mut fetcher =
+            Self::new(&product.base_url(crate::config::DEFAULT_BASE_URL))?.product(product);
+        fetcher.mirror = Some(index);
+        Ok(fetcher)
+    }
+
+    /// Add a file that appeared after the mirror was scanned
+    ///
+    /// Returns `None` if this fetcher has no mirror or the path is not one
+    /// of the product's data files.
+    pub fn index_mirror_file(&mut self, path: &Path) -> Option<FileInfo> {
+        self.mirror.as_mut()?.insert(path)
+    }
+
     /// Download a file from a validated NOAA URL
     ///
     /// # Arguments
@@ -79,26 +398,229 @@ impl Fetcher {
     /// # Errors
     /// Returns error if URL validation fails or download fails
     pub async fn download_file(&self, url: &str) -> Result<String> {
+        match self.download_file_if_modified(url, None).await? {
+            DownloadResult::Downloaded { content, .. } => Ok(content),
+            DownloadResult::NotModified => Err(AppError::InvalidData(format!(
+                "Unexpected 304 Not Modified for {}",
+                url
+            ))),
+        }
+    }
+
+    /// Download a file unless it has not changed since `since`
+    ///
+    /// Sends `If-Modified-Since` when `since` is set and treats a
+    /// `304 Not Modified` response as unchanged. Mirrors compare the
+    /// file's mtime instead. Downloaded content is hashed with SHA-256.
+    ///
+    /// # Errors
+    /// Returns error if URL validation fails or download fails
+    pub async fn download_file_if_modified(
+        &self,
+        url: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<DownloadResult> {
+        if let Some(mirror) = &self.mirror {
+            // A missing file is an error rather than a rejected file, so it
+            // is retried next run instead of being recorded as skipped
+            let path = mirror.paths.get(url).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File '{}' is not in the mirror index", url),
+                )
+            })?;
+            let metadata = tokio::fs::metadata(path).await?;
+            let last_modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            if unchanged_since(last_modified, since) {
+                debug!("Mirror file {} not modified", path.display());
+                return Ok(DownloadResult::NotModified);
+            }
+            debug!("Reading file from mirror {}", path.display());
+            let size = metadata.len();
+            self.check_file_size(url, size)?;
+            let content = tokio::fs::read_to_string(path).await?;
+            return Ok(DownloadResult::Downloaded {
+                hash: content_hash(&content),
+                content,
+                size,
+                last_modified,
+            });
+        }
+
         debug!("Downloading file from {}", url);
 
         // Validate URL before making request
-        validate_url(url)?;
+        validate_url(url, self.allow_http)?;
 
-        retry_with_backoff(3, || async {
-            let response = self.client.get(url).send().await?;
+        if let Some(cache) = &self.content_cache {
+            if let Some((content, last_modified)) =
+                cache.lock().unwrap_or_else(|e| e.into_inner()).get(url)
+            {
+                if unchanged_since(*last_modified, since) {
+                    return Ok(DownloadResult::NotModified);
+                }
+                debug!("Serving {} from content cache", url);
+                return Ok(DownloadResult::Downloaded {
+                    content: content.as_str().to_owned(),
+                    size: content.len() as u64,
+                    last_modified: *last_modified,
+                    hash: content_hash(content),
+                });
+            }
+        }
 
+        let started = std::time::Instant::now();
+        let result = retry_with_backoff(&self.retry, || async {
+            let mut request = self.client.get(url).timeout(self.download_timeout);
+            if let Some(since) = since {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, http_date(since));
+            }
+            metrics::record_http_request();
+            let response = request.send().await?;
+
+            if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(DownloadResult::NotModified);
+            }
             if !response.status().is_success() {
                 return Err(AppError::Http(response.error_for_status().unwrap_err()));
             }
 
-            let content = response.text().await?;
-            Ok(content)
+            let last_modified = response
+                .headers()
+                .get(reqwest::header::LAST_MODIFIED)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_http_date);
+            let content_length = response.content_length();
+            if let Some(size) = content_length {
+                self.check_file_size(url, size)?;
+            }
+
+            // Without a Content-Length (e.g. a chunked response) the limit is
+            // enforced while reading, so an oversized body is never buffered whole
+            let content = self
+                .read_body(url, response, content_length.is_none())
+                .await?;
+            let size = content_length.unwrap_or(content.len() as u64);
+            Ok(DownloadResult::Downloaded {
+                hash: content_hash(&content),
+                content,
+                size,
+                last_modified,
+            })
         })
-        .await
+        .await?;
+        metrics::record_download(started.elapsed());
+
+        if let (
+            Some(cache),
+            DownloadResult::Downloaded {
+                content,
+                last_modified,
+                ..
+            },
+        ) = (&self.content_cache, &result)
+        {
+            cache
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .put(url.to_string(), (Arc::new(content.clone()), *last_modified));
+        }
+
+        Ok(result)
     }
 
+    /// Check a file size against `max_file_size_bytes`
+    ///
+    /// Warns when the file is above 80% of the limit.
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if the file exceeds the limit
+    /// Read a response body as text, checking `max_file_size_bytes` as bytes
+    /// arrive when `enforce_limit` is set
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` once the body exceeds the limit
+    async fn read_body(
+        &self,
+        url: &str,
+        mut response: reqwest::Response,
+        enforce_limit: bool,
+    ) -> Result<String> {
+        let limit = self.max_file_size_bytes.filter(|_| enforce_limit);
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if let Some(limit) = limit.filter(|&limit| body.len() as u64 > limit) {
+                return Err(AppError::InvalidData(format!(
+                    "File {} is over {} bytes, exceeding max_file_size_bytes ({})",
+                    url,
+                    body.len(),
+                    limit
+                )));
+            }
+        }
+
+        if enforce_limit {
+            self.check_file_size(url, body.len() as u64)?;
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn check_file_size(&self, url: &str, size: u64) -> Result<()> {
+        let Some(limit) = self.max_file_size_bytes else {
+            return Ok(());
+        };
+
+        if size > limit {
+            return Err(AppError::InvalidData(format!(
+                "File {} is {} bytes, exceeding max_file_size_bytes ({})",
+                url, size, limit
+            )));
+        }
+
+        if size > limit / 5 * 4 {
+            warn!(
+                "File {} is {} bytes, over 80% of max_file_size_bytes ({})",
+                url, size, limit
+            );
+        }
+
+        Ok(())
+    }
+
+    /// List the years available on the NOAA server
+    ///
+    /// The listing is cached for `years_cache_ttl` (an hour by default), so a
+    /// long-lived fetcher sees a new year's directory; concurrent callers
+    /// share a single in-flight request. Use `list_years_force_refresh` to re-fetch.
     pub async fn list_years(&self) -> Result<Vec<i32>> {
-        retry_with_backoff(3, || async { self.list_years_impl().await }).await
+        let cache = {
+            let mut cache = self.years_cache.lock().unwrap_or_else(|e| e.into_inner());
+            if cache
+                .get()
+                .is_some_and(|(fetched_at, _)| fetched_at.elapsed() >= self.years_cache_ttl)
+            {
+                *cache = Arc::new(OnceCell::new());
+            }
+            cache.clone()
+        };
+
+        let (_, years) = cache
+            .get_or_try_init(|| async {
+                let years =
+                    retry_with_backoff(&self.retry, || async { self.list_years_impl().await })
+                        .await?;
+                Ok::<_, AppError>((Instant::now(), years))
+            })
+            .await?;
+
+        Ok(years.clone())
+    }
+
+    /// Invalidate the cached year listing and fetch it again
+    pub async fn list_years_force_refresh(&self) -> Result<Vec<i32>> {
+        *self.years_cache.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(OnceCell::new());
+        self.list_years().await
     }
 
     pub async fn list_files_for_year(
@@ -107,17 +629,27 @@ impl Fetcher {
         filter: &LocationFilter,
     ) -> Result<Vec<FileInfo>> {
         let filter = filter.clone();
-        retry_with_backoff(3, || async {
+        retry_with_backoff(&self.retry, || async {
             self.list_files_for_year_impl(year, &filter).await
         })
         .await
     }
 
     async fn list_years_impl(&self) -> Result<Vec<i32>> {
+        if let Some(mirror) = &self.mirror {
+            return Ok(mirror.files_by_year.keys().copied().collect());
+        }
+
         let url = format!("{}/", self.base_url);
         debug!("Fetching year listing from {}", url);
 
-        let response = self.client.get(&url).send().await?;
+        metrics::record_http_request();
+        let response = self
+            .client
+            .get(&url)
+            .timeout(self.listing_timeout)
+            .send()
+            .await?;
         let html = response.text().await?;
 
         let document = Html::parse_document(&html);
@@ -147,39 +679,203 @@ impl Fetcher {
         year: i32,
         filter: &LocationFilter,
     ) -> Result<Vec<FileInfo>> {
+        if let Some(mirror) = &self.mirror {
+            let files: Vec<FileInfo> = mirror
+                .files_by_year
+                .get(&year)
+                .map(|files| {
+                    files
+                        .iter()
+                        .filter(|f| filter.matches_file(&f.name))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+            info!(
+                "Found {} mirrored files for year {} (after filtering)",
+                files.len(),
+                year
+            );
+            self.check_file_count(year, filter, files.len())?;
+            return Ok(files);
+        }
+
         let url = format!("{}/{}/", self.base_url, year);
         debug!("Fetching file listing for year {} from {}", year, url);
 
-        let response = self.client.get(&url).send().await?;
-        let html = response.text().await?;
+        let html = self.fetch_listing(&url).await?;
 
-        let document = Html::parse_document(&html);
-        let selector = Selector::parse("a")
-            .map_err(|e| AppError::Parse(format!("Selector error: {:?}", e)))?;
+        let files: Vec<FileInfo> = data_file_links(&html, self.product)?
+            .into_iter()
+            .filter(|href| filter.matches_file(href))
+            .filter_map(|href| parse_filename(&href, year, &self.base_url))
+            .collect();
+
+        info!(
+            "Found {} files for year {} (after filtering)",
+            files.len(),
+            year
+        );
+        self.check_file_count(year, filter, files.len())?;
+        Ok(files)
+    }
 
-        let mut files = Vec::new();
+    /// Fetch a directory listing page
+    async fn fetch_listing(&self, url: &str) -> Result<String> {
+        metrics::record_http_request();
+        let response = self
+            .client
+            .get(url)
+            .timeout(self.listing_timeout)
+            .send()
+            .await?;
+        Ok(response.text().await?)
+    }
 
-        for element in document.select(&selector) {
-            if let Some(href) = element.value().attr("href") {
-                if href.starts_with("CRNH") && href.ends_with(".txt") && filter.matches_file(href) {
-                    if let Some(file_info) = parse_filename(href, year, &self.base_url) {
-                        files.push(file_info);
+    /// Verify the current or previous year's listing still links to the
+    /// product's data files
+    ///
+    /// A change to NOAA's directory listing HTML would otherwise make every
+    /// year list zero files, so ingestion silently finds nothing to do. The
+    /// previous year is checked too, since a new year's directory may be
+    /// missing or empty for a while. Listings that cannot be fetched (after
+    /// retries) or are not 200 OK are only logged, so a transient outage does
+    /// not stop the scheduler. Mirrors are read from disk and are not checked.
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if a listing was fetched but neither
+    /// year links to any data files
+    pub async fn check_listing_format(&self) -> Result<()> {
+        if self.mirror.is_some() {
+            return Ok(());
+        }
+
+        let current_year = chrono::Utc::now().year();
+        let mut empty_listing = None;
+        for year in [current_year, current_year - 1] {
+            let url = format!("{}/{}/", self.base_url, year);
+            let listing = retry_with_backoff(&self.retry, || async {
+                metrics::record_http_request();
+                let response = self
+                    .client
+                    .get(&url)
+                    .timeout(self.listing_timeout)
+                    .send()
+                    .await?;
+                // Server errors are returned as errors so they are retried
+                let response = if response.status().is_server_error() {
+                    response.error_for_status()?
+                } else {
+                    response
+                };
+                Ok((response.status(), response.text().await?))
+            })
+            .await;
+
+            match listing {
+                Ok((status, html)) if status.is_success() => {
+                    if !data_file_links(&html, self.product)?.is_empty() {
+                        debug!("Listing format check passed for {}", url);
+                        return Ok(());
                     }
+                    empty_listing = Some(url);
+                }
+                Ok((status, _)) => {
+                    warn!("Listing format check: {} returned {}", url, status);
+                }
+                Err(e) => {
+                    warn!("Listing format check: failed to fetch {}: {}", url, e);
                 }
             }
         }
 
-        info!(
-            "Found {} files for year {} (after filtering)",
-            files.len(),
-            year
+        match empty_listing {
+            Some(url) => Err(AppError::InvalidData(format!(
+                "NOAA directory listing format may have changed; no {}*.txt files found at {}",
+                self.product.file_prefix(),
+                url
+            ))),
+            None => {
+                warn!("Listing format could not be checked; continuing");
+                Ok(())
+            }
+        }
+    }
+
+    /// Compare a year listing's size with `min_expected_files_per_year`
+    ///
+    /// Filtered listings are expected to be small and are not checked.
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` for a short listing when `strict_file_count` is set
+    fn check_file_count(&self, year: i32, filter: &LocationFilter, count: usize) -> Result<()> {
+        let Some(min) = self.min_expected_files_per_year else {
+            return Ok(());
+        };
+        if !filter.is_empty() || count >= min {
+            return Ok(());
+        }
+
+        if self.strict_file_count {
+            return Err(AppError::InvalidData(format!(
+                "Year {} listing has {} files, fewer than min_expected_files_per_year ({})",
+                year, count, min
+            )));
+        }
+
+        warn!(
+            "Year {} listing has {} files, fewer than min_expected_files_per_year ({}); \
+             the listing may be incomplete",
+            year, count, min
         );
-        Ok(files)
+        Ok(())
     }
 }
 
-/// Retry a future with exponential backoff
-async fn retry_with_backoff<F, Fut, T>(max_retries: u32, mut f: F) -> Result<T>
+/// Source of USCRN file listings and content
+///
+/// Lets callers such as `Scheduler::estimate_run_duration` work against
+/// any data source, including test doubles.
+#[async_trait]
+pub trait FetcherTrait: Send + Sync {
+    async fn list_files_for_year(
+        &self,
+        year: i32,
+        filter: &LocationFilter,
+    ) -> Result<Vec<FileInfo>>;
+
+    async fn download_file(&self, url: &str) -> Result<String>;
+}
+
+#[async_trait]
+impl FetcherTrait for Fetcher {
+    async fn list_files_for_year(
+        &self,
+        year: i32,
+        filter: &LocationFilter,
+    ) -> Result<Vec<FileInfo>> {
+        Fetcher::list_files_for_year(self, year, filter).await
+    }
+
+    async fn download_file(&self, url: &str) -> Result<String> {
+        Fetcher::download_file(self, url).await
+    }
+}
+
+/// Delay before retry `attempt` (from 0): `base_delay_ms * 2^attempt`, capped
+/// at `max_delay_ms`, plus `jitter_ms` of the random jitter
+fn backoff_delay(config: &RetryConfig, attempt: u32, jitter_ms: u64) -> Duration {
+    let backoff = config
+        .base_delay_ms
+        .saturating_mul(2u64.saturating_pow(attempt))
+        .min(config.max_delay_ms);
+    Duration::from_millis(backoff.saturating_add(jitter_ms))
+}
+
+/// Retry a future with jittered exponential backoff
+///
+/// Jitter spreads out retries from concurrent downloads that failed together.
+async fn retry_with_backoff<F, Fut, T>(config: &RetryConfig, mut f: F) -> Result<T>
 where
     F: FnMut() -> Fut,
     Fut: std::future::Future<Output = Result<T>>,
@@ -191,7 +887,7 @@ where
             Err(e) => {
                 retries += 1;
 
-                if retries > max_retries {
+                if retries > config.max_retries {
                     return Err(e);
                 }
 
@@ -214,10 +910,15 @@ where
                     return Err(e);
                 }
 
-                let delay = Duration::from_secs(2u64.pow(retries.saturating_sub(1)));
+                metrics::record_http_retry();
+                let jitter_ms = match config.jitter_ms {
+                    0 => 0,
+                    jitter => rand::random::<u64>() % jitter,
+                };
+                let delay = backoff_delay(config, retries - 1, jitter_ms);
                 warn!(
                     "Request failed (attempt {}/{}): {}. Retrying in {:?}...",
-                    retries, max_retries, e, delay
+                    retries, config.max_retries, e, delay
                 );
                 tokio::time::sleep(delay).await;
             }
@@ -225,16 +926,25 @@ where
     }
 }
 
+/// Collect the link targets to `product`'s data files from a directory listing page
+fn data_file_links(html: &str, product: DataProduct) -> Result<Vec<String>> {
+    let document = Html::parse_document(html);
+    let selector =
+        Selector::parse("a").map_err(|e| AppError::Parse(format!("Selector error: {:?}", e)))?;
+
+    Ok(document
+        .select(&selector)
+        .filter_map(|element| element.value().attr("href"))
+        .filter(|href| product.is_data_file(href))
+        .map(str::to_string)
+        .collect())
+}
+
 fn parse_filename(filename: &str, year: i32, base_url: &str) -> Option<FileInfo> {
-    // Format: CRNH0203-{YEAR}-{STATE}_{LOCATION}_{DISTANCE}_{DIRECTION}.txt
+    // Format: {PREFIX}{YEAR}-{STATE}_{LOCATION}_{DISTANCE}_{DIRECTION}.txt
     // Example: CRNH0203-2024-CA_Bodega_6_WSW.txt
 
-    let parts: Vec<&str> = filename.split('-').collect();
-    if parts.len() < 3 {
-        return None;
-    }
-
-    let location_part = parts[2];
+    let (_, location_part) = split_data_file_name(filename)?;
     let location_parts: Vec<&str> = location_part.split('_').collect();
 
     if location_parts.is_empty() {
@@ -295,4 +1005,83 @@ mod tests {
         assert_eq!(file_info.state, "TX");
         assert_eq!(file_info.station_name, "Austin_33_NW");
     }
+
+    /// A retryable (connection refused) HTTP error
+    async fn connect_error() -> AppError {
+        let err = Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .expect_err("Nothing should listen on port 1");
+        assert!(err.is_connect());
+        AppError::Http(err)
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_retries_http_errors() {
+        let mut errors = vec![connect_error().await, connect_error().await];
+        // Skip the real backoff
+        tokio::time::pause();
+
+        let calls = std::cell::Cell::new(0);
+        let result = retry_with_backoff(&RetryConfig::default(), || {
+            calls.set(calls.get() + 1);
+            let outcome = errors.pop().map_or(Ok("done"), Err);
+            async move { outcome }
+        })
+        .await;
+
+        assert_eq!(calls.get(), 3);
+        assert_eq!(result.unwrap(), "done");
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_does_not_retry_parse_errors() {
+        let calls = std::cell::Cell::new(0);
+        let result: Result<()> = retry_with_backoff(&RetryConfig::default(), || {
+            calls.set(calls.get() + 1);
+            async { Err(AppError::Parse("bad line".to_string())) }
+        })
+        .await;
+
+        assert_eq!(calls.get(), 1);
+        assert!(matches!(result, Err(AppError::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_zero_retries() {
+        let mut errors = vec![connect_error().await];
+
+        let calls = std::cell::Cell::new(0);
+        let config = RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        };
+        let result = retry_with_backoff(&config, || {
+            calls.set(calls.get() + 1);
+            let outcome = errors.pop().map_or(Ok(()), Err);
+            async move { outcome }
+        })
+        .await;
+
+        assert_eq!(calls.get(), 1);
+        assert!(matches!(result, Err(AppError::Http(_))));
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_up_to_max() {
+        let config = RetryConfig {
+            max_retries: 10,
+            base_delay_ms: 1000,
+            max_delay_ms: 5000,
+            jitter_ms: 1000,
+        };
+
+        assert_eq!(backoff_delay(&config, 0, 0), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(&config, 1, 0), Duration::from_millis(2000));
+        assert_eq!(backoff_delay(&config, 2, 250), Duration::from_millis(4250));
+        // Capped before jitter is added, so capped retries still spread out
+        assert_eq!(backoff_delay(&config, 3, 0), Duration::from_millis(5000));
+        assert_eq!(backoff_delay(&config, 63, 999), Duration::from_millis(5999));
+    }
 }
@@ -0,0 +1,109 @@
+use crate::db::{Repository, Wbanno};
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Duration, Utc};
+
+/// A run of consecutive expected observation times with no observation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GapInterval {
+    /// First missing observation time
+    pub start: DateTime<Utc>,
+    /// Last missing observation time (inclusive)
+    pub end: DateTime<Utc>,
+    /// Expected observations missing from `start` to `end`
+    pub missing_count: usize,
+}
+
+/// Find a station's missing observations between `start` and `end`
+///
+/// Observations are expected every `expected_interval` from `start`, and
+/// both bounds are inclusive. Unlike `Repository::find_observation_gaps`,
+/// gaps at the edges of the range (or a range with no observations at all)
+/// are reported.
+///
+/// # Returns
+/// Gaps in time order
+///
+/// # Errors
+/// Returns `AppError::InvalidData` if `expected_interval` is not positive or
+/// `end` is before `start`
+pub async fn detect_gaps(
+    repo: &Repository,
+    wbanno: Wbanno,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    expected_interval: Duration,
+) -> Result<Vec<GapInterval>> {
+    if expected_interval <= Duration::zero() {
+        return Err(AppError::InvalidData(format!(
+            "Expected interval must be positive, got {}",
+            expected_interval
+        )));
+    }
+    if end < start {
+        return Err(AppError::InvalidData(format!(
+            "Gap detection range ends ({}) before it starts ({})",
+            end, start
+        )));
+    }
+
+    let missing = repo
+        .find_missing_observation_times(wbanno, start, end, expected_interval)
+        .await?;
+    Ok(group_missing_times(&missing, expected_interval))
+}
+
+/// Merge sorted missing times one interval apart into gaps
+fn group_missing_times(missing: &[DateTime<Utc>], interval: Duration) -> Vec<GapInterval> {
+    let mut gaps: Vec<GapInterval> = Vec::new();
+    for &time in missing {
+        match gaps.last_mut() {
+            Some(gap) if time - gap.end == interval => {
+                gap.end = time;
+                gap.missing_count += 1;
+            }
+            _ => gaps.push(GapInterval {
+                start: time,
+                end: time,
+                missing_count: 1,
+            }),
+        }
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_missing_times() {
+        let base: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let hours = |h: &[i64]| -> Vec<DateTime<Utc>> {
+            h.iter().map(|&h| base + Duration::hours(h)).collect()
+        };
+
+        assert!(group_missing_times(&[], Duration::hours(1)).is_empty());
+
+        let gaps = group_missing_times(&hours(&[0, 1, 2, 5, 7, 8]), Duration::hours(1));
+        assert_eq!(
+            gaps,
+            vec![
+                GapInterval {
+                    start: base,
+                    end: base + Duration::hours(2),
+                    missing_count: 3,
+                },
+                GapInterval {
+                    start: base + Duration::hours(5),
+                    end: base + Duration::hours(5),
+                    missing_count: 1,
+                },
+                GapInterval {
+                    start: base + Duration::hours(7),
+                    end: base + Duration::hours(8),
+                    missing_count: 2,
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,15 @@
+#[cfg(feature = "api")]
+pub mod api;
+pub mod backfill;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod export;
 pub mod fetcher;
+pub mod gap_detector;
+pub mod metrics;
 pub mod parser;
 pub mod scheduler;
+pub mod setup;
+pub mod station_loader;
+pub mod watcher;
@@ -1,97 +1,941 @@
+use chrono::{NaiveDate, NaiveTime};
+use clap::{Parser, Subcommand, ValueEnum};
 use sqlx::postgres::PgPoolOptions;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::watch;
 use tracing::{error, info};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
-use uscrn_ingest::config::Config;
-use uscrn_ingest::db::Repository;
+use uscrn_ingest::backfill::BackfillJob;
+use uscrn_ingest::config::{Config, LocationFilter};
+use uscrn_ingest::db::{Repository, Wbanno};
+use uscrn_ingest::export;
+use uscrn_ingest::fetcher::Fetcher;
 use uscrn_ingest::scheduler::Scheduler;
+use uscrn_ingest::station_loader::{StationMetadataLoader, DEFAULT_STATIONS_URL};
+
+/// NOAA US Climate Reference Network data ingestion service
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Config file read at startup and written by `setup`
+    #[arg(short, long, global = true, default_value = CONFIG_PATH)]
+    config: PathBuf,
+    /// Log filter (e.g. `debug` or `info,sqlx=warn`), overriding RUST_LOG
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommand selected on the command line
+#[derive(Subcommand)]
+enum Command {
+    /// Run the periodic ingestion scheduler (default)
+    Run,
+    /// Run a single ingestion pass and exit
+    Once,
+    /// Run one ingestion pass for a year, a state or a single file and exit
+    Ingest {
+        /// Only this year, instead of the configured years
+        #[arg(long, conflicts_with = "file")]
+        year: Option<i32>,
+        /// Only this state (2-letter code), instead of the configured locations
+        #[arg(long, conflicts_with = "file")]
+        state: Option<String>,
+        /// Only this data file, e.g. CRNH0203-2024-TX_Austin_33_NW.txt
+        #[arg(long)]
+        file: Option<String>,
+    },
+    /// Load and validate the config file, then exit
+    CheckConfig,
+    /// Print the data files a run would fetch, without downloading them
+    ListFiles {
+        /// Only this year, instead of the configured years
+        #[arg(long)]
+        year: Option<i32>,
+    },
+    /// Load station metadata from the NOAA station list
+    LoadStations {
+        /// Station list URL
+        #[arg(default_value = DEFAULT_STATIONS_URL)]
+        url: String,
+    },
+    /// Print the years available on the NOAA server
+    ListYears,
+    /// Check database referential integrity and per-file observation
+    /// counts, exiting 1 on problems
+    Verify,
+    /// Print an estimate of how long an ingestion run would take
+    Estimate,
+    /// Print a JSON system status report
+    Health,
+    /// Print the latest processing error of the most recently failed files
+    Errors {
+        /// Most errors to list
+        #[arg(long, default_value_t = DEFAULT_ERRORS_LIMIT)]
+        limit: i64,
+    },
+    /// Backfill observation source_file_id from processed files
+    BackfillProvenance {
+        /// Report what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List stations near a point
+    NearestStations {
+        /// Latitude in decimal degrees
+        #[arg(long, allow_negative_numbers = true)]
+        lat: f64,
+        /// Longitude in decimal degrees
+        #[arg(long, allow_negative_numbers = true)]
+        lon: f64,
+        /// Search radius in kilometres
+        #[arg(long, default_value_t = DEFAULT_RADIUS_KM)]
+        radius_km: f64,
+    },
+    /// Print observation counts per year and state
+    DataCoverage,
+    /// Inspect processed file records
+    Files {
+        #[command(subcommand)]
+        command: FilesCommand,
+    },
+    /// Print aggregate observation statistics for a station
+    StationStats {
+        /// Station WBANNO
+        #[arg(long = "station")]
+        wbanno: Wbanno,
+    },
+    /// Print a station's uptime and per-sensor data availability
+    StationHealth {
+        /// Station WBANNO
+        #[arg(long = "station")]
+        wbanno: Wbanno,
+    },
+    /// List sharp soil moisture changes at a station
+    DetectAnomalies {
+        /// Station WBANNO
+        #[arg(long = "station")]
+        wbanno: Wbanno,
+        /// Soil depth in cm
+        #[arg(long = "depth", default_value_t = DEFAULT_ANOMALY_DEPTH_CM)]
+        depth_cm: u8,
+        /// Smallest soil moisture change (m^3/m^3) to report
+        #[arg(long, default_value_t = DEFAULT_ANOMALY_THRESHOLD)]
+        threshold: f32,
+    },
+    /// Re-ingest the data files listed by URL, one per line, in a file
+    Backfill {
+        /// File of data file URLs; blank lines and `#` comments are ignored
+        #[arg(long)]
+        urls_file: PathBuf,
+    },
+    /// Move observations before a year into the archive table
+    Archive {
+        /// First year to keep in the observations table
+        #[arg(long)]
+        before_year: i32,
+    },
+    /// Write observations to stdout as JSON Lines or CSV
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+        /// Only this station, instead of every station
+        #[arg(long)]
+        wbanno: Option<Wbanno>,
+        /// First UTC day to export, e.g. 2024-01-01
+        #[arg(long)]
+        start: NaiveDate,
+        /// Last UTC day to export (inclusive)
+        #[arg(long)]
+        end: NaiveDate,
+        /// CSV column groups, e.g. `temperature,soil_moisture` (default: all)
+        #[arg(long)]
+        columns: Option<String>,
+        /// Only observations whose quality flags are all at or below this (0 = good only)
+        #[arg(long)]
+        max_flag: Option<i32>,
+    },
+    /// Ingest files as they change in a local directory
+    Watch { dir: PathBuf },
+    /// Interactively generate a config file (defaults to the --config path)
+    Setup { output: Option<PathBuf> },
+}
+
+/// Output format of `export`
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Subcommands of `files`
+#[derive(Subcommand)]
+enum FilesCommand {
+    /// Print processed file counts by year and state
+    Summary,
+}
+
+/// Default config file location
+const CONFIG_PATH: &str = "config/config.yaml";
+
+/// Default search radius for `nearest-stations`
+const DEFAULT_RADIUS_KM: f64 = 100.0;
+
+/// Maximum stations listed by `nearest-stations`
+const NEAREST_STATIONS_LIMIT: usize = 10;
+
+/// Default soil depth (cm) for `detect-anomalies`
+const DEFAULT_ANOMALY_DEPTH_CM: u8 = 5;
+
+/// Default soil moisture change (m^3/m^3) reported by `detect-anomalies`
+const DEFAULT_ANOMALY_THRESHOLD: f32 = 0.05;
+
+/// Default number of errors listed by `errors`
+const DEFAULT_ERRORS_LIMIT: i64 = 20;
+
+/// How long `health` waits for a connection before reporting the database down
+const HEALTH_ACQUIRE_TIMEOUT_SECS: u64 = 5;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Load environment variables from .env file
     dotenvy::dotenv().ok();
 
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("info,uscrn_ingest=debug,sqlx=warn")),
+    let cli = Cli::parse();
+
+    // Initialize tracing; --log-level takes precedence over RUST_LOG
+    let env_filter = match &cli.log_level {
+        Some(level) => EnvFilter::try_new(level)?,
+        None => EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("info,uscrn_ingest=debug,sqlx=warn")),
+    };
+    // Commands whose stdout is data log to stderr so it can be piped
+    let log_writer = if matches!(
+        cli.command,
+        Some(
+            Command::Export { .. }
+                | Command::Health
+                | Command::ListFiles { .. }
+                | Command::ListYears
+                | Command::Errors { .. }
         )
-        .with(tracing_subscriber::fmt::layer())
+    ) {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(log_writer))
         .init();
 
+    install_panic_hook();
+
+    let command = cli.command.unwrap_or(Command::Run);
+
+    // Setup runs before config loading since the config may not exist yet
+    if let Command::Setup { output } = &command {
+        let output = output.as_ref().unwrap_or(&cli.config);
+        exit_on_error(Config::generate_interactive(output).await);
+        return Ok(());
+    }
+
     info!("USCRN Data Ingestion Service starting...");
 
     // Load configuration
-    let config = Config::load("config/config.yaml").map_err(|e| {
+    let mut config = Config::load(&cli.config).map_err(|e| {
         anyhow::anyhow!(
             "Failed to load configuration: {}\n\n\
              Make sure:\n\
-             1. config/config.yaml exists\n\
+             1. {} exists (run `uscrn-ingest setup` to create one)\n\
              2. All required environment variables are set (check .env.example)\n\
              3. Create a .env file if needed",
-            e
+            e,
+            cli.config.display()
         )
     })?;
     info!("Configuration loaded");
 
+    if let Command::CheckConfig = command {
+        println!("{} is valid", cli.config.display());
+        return Ok(());
+    }
+
+    if let Command::ListYears = command {
+        exit_on_error(list_years(&config).await);
+        return Ok(());
+    }
+
+    if let Command::ListFiles { year } = command {
+        exit_on_error(list_files(&config, year).await);
+        return Ok(());
+    }
+
+    // `ingest --state` replaces the configured filters, which would
+    // otherwise add their own stations to the state's
+    if let Command::Ingest {
+        state: Some(state), ..
+    } = &command
+    {
+        config.locations = LocationFilter {
+            states: vec![state.to_uppercase()],
+            ..LocationFilter::default()
+        };
+    }
+
+    // Health connects lazily so an unreachable database is reported as
+    // `db_connected: false` instead of failing before the report is built.
+    // It is read-only too, and reports the schema version as found.
+    if let Command::Health = command {
+        let options = config.database.connect_options().swap_remove(0);
+        let pool = PgPoolOptions::new()
+            .max_connections(config.database.max_connections)
+            .acquire_timeout(std::time::Duration::from_secs(HEALTH_ACQUIRE_TIMEOUT_SECS))
+            .connect_lazy_with(options);
+        let repository = Repository::new(pool).with_schema(config.database.schema.clone());
+        exit_on_error(health(&repository).await);
+        return Ok(());
+    }
+
     // Connect to database
-    let connection_string = config.database.connection_string();
-    let pool = PgPoolOptions::new()
-        .max_connections(config.database.max_connections)
-        .connect(&connection_string)
+    let pool = match config
+        .database
+        .connect_with(PgPoolOptions::new().max_connections(config.database.max_connections))
         .await
-        .map_err(|e| {
-            anyhow::anyhow!(
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!(
                 "Failed to connect to database: {}\n\n\
-                 Host: {}:{}\n\
-                 Database: {}\n\
-                 User: {}\n\n\
+                 Connection: {}\n\n\
                  Common fixes:\n\
                  1. Ensure PostgreSQL is running\n\
                  2. Check username/password are correct (DB_USER, DB_PASSWORD)\n\
                  3. Verify database exists: createdb {}\n\
                  4. Check host and port (DB_HOST, DB_PORT)",
                 e,
-                config.database.host,
-                config.database.port,
-                config.database.name,
-                config.database.user,
+                config.database.masked(),
                 config.database.name
-            )
-        })?;
+            );
+            std::process::exit(e.exit_code());
+        }
+    };
+
+    info!("Connected to database: {}", config.database.masked());
 
-    info!(
-        "Connected to database: {}@{}:{}/{}",
-        config.database.user, config.database.host, config.database.port, config.database.name
+    let repository = Arc::new(
+        Repository::new(pool)
+            .with_schema(config.database.schema.clone())
+            .with_conflict_strategy(config.database.conflict_strategy)
+            .with_stream_chunk_size(config.database.stream_chunk_size),
     );
 
-    // Create repository and run migrations
-    let repository = Arc::new(Repository::new(pool));
-    repository.run_migrations().await?;
+    // Estimate runs before migrations so it performs no writes
+    if let Command::Estimate = command {
+        exit_on_error(estimate(config, repository).await);
+        return Ok(());
+    }
+
+    // Refuse a schema newer than this binary before migrating, since the
+    // migrator would otherwise fail on the unknown versions first
+    exit_on_error(repository.check_migration_version().await);
+    exit_on_error(repository.run_migrations().await);
+
+    if let Command::LoadStations { url } = command {
+        exit_on_error(load_stations(&repository, &url).await);
+        return Ok(());
+    }
+
+    if let Command::Errors { limit } = command {
+        exit_on_error(errors(&repository, limit).await);
+        return Ok(());
+    }
+
+    if let Command::Verify = command {
+        exit_on_error(verify(&config, &repository).await);
+        return Ok(());
+    }
+
+    if let Command::BackfillProvenance { dry_run } = command {
+        exit_on_error(backfill_provenance(&repository, dry_run).await);
+        return Ok(());
+    }
+
+    if let Command::NearestStations {
+        lat,
+        lon,
+        radius_km,
+    } = command
+    {
+        exit_on_error(nearest_stations(&repository, lat, lon, radius_km).await);
+        return Ok(());
+    }
+
+    if let Command::DataCoverage = command {
+        exit_on_error(data_coverage(&repository).await);
+        return Ok(());
+    }
+
+    if let Command::Files {
+        command: FilesCommand::Summary,
+    } = command
+    {
+        exit_on_error(files_summary(&repository).await);
+        return Ok(());
+    }
+
+    if let Command::StationStats { wbanno } = command {
+        exit_on_error(station_stats(&repository, wbanno).await);
+        return Ok(());
+    }
+
+    if let Command::StationHealth { wbanno } = command {
+        exit_on_error(station_health(&repository, wbanno).await);
+        return Ok(());
+    }
+
+    if let Command::DetectAnomalies {
+        wbanno,
+        depth_cm,
+        threshold,
+    } = command
+    {
+        exit_on_error(detect_anomalies(&repository, wbanno, depth_cm, threshold).await);
+        return Ok(());
+    }
+
+    if let Command::Archive { before_year } = command {
+        exit_on_error(archive(&repository, before_year).await);
+        return Ok(());
+    }
+
+    if let Command::Export {
+        format,
+        wbanno,
+        start,
+        end,
+        columns,
+        max_flag,
+    } = command
+    {
+        exit_on_error(
+            export(
+                &repository,
+                format,
+                wbanno,
+                max_flag,
+                start,
+                end,
+                columns.as_deref(),
+            )
+            .await,
+        );
+        return Ok(());
+    }
+
+    if config.metrics.enabled {
+        uscrn_ingest::metrics::install(&config.metrics)?;
+    }
 
     // Set up shutdown signal
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     // Spawn signal handler
-    tokio::spawn(async move {
+    task_panic_guard("signal handler", async move {
         shutdown_signal().await;
         let _ = shutdown_tx.send(true);
     });
 
+    // The API only runs alongside the long-running scheduler commands
+    #[cfg(feature = "api")]
+    if config.api_server.enabled && matches!(command, Command::Run | Command::Watch { .. }) {
+        let api_config = config.api_server.clone();
+        let repository = Arc::clone(&repository);
+        let shutdown_rx = shutdown_rx.clone();
+        task_panic_guard("api server", async move {
+            if let Err(e) = uscrn_ingest::api::serve(&api_config, repository, shutdown_rx).await {
+                error!("API server error: {}", e);
+            }
+        });
+    }
+
     // Create and run scheduler
     let mut scheduler = Scheduler::new(config, repository, shutdown_rx);
+    if let Command::Ingest {
+        year: Some(year), ..
+    } = command
+    {
+        scheduler = scheduler.with_years_override(vec![year]);
+    }
 
-    if let Err(e) = scheduler.run().await {
-        error!("Scheduler error: {}", e);
+    if let Command::Ingest {
+        file: Some(file), ..
+    } = &command
+    {
+        exit_on_error(ingest_file(&scheduler, file).await);
+    } else if let Command::Backfill { urls_file } = &command {
+        exit_on_error(backfill(&scheduler, urls_file).await);
+    } else if let Command::Once | Command::Ingest { .. } = command {
+        exit_on_error(scheduler.run_ingestion().await.map(|_| ()));
+    } else if let Command::Watch { dir } = command {
+        // A daemon that stops on an error must exit non-zero so its
+        // supervisor restarts it
+        exit_on_error(scheduler.watch(&dir).await);
+    } else {
+        exit_on_error(scheduler.run().await);
     }
 
     info!("USCRN Data Ingestion Service shutting down");
     Ok(())
 }
 
+/// Route panics through tracing so they reach the structured log output
+///
+/// The default hook still runs afterwards to print the backtrace to stderr.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        error!("PANIC: {}", info);
+        default_hook(info);
+    }));
+}
+
+/// Spawn a task and log if it panics
+///
+/// A panicking task otherwise only surfaces through its `JoinHandle`, which
+/// fire-and-forget spawns never await. This needs the release profile's
+/// `panic = "unwind"`; under `abort` the process exits before the handle
+/// reports the panic, and only `install_panic_hook` logs it.
+fn task_panic_guard<F>(name: &'static str, future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let handle = tokio::spawn(future);
+    tokio::spawn(async move {
+        if let Err(e) = handle.await {
+            if e.is_panic() {
+                error!("Task '{}' panicked: {}", name, e);
+            }
+        }
+    });
+}
+
+/// Exit with the error's category code when a one-shot command fails
+fn exit_on_error(result: uscrn_ingest::error::Result<()>) {
+    if let Err(e) = result {
+        error!("Command failed: {}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+async fn list_years(config: &Config) -> uscrn_ingest::error::Result<()> {
+    let fetcher = Fetcher::from_config(&config.source)?;
+    for year in fetcher.list_years().await? {
+        println!("{}", year);
+    }
+    Ok(())
+}
+
+async fn list_files(config: &Config, year: Option<i32>) -> uscrn_ingest::error::Result<()> {
+    let fetcher = Fetcher::from_config(&config.source)?;
+    let years = match year {
+        Some(year) => vec![year],
+        None => config.source.years_to_fetch.get_years(),
+    };
+    for year in years {
+        for file in fetcher.list_files_for_year(year, &config.locations).await? {
+            if !config.source.is_skipped(&file.name) {
+                println!("{}", file.url);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn ingest_file(scheduler: &Scheduler, file: &str) -> uscrn_ingest::error::Result<()> {
+    let rows = scheduler.ingest_file(file).await?;
+    println!("Ingested {} observations from {}", rows, file);
+    Ok(())
+}
+
+async fn backfill(scheduler: &Scheduler, urls_file: &Path) -> uscrn_ingest::error::Result<()> {
+    let job = BackfillJob::from_urls_file(urls_file)?;
+    let report = job.run(scheduler).await?;
+
+    for file in &report.files {
+        match &file.result {
+            Ok(rows) => println!("OK      {} ({} observations)", file.url, rows),
+            Err(e) => println!("FAILED  {}: {}", file.url, e),
+        }
+    }
+    println!(
+        "{} of {} files backfilled",
+        report.succeeded(),
+        report.files.len()
+    );
+
+    if report.failed() > 0 {
+        return Err(uscrn_ingest::error::AppError::InvalidData(format!(
+            "{} files failed to backfill",
+            report.failed()
+        )));
+    }
+    Ok(())
+}
+
+async fn load_stations(repository: &Repository, url: &str) -> uscrn_ingest::error::Result<()> {
+    let stations = StationMetadataLoader::load_from_url(url).await?;
+    repository.batch_upsert_stations(&stations).await?;
+    info!("Upserted {} stations from station list", stations.len());
+    Ok(())
+}
+
+async fn estimate(config: Config, repository: Arc<Repository>) -> uscrn_ingest::error::Result<()> {
+    let fetcher = Fetcher::from_config(&config.source)?;
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, repository, shutdown_rx);
+
+    let duration = scheduler.estimate_run_duration(&fetcher).await?;
+    let secs = duration.as_secs();
+    println!(
+        "Estimated run duration: {}h {}m {}s",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    );
+    Ok(())
+}
+
+async fn backfill_provenance(
+    repository: &Repository,
+    dry_run: bool,
+) -> uscrn_ingest::error::Result<()> {
+    let count = repository.backfill_source_file_ids(dry_run).await?;
+    if dry_run {
+        println!("{} observations would be updated", count);
+    } else {
+        println!("{} observations updated", count);
+    }
+    Ok(())
+}
+
+async fn nearest_stations(
+    repository: &Repository,
+    lat: f64,
+    lon: f64,
+    radius_km: f64,
+) -> uscrn_ingest::error::Result<()> {
+    let stations = repository
+        .find_nearest_stations(lat, lon, radius_km, NEAREST_STATIONS_LIMIT)
+        .await?;
+
+    if stations.is_empty() {
+        println!("No stations within {} km", radius_km);
+        return Ok(());
+    }
+
+    for (station, distance) in stations {
+        println!(
+            "{:>6}  {:>8.1} km  {}  {}",
+            station.wbanno,
+            distance,
+            station.state,
+            station.name.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+async fn data_coverage(repository: &Repository) -> uscrn_ingest::error::Result<()> {
+    let years = repository.list_years_with_data().await?;
+    let states = repository.list_states_with_data().await?;
+    if years.is_empty() {
+        println!("No observations stored");
+        return Ok(());
+    }
+
+    let counts: HashMap<(i32, String), i64> = repository
+        .count_observations_by_year_and_state()
+        .await?
+        .into_iter()
+        .map(|(year, state, count)| ((year, state), count))
+        .collect();
+
+    print!("{:<6}", "Year");
+    for state in &states {
+        print!("{:>9}", state);
+    }
+    println!();
+    for year in years {
+        print!("{:<6}", year);
+        for state in &states {
+            let count = counts.get(&(year, state.clone())).copied().unwrap_or(0);
+            print!("{:>9}", count);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+async fn files_summary(repository: &Repository) -> uscrn_ingest::error::Result<()> {
+    let summary = repository.get_processed_files_summary().await?;
+    if summary.is_empty() {
+        println!("No files processed");
+        return Ok(());
+    }
+
+    println!(
+        "{:<6}{:<7}{:>8}{:>11}{:>8}{:>9}{:>14}{:>16}",
+        "Year",
+        "State",
+        "Files",
+        "Completed",
+        "Failed",
+        "Skipped",
+        "Observations",
+        "Parse failures"
+    );
+    for row in summary {
+        println!(
+            "{:<6}{:<7}{:>8}{:>11}{:>8}{:>9}{:>14}{:>16}",
+            row.year,
+            row.state,
+            row.total_files,
+            row.completed_files,
+            row.failed_files,
+            row.skipped_files,
+            row.total_observations,
+            row.total_parse_failures
+        );
+    }
+    Ok(())
+}
+
+async fn export(
+    repository: &Repository,
+    format: ExportFormat,
+    wbanno: Option<Wbanno>,
+    max_flag: Option<i32>,
+    start: NaiveDate,
+    end: NaiveDate,
+    columns: Option<&str>,
+) -> uscrn_ingest::error::Result<()> {
+    if end < start {
+        return Err(uscrn_ingest::error::AppError::InvalidData(format!(
+            "--end ({}) is before --start ({})",
+            end, start
+        )));
+    }
+    if columns.is_some() && matches!(format, ExportFormat::Jsonl) {
+        return Err(uscrn_ingest::error::AppError::InvalidData(
+            "--columns applies only to --format csv".to_string(),
+        ));
+    }
+    // Whole days: from midnight on start up to midnight after end
+    let start_utc = start.and_time(NaiveTime::MIN).and_utc();
+    let end_utc = (end + chrono::Days::new(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let mut stdout = tokio::io::stdout();
+    let written = match format {
+        ExportFormat::Jsonl => {
+            export::export_observations_jsonl(
+                repository,
+                wbanno,
+                max_flag,
+                start_utc,
+                end_utc,
+                &mut stdout,
+            )
+            .await?
+        }
+        ExportFormat::Csv => {
+            let columns = columns
+                .map(export::parse_columns)
+                .transpose()?
+                .unwrap_or_default();
+            export::export_observations_csv(
+                repository,
+                wbanno,
+                max_flag,
+                start_utc,
+                end_utc,
+                columns,
+                &mut stdout,
+            )
+            .await?
+        }
+    };
+    info!("Exported {} observations", written);
+    Ok(())
+}
+
+async fn station_stats(repository: &Repository, wbanno: Wbanno) -> uscrn_ingest::error::Result<()> {
+    let stats = repository.get_observation_stats(wbanno).await?;
+    let show = |v: Option<f32>| v.map_or("-".to_string(), |v| format!("{:.1}", v));
+
+    println!("Station:                {}", stats.wbanno);
+    println!("Observations:           {}", stats.total_count);
+    println!(
+        "Soil moisture coverage: {:.1}%",
+        stats.complete_soil_pct * 100.0
+    );
+    println!("Mean temperature (C):   {}", show(stats.avg_t_hr_avg));
+    println!("Min temperature (C):    {}", show(stats.min_t_min));
+    println!("Max temperature (C):    {}", show(stats.max_t_max));
+    println!(
+        "Total precip (mm):      {}",
+        stats
+            .total_precip
+            .map_or("-".to_string(), |p| format!("{:.1}", p))
+    );
+    println!(
+        "Span (days):            {}",
+        stats
+            .observation_span_days
+            .map_or("-".to_string(), |d| d.to_string())
+    );
+    Ok(())
+}
+
+async fn station_health(
+    repository: &Repository,
+    wbanno: Wbanno,
+) -> uscrn_ingest::error::Result<()> {
+    let stats = repository.compute_station_statistics(wbanno).await?;
+    let pct = |fraction: f64| format!("{:.1}%", fraction * 100.0);
+
+    println!("Station:         {}", stats.wbanno);
+    println!("Observations:    {}", stats.total_records);
+    println!(
+        "Years:           {}-{}",
+        stats.year_range.0, stats.year_range.1
+    );
+    println!("Uptime:          {}", pct(stats.uptime_pct));
+    println!("Sensor availability:");
+    println!("  Temperature:   {}", pct(stats.sensors.temperature));
+    println!("  Solar:         {}", pct(stats.sensors.solar));
+    println!("  Precip:        {}", pct(stats.sensors.precip));
+    println!("  Humidity:      {}", pct(stats.sensors.rh));
+    println!("  Soil moisture: {}", pct(stats.sensors.soil_moisture));
+    println!("  Soil temp:     {}", pct(stats.sensors.soil_temp));
+    Ok(())
+}
+
+async fn detect_anomalies(
+    repository: &Repository,
+    wbanno: Wbanno,
+    depth_cm: u8,
+    threshold: f32,
+) -> uscrn_ingest::error::Result<()> {
+    let events = repository
+        .detect_soil_moisture_anomalies(wbanno, depth_cm, threshold)
+        .await?;
+
+    if events.is_empty() {
+        println!(
+            "No soil moisture changes above {} at {} cm",
+            threshold, depth_cm
+        );
+        return Ok(());
+    }
+
+    for event in events {
+        println!(
+            "{}  {:.3} -> {:.3}  (delta {:.3})",
+            event.utc_datetime, event.previous, event.current, event.delta
+        );
+    }
+    Ok(())
+}
+
+async fn archive(repository: &Repository, before_year: i32) -> uscrn_ingest::error::Result<()> {
+    let count = repository.archive_observations(before_year).await?;
+    println!(
+        "{} observations from before {} moved to observations_archive",
+        count, before_year
+    );
+    Ok(())
+}
+
+async fn health(repository: &Repository) -> uscrn_ingest::error::Result<()> {
+    let report = repository.health_report().await?;
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| uscrn_ingest::error::AppError::InvalidData(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+async fn errors(repository: &Repository, limit: i64) -> uscrn_ingest::error::Result<()> {
+    let errors = repository
+        .get_recent_ingestion_errors(chrono::DateTime::<chrono::Utc>::UNIX_EPOCH, limit)
+        .await?;
+
+    if errors.is_empty() {
+        println!("No ingestion errors recorded");
+        return Ok(());
+    }
+
+    for e in errors {
+        println!(
+            "{}  {}  (retry {})  {}",
+            e.occurred_at.format("%Y-%m-%d %H:%M:%S"),
+            e.file_name,
+            e.retry_count,
+            e.error_message
+        );
+    }
+    Ok(())
+}
+
+async fn verify(config: &Config, repository: &Repository) -> uscrn_ingest::error::Result<()> {
+    let report = repository.check_foreign_key_integrity().await?;
+    println!(
+        "Orphaned observations:         {}",
+        report.orphaned_observations
+    );
+    println!(
+        "Orphaned processed files:      {}",
+        report.orphaned_processed_files
+    );
+    println!(
+        "Stations without observations: {}",
+        report.stations_without_observations
+    );
+
+    let mismatches = repository
+        .find_file_count_mismatches(
+            config.retention.cutoff(),
+            config.source.reprocess_window_hours.is_some(),
+        )
+        .await?;
+    for mismatch in &mismatches {
+        println!(
+            "  {}: {} rows processed, {} observations stored",
+            mismatch.file_name, mismatch.expected, mismatch.actual
+        );
+    }
+    println!("Files with count mismatches:   {}", mismatches.len());
+
+    if !report.is_clean() || !mismatches.is_empty() {
+        error!("Database integrity check failed");
+        std::process::exit(1);
+    }
+
+    info!("Database integrity check passed");
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         if let Err(e) = signal::ctrl_c().await {
@@ -0,0 +1,119 @@
+use crate::config::MetricsConfig;
+use crate::error::{AppError, Result};
+use ::metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::info;
+
+pub const OBSERVATIONS_INGESTED_TOTAL: &str = "observations_ingested_total";
+pub const OBSERVATIONS_UPDATED_TOTAL: &str = "observations_updated_total";
+pub const FILES_PROCESSED_TOTAL: &str = "files_processed_total";
+pub const FILES_SKIPPED_TOTAL: &str = "files_skipped_total";
+pub const PARSE_FAILURES_TOTAL: &str = "parse_failures_total";
+pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+pub const HTTP_RETRIES_TOTAL: &str = "http_retries_total";
+pub const FILE_PARSE_DURATION_SECONDS: &str = "file_parse_duration_seconds";
+pub const FILE_DOWNLOAD_DURATION_SECONDS: &str = "file_download_duration_seconds";
+pub const BATCH_INSERT_DURATION_SECONDS: &str = "batch_insert_duration_seconds";
+
+/// Start the Prometheus exporter's HTTP listener on `config.bind_address`
+///
+/// Until this is called, recorded metrics are discarded.
+///
+/// # Errors
+/// Returns `AppError::Config` if the address is invalid or the exporter
+/// cannot be started
+pub fn install(config: &MetricsConfig) -> Result<()> {
+    let address: SocketAddr = config.bind_address.parse().map_err(|e| {
+        AppError::Config(format!(
+            "Invalid metrics bind_address '{}': {}",
+            config.bind_address, e
+        ))
+    })?;
+
+    PrometheusBuilder::new()
+        .with_http_listener(address)
+        .install()
+        .map_err(|e| {
+            AppError::Config(format!(
+                "Failed to start metrics server on {}: {}",
+                address, e
+            ))
+        })?;
+    describe();
+
+    info!("Serving Prometheus metrics on http://{}/metrics", address);
+    Ok(())
+}
+
+fn describe() {
+    describe_counter!(
+        OBSERVATIONS_INGESTED_TOTAL,
+        "Observation rows newly inserted"
+    );
+    describe_counter!(
+        OBSERVATIONS_UPDATED_TOTAL,
+        "Observation rows that replaced an existing row"
+    );
+    describe_counter!(
+        FILES_PROCESSED_TOTAL,
+        "Files whose observations were stored"
+    );
+    describe_counter!(
+        FILES_SKIPPED_TOTAL,
+        "Files skipped as rejected, filtered out or not modified"
+    );
+    describe_counter!(PARSE_FAILURES_TOTAL, "Data lines that failed to parse");
+    describe_counter!(HTTP_REQUESTS_TOTAL, "HTTP requests sent to the data source");
+    describe_counter!(
+        HTTP_RETRIES_TOTAL,
+        "HTTP requests retried after a transient error"
+    );
+    describe_histogram!(
+        FILE_PARSE_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time to parse one data file"
+    );
+    describe_histogram!(
+        FILE_DOWNLOAD_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time to download one data file, including retries"
+    );
+    describe_histogram!(
+        BATCH_INSERT_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time to insert one file's observations"
+    );
+}
+
+pub(crate) fn record_file_processed() {
+    counter!(FILES_PROCESSED_TOTAL).increment(1);
+}
+
+pub(crate) fn record_file_skipped() {
+    counter!(FILES_SKIPPED_TOTAL).increment(1);
+}
+
+pub(crate) fn record_parse(duration: Duration, parse_failures: usize) {
+    histogram!(FILE_PARSE_DURATION_SECONDS).record(duration.as_secs_f64());
+    counter!(PARSE_FAILURES_TOTAL).increment(parse_failures as u64);
+}
+
+pub(crate) fn record_http_request() {
+    counter!(HTTP_REQUESTS_TOTAL).increment(1);
+}
+
+pub(crate) fn record_http_retry() {
+    counter!(HTTP_RETRIES_TOTAL).increment(1);
+}
+
+pub(crate) fn record_download(duration: Duration) {
+    histogram!(FILE_DOWNLOAD_DURATION_SECONDS).record(duration.as_secs_f64());
+}
+
+pub(crate) fn record_insert(duration: Duration, inserted: usize, updated: usize) {
+    histogram!(BATCH_INSERT_DURATION_SECONDS).record(duration.as_secs_f64());
+    counter!(OBSERVATIONS_INGESTED_TOTAL).increment(inserted as u64);
+    counter!(OBSERVATIONS_UPDATED_TOTAL).increment(updated as u64);
+}
@@ -0,0 +1,224 @@
+//! Zero-based column indexes for the USCRN hourly02 format
+//!
+//! From the NOAA hourly02 README. Fields are whitespace-separated; numbers in
+//! the README are 1-based, these are 0-based indexes into the split line.
+
+pub const WBANNO_COL: usize = 0;
+pub const UTC_DATE_COL: usize = 1;
+pub const UTC_TIME_COL: usize = 2;
+pub const LST_DATE_COL: usize = 3;
+pub const LST_TIME_COL: usize = 4;
+pub const CRX_VN_COL: usize = 5;
+pub const LONGITUDE_COL: usize = 6;
+pub const LATITUDE_COL: usize = 7;
+
+pub const T_CALC_COL: usize = 8;
+pub const T_HR_AVG_COL: usize = 9;
+pub const T_MAX_COL: usize = 10;
+pub const T_MIN_COL: usize = 11;
+pub const P_CALC_COL: usize = 12;
+
+pub const SOLARAD_COL: usize = 13;
+pub const SOLARAD_FLAG_COL: usize = 14;
+pub const SOLARAD_MAX_COL: usize = 15;
+pub const SOLARAD_MAX_FLAG_COL: usize = 16;
+pub const SOLARAD_MIN_COL: usize = 17;
+pub const SOLARAD_MIN_FLAG_COL: usize = 18;
+
+pub const SUR_TEMP_TYPE_COL: usize = 19;
+pub const SUR_TEMP_COL: usize = 20;
+pub const SUR_TEMP_FLAG_COL: usize = 21;
+pub const SUR_TEMP_MAX_COL: usize = 22;
+pub const SUR_TEMP_MAX_FLAG_COL: usize = 23;
+pub const SUR_TEMP_MIN_COL: usize = 24;
+pub const SUR_TEMP_MIN_FLAG_COL: usize = 25;
+
+pub const RH_HR_AVG_COL: usize = 26;
+pub const RH_HR_AVG_FLAG_COL: usize = 27;
+
+pub const SOIL_MOISTURE_5_COL: usize = 28;
+pub const SOIL_MOISTURE_10_COL: usize = 29;
+pub const SOIL_MOISTURE_20_COL: usize = 30;
+pub const SOIL_MOISTURE_50_COL: usize = 31;
+pub const SOIL_MOISTURE_100_COL: usize = 32;
+
+pub const SOIL_TEMP_5_COL: usize = 33;
+pub const SOIL_TEMP_10_COL: usize = 34;
+pub const SOIL_TEMP_20_COL: usize = 35;
+pub const SOIL_TEMP_50_COL: usize = 36;
+pub const SOIL_TEMP_100_COL: usize = 37;
+
+/// Minimum fields for a line to be parsed (through RH_HR_AVG_FLAG)
+pub const MIN_FIELDS: usize = RH_HR_AVG_FLAG_COL + 1;
+
+/// Total fields in the full format (through SOIL_TEMP_100)
+pub const TOTAL_FIELDS: usize = SOIL_TEMP_100_COL + 1;
+
+/// Column names by index, as reported in `ParseError::field_name`
+pub const COLUMN_NAMES: [&str; TOTAL_FIELDS] = [
+    "wbanno",
+    "utc_date",
+    "utc_time",
+    "lst_date",
+    "lst_time",
+    "crx_vn",
+    "longitude",
+    "latitude",
+    "t_calc",
+    "t_hr_avg",
+    "t_max",
+    "t_min",
+    "p_calc",
+    "solarad",
+    "solarad_flag",
+    "solarad_max",
+    "solarad_max_flag",
+    "solarad_min",
+    "solarad_min_flag",
+    "sur_temp_type",
+    "sur_temp",
+    "sur_temp_flag",
+    "sur_temp_max",
+    "sur_temp_max_flag",
+    "sur_temp_min",
+    "sur_temp_min_flag",
+    "rh_hr_avg",
+    "rh_hr_avg_flag",
+    "soil_moisture_5",
+    "soil_moisture_10",
+    "soil_moisture_20",
+    "soil_moisture_50",
+    "soil_moisture_100",
+    "soil_temp_5",
+    "soil_temp_10",
+    "soil_temp_20",
+    "soil_temp_50",
+    "soil_temp_100",
+];
+
+/// Zero-based column indexes for the USCRN subhourly01 (5-minute) format
+///
+/// From the NOAA subhourly01 README. The date, time, version and position
+/// columns match hourly02; the measurements that follow do not.
+pub mod subhourly {
+    pub const AIR_TEMPERATURE_COL: usize = 8;
+    pub const PRECIPITATION_COL: usize = 9;
+    pub const SOLAR_RADIATION_COL: usize = 10;
+    pub const SR_FLAG_COL: usize = 11;
+    pub const SURFACE_TEMPERATURE_COL: usize = 12;
+    pub const ST_TYPE_COL: usize = 13;
+    pub const ST_FLAG_COL: usize = 14;
+    pub const RELATIVE_HUMIDITY_COL: usize = 15;
+    pub const RH_FLAG_COL: usize = 16;
+    pub const SOIL_MOISTURE_5_COL: usize = 17;
+    pub const SOIL_TEMPERATURE_5_COL: usize = 18;
+    pub const WETNESS_COL: usize = 19;
+    pub const WET_FLAG_COL: usize = 20;
+    pub const WIND_1_5_COL: usize = 21;
+    pub const WIND_FLAG_COL: usize = 22;
+
+    /// Fields in a subhourly01 line (through WIND_FLAG)
+    pub const TOTAL_FIELDS: usize = WIND_FLAG_COL + 1;
+
+    /// Column names by index, as reported in `ParseError::field_name`
+    pub const COLUMN_NAMES: [&str; TOTAL_FIELDS] = [
+        "wbanno",
+        "utc_date",
+        "utc_time",
+        "lst_date",
+        "lst_time",
+        "crx_vn",
+        "longitude",
+        "latitude",
+        "air_temperature",
+        "precipitation",
+        "solar_radiation",
+        "sr_flag",
+        "surface_temperature",
+        "st_type",
+        "st_flag",
+        "relative_humidity",
+        "rh_flag",
+        "soil_moisture_5",
+        "soil_temperature_5",
+        "wetness",
+        "wet_flag",
+        "wind_1_5",
+        "wind_flag",
+    ];
+}
+
+/// Zero-based column indexes for the USCRN daily01 format
+///
+/// From the NOAA daily01 README. Each line summarizes one local standard
+/// time day, so there is no time column.
+pub mod daily {
+    pub const WBANNO_COL: usize = 0;
+    pub const LST_DATE_COL: usize = 1;
+    pub const CRX_VN_COL: usize = 2;
+    pub const LONGITUDE_COL: usize = 3;
+    pub const LATITUDE_COL: usize = 4;
+
+    pub const T_DAILY_MAX_COL: usize = 5;
+    pub const T_DAILY_MIN_COL: usize = 6;
+    pub const T_DAILY_MEAN_COL: usize = 7;
+    pub const T_DAILY_AVG_COL: usize = 8;
+    pub const P_DAILY_CALC_COL: usize = 9;
+    pub const SOLARAD_DAILY_COL: usize = 10;
+
+    pub const SUR_TEMP_DAILY_TYPE_COL: usize = 11;
+    pub const SUR_TEMP_DAILY_MAX_COL: usize = 12;
+    pub const SUR_TEMP_DAILY_MIN_COL: usize = 13;
+    pub const SUR_TEMP_DAILY_AVG_COL: usize = 14;
+
+    pub const RH_DAILY_MAX_COL: usize = 15;
+    pub const RH_DAILY_MIN_COL: usize = 16;
+    pub const RH_DAILY_AVG_COL: usize = 17;
+
+    pub const SOIL_MOISTURE_5_DAILY_COL: usize = 18;
+    pub const SOIL_MOISTURE_10_DAILY_COL: usize = 19;
+    pub const SOIL_MOISTURE_20_DAILY_COL: usize = 20;
+    pub const SOIL_MOISTURE_50_DAILY_COL: usize = 21;
+    pub const SOIL_MOISTURE_100_DAILY_COL: usize = 22;
+
+    pub const SOIL_TEMP_5_DAILY_COL: usize = 23;
+    pub const SOIL_TEMP_10_DAILY_COL: usize = 24;
+    pub const SOIL_TEMP_20_DAILY_COL: usize = 25;
+    pub const SOIL_TEMP_50_DAILY_COL: usize = 26;
+    pub const SOIL_TEMP_100_DAILY_COL: usize = 27;
+
+    /// Fields in a daily01 line (through SOIL_TEMP_100_DAILY)
+    pub const TOTAL_FIELDS: usize = SOIL_TEMP_100_DAILY_COL + 1;
+
+    /// Column names by index, as reported in `ParseError::field_name`
+    pub const COLUMN_NAMES: [&str; TOTAL_FIELDS] = [
+        "wbanno",
+        "lst_date",
+        "crx_vn",
+        "longitude",
+        "latitude",
+        "t_daily_max",
+        "t_daily_min",
+        "t_daily_mean",
+        "t_daily_avg",
+        "p_daily_calc",
+        "solarad_daily",
+        "sur_temp_daily_type",
+        "sur_temp_daily_max",
+        "sur_temp_daily_min",
+        "sur_temp_daily_avg",
+        "rh_daily_max",
+        "rh_daily_min",
+        "rh_daily_avg",
+        "soil_moisture_5_daily",
+        "soil_moisture_10_daily",
+        "soil_moisture_20_daily",
+        "soil_moisture_50_daily",
+        "soil_moisture_100_daily",
+        "soil_temp_5_daily",
+        "soil_temp_10_daily",
+        "soil_temp_20_daily",
+        "soil_temp_50_daily",
+        "soil_temp_100_daily",
+    ];
+}
@@ -0,0 +1,1202 @@
+pub mod columns;
+mod streaming;
+
+use crate::config::FieldSeparator;
+use crate::db::models::{NewDailyObservation, NewObservation, NewSubhourlyObservation, Wbanno};
+use crate::error::{AppError, ParseError, Result};
+use crate::metrics;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use columns::*;
+use tracing::warn;
+
+pub use streaming::StreamingParser;
+
+const MISSING_VALUE: f32 = -9999.0;
+const MISSING_VALUE_INT: i32 = -9999;
+
+/// Default failure threshold - fail if more than 10% of lines fail to parse
+pub const DEFAULT_FAILURE_THRESHOLD: f64 = 0.10;
+
+/// How data lines are split and read, for `Parser::parse_file_with_config`
+///
+/// The defaults match the hourly02 product. Some derived products mark
+/// missing values with other sentinels, such as -99.99 or -9.999.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseConfig {
+    pub separator: FieldSeparator,
+    /// Float field value that parses as `None`
+    pub missing_value_float: f32,
+    /// Integer field value that parses as `None`
+    pub missing_value_int: i32,
+    /// Largest fraction of non-empty lines allowed to fail; see `ParseStats::check`
+    pub failure_threshold: f64,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            separator: FieldSeparator::default(),
+            missing_value_float: MISSING_VALUE,
+            missing_value_int: MISSING_VALUE_INT,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// The default config with `separator`
+    pub fn with_separator(separator: FieldSeparator) -> Self {
+        Self {
+            separator,
+            ..Self::default()
+        }
+    }
+
+    /// An absent field or the missing-value sentinel parses as `None`
+    fn parse_optional_int(&self, s: Option<&str>) -> std::result::Result<Option<i32>, String> {
+        let Some(s) = s else {
+            return Ok(None);
+        };
+        let val = parse_int(s)?;
+        Ok((val != self.missing_value_int).then_some(val))
+    }
+
+    /// An absent field or the missing-value sentinel parses as `None`
+    ///
+    /// The sentinel is matched exactly, so "-9999" and "-9999.0" are both
+    /// missing but a real reading close to a small sentinel is kept.
+    fn parse_optional_float(&self, s: Option<&str>) -> std::result::Result<Option<f32>, String> {
+        let Some(s) = s else {
+            return Ok(None);
+        };
+        let val = s
+            .parse::<f32>()
+            .map_err(|e| format!("Failed to parse float '{}': {}", s, e))?;
+        Ok((val != self.missing_value_float).then_some(val))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseStats {
+    pub total_lines: usize,
+    pub parsed_successfully: usize,
+    pub parse_failures: usize,
+    pub empty_lines: usize,
+    /// Lines that parsed but carry no usable measurements (all values missing)
+    pub validation_warnings: usize,
+    /// Observations dropped because the same (wbanno, utc_datetime) appeared earlier
+    pub duplicates_removed: usize,
+    /// Observations dropped by `require_complete_observations` (set by the scheduler)
+    pub incomplete_observations: usize,
+    /// The `max_observations_per_file` limit, if parsing stopped there
+    pub observation_limit_reached: Option<usize>,
+    pub failure_rate: f64,
+}
+
+impl Default for ParseStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseStats {
+    pub fn new() -> Self {
+        Self {
+            total_lines: 0,
+            parsed_successfully: 0,
+            parse_failures: 0,
+            empty_lines: 0,
+            validation_warnings: 0,
+            duplicates_removed: 0,
+            incomplete_observations: 0,
+            observation_limit_reached: None,
+            failure_rate: 0.0,
+        }
+    }
+
+    pub fn finalize(&mut self) {
+        let non_empty = self.total_lines - self.empty_lines;
+        self.failure_rate = if non_empty > 0 {
+            self.parse_failures as f64 / non_empty as f64
+        } else {
+            0.0
+        };
+    }
+
+    pub fn exceeds_threshold(&self, threshold: f64) -> bool {
+        self.failure_rate > threshold
+    }
+
+    /// Check that a parse is usable: within `failure_threshold`, not empty,
+    /// and not cut short by an observation limit
+    ///
+    /// # Errors
+    /// Returns `AppError::Parse` if too many lines failed, none parsed, or the
+    /// file exceeded its observation limit
+    pub fn check(&self, failure_threshold: f64) -> Result<()> {
+        if let Some(limit) = self.observation_limit_reached {
+            return Err(AppError::Parse(format!(
+                "File has more than {} observations (max_observations_per_file); \
+                 stopped parsing after line {}",
+                limit, self.total_lines
+            )));
+        }
+
+        if self.exceeds_threshold(failure_threshold) {
+            return Err(AppError::Parse(format!(
+                "Parse failure rate {:.1}% exceeds threshold {:.1}%: {} failures out of {} non-empty lines",
+                self.failure_rate * 100.0,
+                failure_threshold * 100.0,
+                self.parse_failures,
+                self.total_lines - self.empty_lines
+            )));
+        }
+
+        if self.parsed_successfully == 0 && self.total_lines > self.empty_lines {
+            return Err(AppError::Parse(
+                "No observations successfully parsed from non-empty file".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Compute a 0.0-1.0 data quality score for a file
+    ///
+    /// Score is `1.0 - failure_rate - (validation_warnings / rows_processed)`,
+    /// clamped to the valid range. Returns None when no rows were processed.
+    pub fn data_quality_score(&self, rows_processed: usize) -> Option<f64> {
+        if rows_processed == 0 {
+            return None;
+        }
+        let warning_rate = self.validation_warnings as f64 / rows_processed as f64;
+        Some((1.0 - self.failure_rate - warning_rate).clamp(0.0, 1.0))
+    }
+}
+
+pub struct Parser;
+
+impl Parser {
+    /// Parse a USCRN data file and return observations with parse statistics
+    pub fn parse_file(
+        content: &str,
+        separator: FieldSeparator,
+    ) -> Result<(Vec<NewObservation>, ParseStats)> {
+        Self::parse_file_with_config(content, &ParseConfig::with_separator(separator))
+    }
+
+    /// Parse a USCRN data file with a custom failure threshold
+    pub fn parse_file_with_threshold(
+        content: &str,
+        separator: FieldSeparator,
+        failure_threshold: f64,
+    ) -> Result<(Vec<NewObservation>, ParseStats)> {
+        let config = ParseConfig {
+            failure_threshold,
+            ..ParseConfig::with_separator(separator)
+        };
+        Self::parse_file_with_config(content, &config)
+    }
+
+    /// Parse a USCRN data file with custom missing-value sentinels and failure threshold
+    ///
+    /// Duplicate rows are removed as in `parse_file`.
+    pub fn parse_file_with_config(
+        content: &str,
+        config: &ParseConfig,
+    ) -> Result<(Vec<NewObservation>, ParseStats)> {
+        let (observations, stats) = parse_file_unchecked_with_config(content, config, None);
+        stats.check(config.failure_threshold)?;
+        Ok((observations, stats))
+    }
+
+    /// Parse every line of a USCRN data file without applying a failure threshold
+    ///
+    /// Lets callers inspect `ParseStats` before deciding whether to reject the
+    /// file; `ParseStats::check` applies the same rules as `parse_file`.
+    pub fn parse_file_unchecked(
+        content: &str,
+        separator: FieldSeparator,
+    ) -> (Vec<NewObservation>, ParseStats) {
+        Self::parse_file_unchecked_with_limit(content, separator, None)
+    }
+
+    /// Parse like `parse_file_unchecked`, stopping once more than
+    /// `max_observations` lines have parsed
+    ///
+    /// Guards against allocating for a malformed or unexpectedly huge file.
+    /// When the limit is hit, parsing stops, `observation_limit_reached` is
+    /// set and `ParseStats::check` rejects the file.
+    pub fn parse_file_unchecked_with_limit(
+        content: &str,
+        separator: FieldSeparator,
+        max_observations: Option<usize>,
+    ) -> (Vec<NewObservation>, ParseStats) {
+        parse_file_unchecked_with_config(
+            content,
+            &ParseConfig::with_separator(separator),
+            max_observations,
+        )
+    }
+
+    /// Parse a USCRN data file lazily, one observation per line
+    ///
+    /// Lets callers hand rows to the database in batches without first
+    /// collecting the whole file. See `StreamingParser` for how failures and
+    /// statistics are reported.
+    pub fn parse_file_streaming(content: &str, separator: FieldSeparator) -> StreamingParser<'_> {
+        StreamingParser::new(content, ParseConfig::with_separator(separator))
+    }
+
+    /// Remove observations sharing the same (wbanno, utc_datetime)
+    ///
+    /// Duplicates within one batch make PostgreSQL reject the whole upsert, so
+    /// they must be dropped before insertion. Observations are sorted by
+    /// (wbanno, utc_datetime) and the last occurrence in file order wins.
+    ///
+    /// # Returns
+    /// The deduplicated observations and the number of duplicates removed
+    pub fn deduplicate_observations(
+        mut observations: Vec<NewObservation>,
+    ) -> (Vec<NewObservation>, usize) {
+        let original_len = observations.len();
+
+        // Stable sort keeps duplicates in file order
+        observations.sort_by_key(|obs| (obs.wbanno, obs.utc_datetime));
+        observations.dedup_by(|later, kept| {
+            if later.wbanno == kept.wbanno && later.utc_datetime == kept.utc_datetime {
+                std::mem::swap(later, kept);
+                true
+            } else {
+                false
+            }
+        });
+
+        let removed = original_len - observations.len();
+        (observations, removed)
+    }
+
+    /// Parse a single line of an hourly02 data file
+    ///
+    /// Missing values (-9999) parse as `None`. The error's `line_number` is
+    /// 1; file parsers replace it with the line's position in the file.
+    ///
+    /// # Errors
+    /// Returns a `ParseError` naming the column at fault, or with no column
+    /// if the line has too few fields
+    pub fn parse_line(
+        line: &str,
+        separator: FieldSeparator,
+    ) -> std::result::Result<NewObservation, ParseError> {
+        Self::parse_line_with_config(line, &ParseConfig::with_separator(separator))
+    }
+
+    /// Parse a single line of an hourly02 data file with custom missing-value sentinels
+    ///
+    /// # Errors
+    /// Returns a `ParseError` as for `parse_line`
+    pub fn parse_line_with_config(
+        line: &str,
+        config: &ParseConfig,
+    ) -> std::result::Result<NewObservation, ParseError> {
+        let fields = LineFields::new(line, *config, &COLUMN_NAMES);
+
+        if fields.len() < MIN_FIELDS {
+            return Err(ParseError {
+                line_number: 1,
+                field_index: None,
+                field_name: None,
+                reason: format!(
+                    "Expected at least {} fields, got {}",
+                    MIN_FIELDS,
+                    fields.len()
+                ),
+            });
+        }
+
+        // Parse required fields
+        let wbanno = Wbanno(fields.int(WBANNO_COL)?);
+        let utc_datetime = fields.datetime(UTC_DATE_COL, UTC_TIME_COL)?;
+        let lst_datetime = fields.datetime(LST_DATE_COL, LST_TIME_COL)?;
+        let crx_version = fields.values[CRX_VN_COL].to_string();
+
+        // Parse optional fields with missing value handling
+        let t_calc = fields.optional_float(T_CALC_COL)?;
+        let t_hr_avg = fields.optional_float(T_HR_AVG_COL)?;
+        let t_max = fields.optional_float(T_MAX_COL)?;
+        let t_min = fields.optional_float(T_MIN_COL)?;
+        let p_calc = fields.optional_float(P_CALC_COL)?;
+
+        let solarad = fields.optional_float(SOLARAD_COL)?;
+        let solarad_flag = fields.optional_int(SOLARAD_FLAG_COL)?;
+        let solarad_max = fields.optional_float(SOLARAD_MAX_COL)?;
+        let solarad_max_flag = fields.optional_int(SOLARAD_MAX_FLAG_COL)?;
+        let solarad_min = fields.optional_float(SOLARAD_MIN_COL)?;
+        let solarad_min_flag = fields.optional_int(SOLARAD_MIN_FLAG_COL)?;
+
+        let sur_temp_type = fields.get(SUR_TEMP_TYPE_COL).map(|s| s.to_string());
+        let sur_temp = fields.optional_float(SUR_TEMP_COL)?;
+        let sur_temp_flag = fields.optional_int(SUR_TEMP_FLAG_COL)?;
+        let sur_temp_max = fields.optional_float(SUR_TEMP_MAX_COL)?;
+        let sur_temp_max_flag = fields.optional_int(SUR_TEMP_MAX_FLAG_COL)?;
+        let sur_temp_min = fields.optional_float(SUR_TEMP_MIN_COL)?;
+        let sur_temp_min_flag = fields.optional_int(SUR_TEMP_MIN_FLAG_COL)?;
+
+        let rh_hr_avg = fields.optional_float(RH_HR_AVG_COL)?;
+        let rh_hr_avg_flag = fields.optional_int(RH_HR_AVG_FLAG_COL)?;
+
+        // Soil moisture (5 depths)
+        let soil_moisture_5 = fields.optional_float(SOIL_MOISTURE_5_COL)?;
+        let soil_moisture_10 = fields.optional_float(SOIL_MOISTURE_10_COL)?;
+        let soil_moisture_20 = fields.optional_float(SOIL_MOISTURE_20_COL)?;
+        let soil_moisture_50 = fields.optional_float(SOIL_MOISTURE_50_COL)?;
+        let soil_moisture_100 = fields.optional_float(SOIL_MOISTURE_100_COL)?;
+
+        // Soil temperature (5 depths)
+        let soil_temp_5 = fields.optional_float(SOIL_TEMP_5_COL)?;
+        let soil_temp_10 = fields.optional_float(SOIL_TEMP_10_COL)?;
+        let soil_temp_20 = fields.optional_float(SOIL_TEMP_20_COL)?;
+        let soil_temp_50 = fields.optional_float(SOIL_TEMP_50_COL)?;
+        let soil_temp_100 = fields.optional_float(SOIL_TEMP_100_COL)?;
+
+        Ok(NewObservation {
+            wbanno,
+            utc_datetime,
+            lst_datetime,
+            crx_version: Some(crx_version),
+            t_calc,
+            t_hr_avg,
+            t_max,
+            t_min,
+            p_calc,
+            solarad,
+            solarad_flag,
+            solarad_max,
+            solarad_max_flag,
+            solarad_min,
+            solarad_min_flag,
+            sur_temp_type,
+            sur_temp,
+            sur_temp_flag,
+            sur_temp_max,
+            sur_temp_max_flag,
+            sur_temp_min,
+            sur_temp_min_flag,
+            rh_hr_avg,
+            rh_hr_avg_flag,
+            soil_moisture_5,
+            soil_moisture_10,
+            soil_moisture_20,
+            soil_moisture_50,
+            soil_moisture_100,
+            soil_temp_5,
+            soil_temp_10,
+            soil_temp_20,
+            soil_temp_50,
+            soil_temp_100,
+            source_file_id: None,
+            ingestion_run_id: None,
+        })
+    }
+}
+
+impl Parser {
+    /// Parse a subhourly01 (5-minute) data file
+    ///
+    /// Applies the same failure threshold and duplicate removal as `parse_file`,
+    /// and rejects files with more than `max_observations` rows as
+    /// `parse_file_unchecked_with_limit` does.
+    pub fn parse_subhourly_file(
+        content: &str,
+        separator: FieldSeparator,
+        max_observations: Option<usize>,
+    ) -> Result<(Vec<NewSubhourlyObservation>, ParseStats)> {
+        parse_product_file(
+            content,
+            separator,
+            max_observations,
+            "subhourly",
+            Self::parse_subhourly_line,
+            has_subhourly_measurements,
+            |obs| (obs.wbanno, obs.utc_datetime),
+        )
+    }
+
+    /// Parse a daily01 (daily summary) data file
+    ///
+    /// Applies the same failure threshold, duplicate removal and observation
+    /// limit as `parse_subhourly_file`.
+    pub fn parse_daily_file(
+        content: &str,
+        separator: FieldSeparator,
+        max_observations: Option<usize>,
+    ) -> Result<(Vec<NewDailyObservation>, ParseStats)> {
+        parse_product_file(
+            content,
+            separator,
+            max_observations,
+            "daily",
+            Self::parse_daily_line,
+            has_daily_measurements,
+            |obs| (obs.wbanno, obs.lst_date),
+        )
+    }
+
+    /// Parse a single line of a subhourly01 data file
+    ///
+    /// # Errors
+    /// Returns a `ParseError` as for `parse_line`
+    pub fn parse_subhourly_line(
+        line: &str,
+        separator: FieldSeparator,
+    ) -> std::result::Result<NewSubhourlyObservation, ParseError> {
+        use columns::subhourly::*;
+
+        let fields = LineFields::new(line, ParseConfig::with_separator(separator), &COLUMN_NAMES);
+
+        if fields.len() < TOTAL_FIELDS {
+            return Err(ParseError {
+                line_number: 1,
+                field_index: None,
+                field_name: None,
+                reason: format!(
+                    "Expected {} subhourly fields, got {}",
+                    TOTAL_FIELDS,
+                    fields.len()
+                ),
+            });
+        }
+
+        Ok(NewSubhourlyObservation {
+            wbanno: Wbanno(fields.int(WBANNO_COL)?),
+            utc_datetime: fields.datetime(UTC_DATE_COL, UTC_TIME_COL)?,
+            lst_datetime: fields.datetime(LST_DATE_COL, LST_TIME_COL)?,
+            crx_version: Some(fields.values[CRX_VN_COL].to_string()),
+            air_temperature: fields.optional_float(AIR_TEMPERATURE_COL)?,
+            precipitation: fields.optional_float(PRECIPITATION_COL)?,
+            solar_radiation: fields.optional_float(SOLAR_RADIATION_COL)?,
+            sr_flag: fields.optional_int(SR_FLAG_COL)?,
+            surface_temperature: fields.optional_float(SURFACE_TEMPERATURE_COL)?,
+            st_type: fields.get(ST_TYPE_COL).map(|s| s.to_string()),
+            st_flag: fields.optional_int(ST_FLAG_COL)?,
+            relative_humidity: fields.optional_float(RELATIVE_HUMIDITY_COL)?,
+            rh_flag: fields.optional_int(RH_FLAG_COL)?,
+            soil_moisture_5: fields.optional_float(SOIL_MOISTURE_5_COL)?,
+            soil_temperature_5: fields.optional_float(SOIL_TEMPERATURE_5_COL)?,
+            wetness: fields.optional_int(WETNESS_COL)?,
+            wet_flag: fields.optional_int(WET_FLAG_COL)?,
+            wind_1_5: fields.optional_float(WIND_1_5_COL)?,
+            wind_flag: fields.optional_int(WIND_FLAG_COL)?,
+            source_file_id: None,
+        })
+    }
+}
+
+impl Parser {
+    /// Parse a single line of a daily01 data file
+    ///
+    /// # Errors
+    /// Returns a `ParseError` as for `parse_line`
+    pub fn parse_daily_line(
+        line: &str,
+        separator: FieldSeparator,
+    ) -> std::result::Result<NewDailyObservation, ParseError> {
+        use columns::daily::*;
+
+        let fields = LineFields::new(line, ParseConfig::with_separator(separator), &COLUMN_NAMES);
+
+        if fields.len() < TOTAL_FIELDS {
+            return Err(ParseError {
+                line_number: 1,
+                field_index: None,
+                field_name: None,
+                reason: format!(
+                    "Expected {} daily fields, got {}",
+                    TOTAL_FIELDS,
+                    fields.len()
+                ),
+            });
+        }
+
+        Ok(NewDailyObservation {
+            wbanno: Wbanno(fields.int(WBANNO_COL)?),
+            lst_date: fields.date(LST_DATE_COL)?,
+            crx_version: Some(fields.values[CRX_VN_COL].to_string()),
+            t_daily_max: fields.optional_float(T_DAILY_MAX_COL)?,
+            t_daily_min: fields.optional_float(T_DAILY_MIN_COL)?,
+            t_daily_mean: fields.optional_float(T_DAILY_MEAN_COL)?,
+            t_daily_avg: fields.optional_float(T_DAILY_AVG_COL)?,
+            p_daily_calc: fields.optional_float(P_DAILY_CALC_COL)?,
+            solarad_daily: fields.optional_float(SOLARAD_DAILY_COL)?,
+            sur_temp_daily_type: fields.get(SUR_TEMP_DAILY_TYPE_COL).map(|s| s.to_string()),
+            sur_temp_daily_max: fields.optional_float(SUR_TEMP_DAILY_MAX_COL)?,
+            sur_temp_daily_min: fields.optional_float(SUR_TEMP_DAILY_MIN_COL)?,
+            sur_temp_daily_avg: fields.optional_float(SUR_TEMP_DAILY_AVG_COL)?,
+            rh_daily_max: fields.optional_float(RH_DAILY_MAX_COL)?,
+            rh_daily_min: fields.optional_float(RH_DAILY_MIN_COL)?,
+            rh_daily_avg: fields.optional_float(RH_DAILY_AVG_COL)?,
+            soil_moisture_5_daily: fields.optional_float(SOIL_MOISTURE_5_DAILY_COL)?,
+            soil_moisture_10_daily: fields.optional_float(SOIL_MOISTURE_10_DAILY_COL)?,
+            soil_moisture_20_daily: fields.optional_float(SOIL_MOISTURE_20_DAILY_COL)?,
+            soil_moisture_50_daily: fields.optional_float(SOIL_MOISTURE_50_DAILY_COL)?,
+            soil_moisture_100_daily: fields.optional_float(SOIL_MOISTURE_100_DAILY_COL)?,
+            soil_temp_5_daily: fields.optional_float(SOIL_TEMP_5_DAILY_COL)?,
+            soil_temp_10_daily: fields.optional_float(SOIL_TEMP_10_DAILY_COL)?,
+            soil_temp_20_daily: fields.optional_float(SOIL_TEMP_20_DAILY_COL)?,
+            soil_temp_50_daily: fields.optional_float(SOIL_TEMP_50_DAILY_COL)?,
+            soil_temp_100_daily: fields.optional_float(SOIL_TEMP_100_DAILY_COL)?,
+            source_file_id: None,
+        })
+    }
+}
+
+/// Parse every line of an hourly02 file, removing duplicates but applying
+/// no failure threshold
+fn parse_file_unchecked_with_config(
+    content: &str,
+    config: &ParseConfig,
+    max_observations: Option<usize>,
+) -> (Vec<NewObservation>, ParseStats) {
+    let started = std::time::Instant::now();
+    let mut parser = StreamingParser::new(content, *config).with_max_observations(max_observations);
+    let observations: Vec<NewObservation> = parser.by_ref().filter_map(Result::ok).collect();
+    let mut stats = parser.into_stats();
+
+    let (observations, duplicates_removed) = Parser::deduplicate_observations(observations);
+    if duplicates_removed > 0 {
+        warn!(
+            "Removed {} duplicate observations (same station and timestamp)",
+            duplicates_removed
+        );
+    }
+    stats.duplicates_removed = duplicates_removed;
+    metrics::record_parse(started.elapsed(), stats.parse_failures);
+
+    (observations, stats)
+}
+
+/// Parse every line of a subhourly01 or daily01 file with `parse_line`
+///
+/// Applies `parse_file`'s failure threshold. Of rows sharing a `key`, the
+/// last in file order wins, as in `deduplicate_observations`.
+fn parse_product_file<T, K: Ord>(
+    content: &str,
+    separator: FieldSeparator,
+    max_observations: Option<usize>,
+    product: &str,
+    parse_line: fn(&str, FieldSeparator) -> std::result::Result<T, ParseError>,
+    has_measurements: fn(&T) -> bool,
+    key: fn(&T) -> K,
+) -> Result<(Vec<T>, ParseStats)> {
+    let mut observations = Vec::new();
+    let mut stats = ParseStats::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        stats.total_lines += 1;
+
+        let line = line.trim();
+        if line.is_empty() {
+            stats.empty_lines += 1;
+            continue;
+        }
+
+        match parse_line(line, separator) {
+            Ok(obs) => {
+                if max_observations.is_some_and(|max| observations.len() >= max) {
+                    stats.observation_limit_reached = max_observations;
+                    break;
+                }
+                if !has_measurements(&obs) {
+                    stats.validation_warnings += 1;
+                }
+                observations.push(obs);
+                stats.parsed_successfully += 1;
+            }
+            Err(mut e) => {
+                e.line_number = line_num + 1;
+                stats.parse_failures += 1;
+                warn!(
+                    "Failed to parse {} line (failure {}/{}): {} - {}",
+                    product,
+                    stats.parse_failures,
+                    stats.total_lines - stats.empty_lines,
+                    e,
+                    line
+                );
+            }
+        }
+    }
+
+    stats.finalize();
+    stats.check(DEFAULT_FAILURE_THRESHOLD)?;
+
+    let original_len = observations.len();
+    observations.sort_by_key(key);
+    observations.dedup_by(|later, kept| {
+        if key(later) == key(kept) {
+            std::mem::swap(later, kept);
+            true
+        } else {
+            false
+        }
+    });
+    stats.duplicates_removed = original_len - observations.len();
+
+    Ok((observations, stats))
+}
+
+/// A data line split into fields, attributing parse errors to named columns
+struct LineFields<'a> {
+    values: Vec<&'a str>,
+    names: &'static [&'static str],
+    config: ParseConfig,
+}
+
+impl<'a> LineFields<'a> {
+    fn new(line: &'a str, config: ParseConfig, names: &'static [&'static str]) -> Self {
+        Self {
+            values: split_fields(line, config.separator),
+            names,
+            config,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn get(&self, col: usize) -> Option<&'a str> {
+        self.values.get(col).copied()
+    }
+
+    fn error(&self, col: usize, reason: String) -> ParseError {
+        ParseError {
+            line_number: 1,
+            field_index: Some(col),
+            field_name: self.names.get(col).copied(),
+            reason,
+        }
+    }
+
+    /// A required integer; callers check the field count first
+    fn int(&self, col: usize) -> std::result::Result<i32, ParseError> {
+        parse_int(self.values[col]).map_err(|reason| self.error(col, reason))
+    }
+
+    fn optional_int(&self, col: usize) -> std::result::Result<Option<i32>, ParseError> {
+        self.config
+            .parse_optional_int(self.get(col))
+            .map_err(|reason| self.error(col, reason))
+    }
+
+    fn optional_float(&self, col: usize) -> std::result::Result<Option<f32>, ParseError> {
+        self.config
+            .parse_optional_float(self.get(col))
+            .map_err(|reason| self.error(col, reason))
+    }
+
+    /// A required YYYYMMDD date
+    fn date(&self, col: usize) -> std::result::Result<NaiveDate, ParseError> {
+        parse_date(self.int(col)?).map_err(|reason| self.error(col, reason))
+    }
+
+    /// A required YYYYMMDD date and HHMM time
+    fn datetime(
+        &self,
+        date_col: usize,
+        time_col: usize,
+    ) -> std::result::Result<chrono::DateTime<Utc>, ParseError> {
+        let date = self.date(date_col)?;
+        let time =
+            parse_time(self.int(time_col)?).map_err(|reason| self.error(time_col, reason))?;
+        Ok(Utc.from_utc_datetime(&NaiveDateTime::new(date, time)))
+    }
+}
+
+fn split_fields(line: &str, separator: FieldSeparator) -> Vec<&str> {
+    match separator {
+        FieldSeparator::Whitespace => line.split_whitespace().collect(),
+        FieldSeparator::Comma => line.split(',').map(str::trim).collect(),
+        FieldSeparator::Tab => line.split('\t').map(str::trim).collect(),
+    }
+}
+
+/// Whether an observation carries at least one measured value
+fn has_measurements(obs: &NewObservation) -> bool {
+    [
+        obs.t_hr_avg,
+        obs.p_calc,
+        obs.solarad,
+        obs.sur_temp,
+        obs.rh_hr_avg,
+        obs.soil_moisture_5,
+        obs.soil_temp_5,
+    ]
+    .iter()
+    .any(Option::is_some)
+}
+
+/// Whether a 5-minute observation carries at least one measured value
+fn has_subhourly_measurements(obs: &NewSubhourlyObservation) -> bool {
+    [
+        obs.air_temperature,
+        obs.precipitation,
+        obs.solar_radiation,
+        obs.surface_temperature,
+        obs.relative_humidity,
+        obs.soil_moisture_5,
+        obs.soil_temperature_5,
+        obs.wind_1_5,
+    ]
+    .iter()
+    .any(Option::is_some)
+}
+
+/// Whether a daily summary carries at least one measured value
+fn has_daily_measurements(obs: &NewDailyObservation) -> bool {
+    [
+        obs.t_daily_avg,
+        obs.p_daily_calc,
+        obs.solarad_daily,
+        obs.sur_temp_daily_avg,
+        obs.rh_daily_avg,
+        obs.soil_moisture_5_daily,
+        obs.soil_temp_5_daily,
+    ]
+    .iter()
+    .any(Option::is_some)
+}
+
+fn parse_int(s: &str) -> std::result::Result<i32, String> {
+    s.parse::<i32>()
+        .map_err(|e| format!("Failed to parse int '{}': {}", s, e))
+}
+
+/// Parse a YYYYMMDD date
+fn parse_date(date: i32) -> std::result::Result<NaiveDate, String> {
+    let year = date / 10000;
+    let month = (date % 10000) / 100;
+    let day = date % 100;
+
+    // Validate ranges before creating the date
+    if !(1900..=2100).contains(&year) {
+        return Err(format!(
+            "Year {} out of valid range (1900-2100) from date {}",
+            year, date
+        ));
+    }
+
+    if !(1..=12).contains(&month) {
+        return Err(format!(
+            "Month {} out of valid range (1-12) from date {}",
+            month, date
+        ));
+    }
+
+    if !(1..=31).contains(&day) {
+        return Err(format!(
+            "Day {} out of valid range (1-31) from date {}",
+            day, date
+        ));
+    }
+
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).ok_or_else(|| {
+        format!(
+            "Invalid date combination: year={}, month={}, day={} from {}",
+            year, month, day, date
+        )
+    })
+}
+
+/// Parse an HHMM time
+fn parse_time(time: i32) -> std::result::Result<NaiveTime, String> {
+    let hour = time / 100;
+    let minute = time % 100;
+
+    if !(0..=23).contains(&hour) {
+        return Err(format!(
+            "Hour {} out of valid range (0-23) from time {}",
+            hour, time
+        ));
+    }
+
+    if !(0..=59).contains(&minute) {
+        return Err(format!(
+            "Minute {} out of valid range (0-59) from time {}",
+            minute, time
+        ));
+    }
+
+    NaiveTime::from_hms_opt(hour as u32, minute as u32, 0).ok_or_else(|| {
+        format!(
+            "Invalid time combination: hour={}, minute={} from {}",
+            hour, minute, time
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UTC datetime from YYYYMMDD and HHMM
+    fn datetime(date: i32, time: i32) -> chrono::DateTime<Utc> {
+        let date = parse_date(date).unwrap();
+        let time = parse_time(time).unwrap();
+        Utc.from_utc_datetime(&NaiveDateTime::new(date, time))
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        assert_eq!(
+            datetime(20240115, 1430)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2024-01-15 14:30:00"
+        );
+        assert!(parse_date(20241301).is_err());
+        assert!(parse_time(2460).is_err());
+    }
+
+    #[test]
+    fn test_parse_optional_float_missing() {
+        let config = ParseConfig::default();
+        assert_eq!(config.parse_optional_float(Some("-9999.0")), Ok(None));
+        assert_eq!(config.parse_optional_float(Some("-9999")), Ok(None));
+        assert_eq!(config.parse_optional_float(None), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_optional_float_valid() {
+        let config = ParseConfig::default();
+        assert_eq!(config.parse_optional_float(Some("25.5")), Ok(Some(25.5)));
+        assert_eq!(config.parse_optional_float(Some("0.0")), Ok(Some(0.0)));
+        assert!(config.parse_optional_float(Some("abc")).is_err());
+    }
+
+    #[test]
+    fn test_parse_file_with_config_custom_sentinels() {
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53   -99.99     4.1     4.9     3.4     0.0    45.5 -99    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0";
+        let config = ParseConfig {
+            missing_value_float: -99.99,
+            missing_value_int: -99,
+            ..ParseConfig::default()
+        };
+
+        let (observations, _) = Parser::parse_file_with_config(line, &config).unwrap();
+        assert_eq!(observations[0].t_calc, None);
+        assert_eq!(observations[0].solarad_flag, None);
+        assert_eq!(observations[0].t_hr_avg, Some(4.1));
+
+        // The default sentinels keep these values
+        let (observations, _) = Parser::parse_file(line, FieldSeparator::Whitespace).unwrap();
+        assert_eq!(observations[0].t_calc, Some(-99.99));
+        assert_eq!(observations[0].solarad_flag, Some(-99));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        // Sample line from USCRN data
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
+
+        let result = Parser::parse_line(line, FieldSeparator::Whitespace);
+        assert!(result.is_ok(), "Parse error: {:?}", result.err());
+
+        let obs = result.unwrap();
+        assert_eq!(obs.wbanno, Wbanno(53104));
+        assert_eq!(obs.t_hr_avg, Some(4.1));
+        assert_eq!(obs.t_max, Some(4.9));
+        assert_eq!(obs.t_min, Some(3.4));
+        assert_eq!(obs.p_calc, Some(0.0));
+        assert_eq!(obs.t_calc, None); // -9999.0 should be None
+        assert_eq!(obs.soil_moisture_5, None); // -9999.0 should be None
+    }
+
+    #[test]
+    fn test_parse_line_comma_separated() {
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
+        let csv_line = line.split_whitespace().collect::<Vec<_>>().join(",");
+        let tsv_line = line.split_whitespace().collect::<Vec<_>>().join("\t");
+
+        let expected = Parser::parse_line(line, FieldSeparator::Whitespace).unwrap();
+        let from_csv = Parser::parse_line(&csv_line, FieldSeparator::Comma).unwrap();
+        let from_tsv = Parser::parse_line(&tsv_line, FieldSeparator::Tab).unwrap();
+
+        for obs in [&from_csv, &from_tsv] {
+            assert_eq!(obs.wbanno, expected.wbanno);
+            assert_eq!(obs.utc_datetime, expected.utc_datetime);
+            assert_eq!(obs.t_hr_avg, Some(4.1));
+            assert_eq!(obs.t_max, expected.t_max);
+            assert_eq!(obs.rh_hr_avg, Some(81.9));
+            assert_eq!(obs.sur_temp_type.as_deref(), Some("C"));
+            assert_eq!(obs.t_calc, None);
+            assert_eq!(obs.soil_moisture_5, None);
+        }
+
+        // Commas are not whitespace, so the default separator sees one field
+        assert!(Parser::parse_line(&csv_line, FieldSeparator::Whitespace).is_err());
+    }
+
+    #[test]
+    fn test_parse_line_error_details() {
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0";
+
+        let err =
+            Parser::parse_line("53104 20240115 1400", FieldSeparator::Whitespace).unwrap_err();
+        assert_eq!(err.line_number, 1);
+        assert_eq!(err.field_index, None);
+        assert_eq!(err.field_name, None);
+
+        let bad_temp = line.replacen("4.1", "abc", 1);
+        let err = Parser::parse_line(&bad_temp, FieldSeparator::Whitespace).unwrap_err();
+        assert_eq!(err.field_index, Some(T_HR_AVG_COL));
+        assert_eq!(err.field_name, Some("t_hr_avg"));
+
+        let bad_time = line.replacen("1400", "2500", 1);
+        let err = Parser::parse_line(&bad_time, FieldSeparator::Whitespace).unwrap_err();
+        assert_eq!(err.field_name, Some("utc_time"));
+
+        // File parsers report the line's position in the file
+        let content = format!("{}\n{}", line, bad_temp);
+        let mut parser = Parser::parse_file_streaming(&content, FieldSeparator::Whitespace);
+        assert!(parser.next().unwrap().is_ok());
+        match parser.next() {
+            Some(Err(AppError::ParseLine(err))) => {
+                assert_eq!(err.line_number, 2);
+                assert_eq!(err.field_name, Some("t_hr_avg"));
+            }
+            other => panic!("Expected a line parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_file_comma_separated_with_spaces() {
+        let content = "53104, 20240115, 1400, 20240115, 0600, 3, -81.74, 36.53, -9999.0, 4.1, 4.9, 3.4, 0.0, 45.5, 0, 58.6, 0, 35.9, 0, C, 1.1, 0, 2.1, 0, -0.5, 0, 81.9, 0";
+
+        let (observations, stats) = Parser::parse_file(content, FieldSeparator::Comma).unwrap();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(stats.parse_failures, 0);
+        assert_eq!(observations[0].solarad, Some(45.5));
+        assert_eq!(observations[0].rh_hr_avg_flag, Some(0));
+    }
+
+    #[test]
+    fn test_parse_file_with_stats() {
+        let content = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0\n\
+                        53104 20240115 1500 20240115 0700 3   -81.74    36.53  -9999.0     4.5     5.2     4.0     0.0    52.3 0    65.4 0    42.1 0 C     1.8 0     2.5 0    -0.2 0    78.5 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
+
+        let result = Parser::parse_file(content, FieldSeparator::Whitespace);
+        assert!(result.is_ok());
+
+        let (observations, stats) = result.unwrap();
+        assert_eq!(observations.len(), 2);
+        assert_eq!(stats.parsed_successfully, 2);
+        assert_eq!(stats.parse_failures, 0);
+        assert!(stats.failure_rate < 0.01);
+    }
+
+    #[test]
+    fn test_parse_file_failure_threshold() {
+        // Mix of valid and invalid lines
+        let content = "invalid line 1\n\
+                        53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0\n\
+                        invalid line 2\n\
+                        invalid line 3";
+
+        // Should fail with default 10% threshold (3 failures out of 4 lines = 75%)
+        let result = Parser::parse_file(content, FieldSeparator::Whitespace);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("exceeds threshold"));
+
+        // The unchecked parse still returns the stats needed to report the failure
+        let (observations, stats) =
+            Parser::parse_file_unchecked(content, FieldSeparator::Whitespace);
+        assert_eq!(observations.len(), 1);
+        assert_eq!(stats.parse_failures, 3);
+        assert!(stats.check(DEFAULT_FAILURE_THRESHOLD).is_err());
+        assert!(stats.check(0.8).is_ok());
+    }
+
+    #[test]
+    fn test_data_quality_score() {
+        let mut stats = ParseStats::new();
+        stats.total_lines = 10;
+        stats.parsed_successfully = 9;
+        stats.parse_failures = 1;
+        stats.validation_warnings = 0;
+        stats.finalize();
+        assert!((stats.data_quality_score(9).unwrap() - 0.9).abs() < 1e-9);
+
+        stats.validation_warnings = 9;
+        assert_eq!(stats.data_quality_score(9), Some(0.0));
+        assert_eq!(stats.data_quality_score(0), None);
+    }
+
+    #[test]
+    fn test_all_missing_line_counts_validation_warning() {
+        let content = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0  -9999.0  -9999.0  -9999.0  -9999.0  -9999.0 0  -9999.0 0  -9999.0 0 C  -9999.0 0  -9999.0 0  -9999.0 0  -9999.0 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
+
+        let (observations, stats) =
+            Parser::parse_file(content, FieldSeparator::Whitespace).unwrap();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(stats.validation_warnings, 1);
+    }
+
+    #[test]
+    fn test_deduplicate_observations_keeps_last() {
+        let first = Parser::parse_line("53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0", FieldSeparator::Whitespace).unwrap();
+        let earlier = Parser::parse_line("53104 20240115 1300 20240115 0500 3   -81.74    36.53  -9999.0     3.0     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0", FieldSeparator::Whitespace).unwrap();
+        let mut corrected = first.clone();
+        corrected.t_hr_avg = Some(9.9);
+
+        let (deduped, removed) = Parser::deduplicate_observations(vec![first, earlier, corrected]);
+
+        assert_eq!(removed, 1);
+        assert_eq!(deduped.len(), 2);
+        assert!(deduped[0].utc_datetime < deduped[1].utc_datetime);
+        assert_eq!(deduped[1].t_hr_avg, Some(9.9));
+    }
+
+    #[test]
+    fn test_parse_file_removes_duplicate_lines() {
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0";
+        let content = format!("{}\n{}", line, line);
+
+        let (observations, stats) =
+            Parser::parse_file(&content, FieldSeparator::Whitespace).unwrap();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(stats.parsed_successfully, 2);
+        assert_eq!(stats.duplicates_removed, 1);
+    }
+
+    #[test]
+    fn test_parse_file_stops_at_observation_limit() {
+        let base = datetime(20240101, 0);
+        let content: String = (0..10_000)
+            .map(|hour| {
+                let dt = base + chrono::Duration::hours(hour);
+                format!(
+                    "53104 {} 20240115 0600 3 -81.74 36.53 -9999.0 4.1 4.9 3.4 0.0 \
+                     45.5 0 58.6 0 35.9 0 C 1.1 0 2.1 0 -0.5 0 81.9 0\n",
+                    dt.format("%Y%m%d %H%M")
+                )
+            })
+            .collect();
+
+        let (observations, stats) = Parser::parse_file_unchecked_with_limit(
+            &content,
+            FieldSeparator::Whitespace,
+            Some(5000),
+        );
+        assert_eq!(observations.len(), 5000);
+        assert_eq!(stats.observation_limit_reached, Some(5000));
+        assert!(matches!(
+            stats.check(DEFAULT_FAILURE_THRESHOLD),
+            Err(AppError::Parse(_))
+        ));
+
+        // A file at or under the limit parses normally
+        let (observations, stats) = Parser::parse_file_unchecked_with_limit(
+            &content,
+            FieldSeparator::Whitespace,
+            Some(10_000),
+        );
+        assert_eq!(observations.len(), 10_000);
+        assert!(stats.check(DEFAULT_FAILURE_THRESHOLD).is_ok());
+    }
+
+    #[test]
+    fn test_parse_file_streaming() {
+        let line = "53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0";
+        let content = format!(
+            "{}\n\nnot a data line\n{}",
+            line,
+            line.replace("1400", "1500")
+        );
+
+        let mut parser = Parser::parse_file_streaming(&content, FieldSeparator::Whitespace);
+        assert!(parser.next().unwrap().is_ok());
+        assert!(matches!(parser.next(), Some(Err(AppError::ParseLine(_)))));
+        assert_eq!(parser.stats().parsed_successfully, 1);
+
+        let last = parser.next().unwrap().unwrap();
+        assert_eq!(last.utc_datetime, datetime(20240115, 1500));
+        assert!(parser.next().is_none());
+
+        let stats = parser.into_stats();
+        assert_eq!(stats.total_lines, 4);
+        assert_eq!(stats.empty_lines, 1);
+        assert_eq!(stats.parsed_successfully, 2);
+        assert_eq!(stats.parse_failures, 1);
+        assert!((stats.failure_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_daily_file() {
+        let line = "53104 20240115  2.623  -81.74   36.53     8.3    -2.6     2.9     3.1     0.0    10.53 C     9.9    -3.4     2.6    92.3    51.1    74.4 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0 -9999.0";
+        // The second copy of a day replaces the first
+        let content = format!("{}\n{}", line, line.replace("  8.3", " 9.5"));
+
+        let (days, stats) =
+            Parser::parse_daily_file(&content, FieldSeparator::Whitespace, None).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(stats.duplicates_removed, 1);
+
+        // More rows than max_observations rejects the file
+        let err = Parser::parse_daily_file(&content, FieldSeparator::Whitespace, Some(1));
+        assert!(
+            matches!(err, Err(AppError::Parse(msg)) if msg.contains("max_observations_per_file"))
+        );
+        assert!(Parser::parse_daily_file(&content, FieldSeparator::Whitespace, Some(2)).is_ok());
+
+        let day = &days[0];
+        assert_eq!(day.wbanno, Wbanno(53104));
+        assert_eq!(day.lst_date, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+        assert_eq!(day.crx_version.as_deref(), Some("2.623"));
+        assert_eq!(day.t_daily_max, Some(9.5));
+        assert_eq!(day.t_daily_min, Some(-2.6));
+        assert_eq!(day.t_daily_avg, Some(3.1));
+        assert_eq!(day.solarad_daily, Some(10.53));
+        assert_eq!(day.sur_temp_daily_type.as_deref(), Some("C"));
+        assert_eq!(day.rh_daily_avg, Some(74.4));
+        assert_eq!(day.soil_moisture_5_daily, None);
+        assert_eq!(day.soil_temp_100_daily, None);
+
+        let err = Parser::parse_daily_line(
+            &line.replacen("20240115", "20241315", 1),
+            FieldSeparator::Whitespace,
+        )
+        .unwrap_err();
+        assert_eq!(err.field_name, Some("lst_date"));
+    }
+
+    #[test]
+    fn test_parse_subhourly_line() {
+        let line = "53104 20240115 1405 20240115 0605 2.623 -81.74 36.53 4.3 0.0 45 0 1.2 C 0 81 0 0.215 3.1 1020 0 2.47 0";
+
+        let obs = Parser::parse_subhourly_line(line, FieldSeparator::Whitespace).unwrap();
+        assert_eq!(obs.wbanno, Wbanno(53104));
+        assert_eq!(obs.utc_datetime, datetime(20240115, 1405));
+        assert_eq!(obs.air_temperature, Some(4.3));
+        assert_eq!(obs.solar_radiation, Some(45.0));
+        assert_eq!(obs.st_type.as_deref(), Some("C"));
+        assert_eq!(obs.relative_humidity, Some(81.0));
+        assert_eq!(obs.soil_moisture_5, Some(0.215));
+        assert_eq!(obs.wetness, Some(1020));
+        assert_eq!(obs.wind_1_5, Some(2.47));
+
+        // Missing readings parse as None
+        let missing = "53104 20240115 1405 20240115 0605 2.623 -81.74 36.53 -9999.0 -9999.0 -99999 0 -9999.0 C 0 -9999 0 -99.000 -9999.0 -9999 0 -99.00 0";
+        let obs = Parser::parse_subhourly_line(missing, FieldSeparator::Whitespace).unwrap();
+        assert_eq!(obs.air_temperature, None);
+        assert_eq!(obs.wetness, None);
+
+        assert!(
+            Parser::parse_subhourly_line("53104 20240115 1405", FieldSeparator::Whitespace)
+                .is_err()
+        );
+    }
+}
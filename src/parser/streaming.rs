@@ -0,0 +1,105 @@
+use super::{has_measurements, ParseConfig, ParseStats, Parser};
+use crate::db::models::NewObservation;
+use crate::error::{AppError, Result};
+use std::iter::Enumerate;
+use std::str::Lines;
+use tracing::warn;
+
+/// Iterator over the observations in a USCRN data file, one line at a time
+///
+/// Created by `Parser::parse_file_streaming`. Lines that fail to parse are
+/// yielded as `AppError::ParseLine`; empty lines are skipped. Unlike
+/// `Parser::parse_file`, duplicate (wbanno, utc_datetime) rows are not
+/// removed, since that needs the whole file. `stats` is complete once the
+/// iterator returns `None`.
+pub struct StreamingParser<'a> {
+    lines: Enumerate<Lines<'a>>,
+    config: ParseConfig,
+    max_observations: Option<usize>,
+    stats: ParseStats,
+}
+
+impl<'a> StreamingParser<'a> {
+    pub(super) fn new(content: &'a str, config: ParseConfig) -> Self {
+        Self {
+            lines: content.lines().enumerate(),
+            config,
+            max_observations: None,
+            stats: ParseStats::new(),
+        }
+    }
+
+    /// Stop yielding once `max` observations have parsed
+    ///
+    /// If another line would parse past the limit, iteration ends and
+    /// `ParseStats::observation_limit_reached` is set.
+    pub fn with_max_observations(mut self, max: Option<usize>) -> Self {
+        self.max_observations = max;
+        self
+    }
+
+    /// Statistics for the lines read so far
+    pub fn stats(&self) -> ParseStats {
+        let mut stats = self.stats.clone();
+        stats.finalize();
+        stats
+    }
+
+    /// Consume the parser, returning statistics for the lines read
+    pub fn into_stats(mut self) -> ParseStats {
+        self.stats.finalize();
+        self.stats
+    }
+}
+
+impl Iterator for StreamingParser<'_> {
+    type Item = Result<NewObservation>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stats.observation_limit_reached.is_some() {
+            return None;
+        }
+
+        for (line_num, line) in self.lines.by_ref() {
+            self.stats.total_lines += 1;
+
+            let line = line.trim();
+            if line.is_empty() {
+                self.stats.empty_lines += 1;
+                continue;
+            }
+
+            return match Parser::parse_line_with_config(line, &self.config) {
+                Ok(_)
+                    if self
+                        .max_observations
+                        .is_some_and(|max| self.stats.parsed_successfully >= max) =>
+                {
+                    self.stats.observation_limit_reached = self.max_observations;
+                    None
+                }
+                Ok(obs) => {
+                    if !has_measurements(&obs) {
+                        self.stats.validation_warnings += 1;
+                    }
+                    self.stats.parsed_successfully += 1;
+                    Some(Ok(obs))
+                }
+                Err(mut e) => {
+                    e.line_number = line_num + 1;
+                    self.stats.parse_failures += 1;
+                    warn!(
+                        "Failed to parse line (failure {}/{}): {} - {}",
+                        self.stats.parse_failures,
+                        self.stats.total_lines - self.stats.empty_lines,
+                        e,
+                        line
+                    );
+                    Some(Err(AppError::ParseLine(e)))
+                }
+            };
+        }
+
+        None
+    }
+}
@@ -1,19 +1,156 @@
-use crate::config::Config;
-use crate::db::models::{NewProcessedFile, NewStation};
+use crate::config::{split_data_file_name, Config, DataProduct, LocationFilter};
+use crate::db::models::{
+    FileStatusUpdate, NewDailyObservation, NewObservation, NewProcessedFile, NewStation,
+    NewSubhourlyObservation, ObservationGap, ProcessedFile, ProcessingStatus, Wbanno,
+};
 use crate::db::Repository;
-use crate::error::Result;
-use crate::fetcher::Fetcher;
-use crate::parser::Parser;
+use crate::error::{AppError, Result};
+use crate::fetcher::{DownloadResult, Fetcher, FetcherTrait};
+use crate::metrics;
+use crate::parser::{Parser, DEFAULT_FAILURE_THRESHOLD};
+use crate::watcher::DirectoryWatcher;
 use chrono::Datelike;
-use std::sync::Arc;
-use tokio::sync::watch;
+use futures::{FutureExt, StreamExt};
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio::task::{JoinError, JoinSet};
 use tokio::time::{interval, Duration};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+/// Quiet period before a changed file in watch mode is processed
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Gaps longer than this many hours are reported as critical
+const CRITICAL_GAP_HOURS: i64 = 24;
+
+/// Summary of notable events from one ingestion run
+#[derive(Debug, Clone, Default)]
+pub struct RunReport {
+    /// Historical files whose content no longer matches the stored hash
+    pub suspicious_files: Vec<String>,
+    /// Current-year gaps longer than `scheduler.max_allowed_gap_hours`
+    pub gap_alerts: Vec<GapAlert>,
+}
+
+/// A run of missing observations longer than the configured limit
+#[derive(Debug, Clone)]
+pub struct GapAlert {
+    pub gap: ObservationGap,
+    /// Gap exceeds `CRITICAL_GAP_HOURS`
+    pub critical: bool,
+}
+
+/// Per-run state threaded through year and file processing
+struct RunState {
+    /// `ingestion_runs.id` recorded on every observation written this run
+    run_id: i32,
+    /// Final file statuses, written in one statement once all years are done
+    status_updates: Vec<FileStatusUpdate>,
+}
+
+/// Result of processing a single file
+enum FileOutcome {
+    /// Observations were stored; holds the rows affected
+    Processed(usize),
+    /// Content hash differs from a previous run; nothing was stored
+    Suspicious,
+    /// File was rejected (e.g. too large) or held no observations for the
+    /// configured stations; nothing was stored
+    Skipped,
+    /// Server reported the file unchanged since the last completed run
+    NotModified,
+}
+
+/// Why a file is being processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileTrigger {
+    /// Regular run: files unchanged since their last completed run are skipped
+    Scheduled,
+    /// Year listed in `force_reprocess_years`: always downloaded and re-parsed
+    Forced,
+    /// Named by the caller (`ingest --file`, backfill): always downloaded and
+    /// re-parsed, and stored whole regardless of the station filter
+    Explicit,
+}
+
+/// Output of one year task in `Scheduler::run_ingestion`
+type YearResult = (i32, Result<()>, RunReport, RunState);
+
+/// Fold a finished year task into the run's report and state
+///
+/// A failed or panicked year is logged and does not affect the other years.
+/// A year that panicked still reports the statuses of the files it finished
+/// (see `Scheduler::run_ingestion`).
+fn merge_year_result(
+    result: std::result::Result<YearResult, JoinError>,
+    report: &mut RunReport,
+    state: &mut RunState,
+) {
+    match result {
+        Ok((year, result, year_report, year_state)) => {
+            if let Err(e) = result {
+                error!("Error processing year {}: {}", year, e);
+            }
+            report.suspicious_files.extend(year_report.suspicious_files);
+            report.gap_alerts.extend(year_report.gap_alerts);
+            state.status_updates.extend(year_state.status_updates);
+        }
+        Err(e) => error!("Year processing task failed: {}", e),
+    }
+}
+
+/// Download permits keyed by state, so one prolific state cannot take every slot
+pub struct StateAwareSemaphore {
+    permits_per_state: usize,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl StateAwareSemaphore {
+    pub fn new(permits_per_state: usize) -> Self {
+        Self {
+            permits_per_state,
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a download slot for `state`
+    ///
+    /// The slot is released when the returned permit is dropped.
+    pub async fn acquire(&self, state: &str) -> OwnedSemaphorePermit {
+        let semaphore = {
+            let mut semaphores = self.semaphores.lock().unwrap_or_else(|e| e.into_inner());
+            semaphores
+                .entry(state.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(self.permits_per_state)))
+                .clone()
+        };
+
+        semaphore
+            .acquire_owned()
+            .await
+            .expect("state semaphores are never closed")
+    }
+
+    /// Slots currently free for `state`
+    pub fn available_permits(&self, state: &str) -> usize {
+        self.semaphores
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(state)
+            .map_or(self.permits_per_state, |s| s.available_permits())
+    }
+}
 
 pub struct Scheduler {
     config: Config,
     repository: Arc<Repository>,
     shutdown_rx: watch::Receiver<bool>,
+    last_run_duration_secs: Option<u64>,
+    /// Years to process instead of `config.source.years_to_fetch`
+    override_years: Option<Vec<i32>>,
 }
 
 impl Scheduler {
@@ -26,6 +163,69 @@ impl Scheduler {
             config,
             repository,
             shutdown_rx,
+            last_run_duration_secs: None,
+            override_years: None,
+        }
+    }
+
+    /// Process exactly `years`, ignoring `source.years_to_fetch`
+    pub fn with_years_override(mut self, years: Vec<i32>) -> Self {
+        self.override_years = Some(years);
+        self
+    }
+
+    /// Years covered by a run: the override if set, otherwise the config
+    fn years(&self) -> Vec<i32> {
+        match &self.override_years {
+            Some(years) => years.clone(),
+            None => self.config.source.years_to_fetch.get_years(),
+        }
+    }
+
+    /// A scheduler sharing this one's config and repository, for a year task
+    fn year_worker(&self) -> Scheduler {
+        Scheduler {
+            config: self.config.clone(),
+            repository: Arc::clone(&self.repository),
+            shutdown_rx: self.shutdown_rx.clone(),
+            last_run_duration_secs: None,
+            override_years: None,
+        }
+    }
+
+    pub(crate) fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub(crate) fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    /// Duration of the most recent ingestion run, if one has completed
+    pub fn last_run_duration_secs(&self) -> Option<u64> {
+        self.last_run_duration_secs
+    }
+
+    /// Run ingestion, recording its duration and warning if it outlasts the interval
+    async fn run_ingestion_timed(&mut self) {
+        let started = std::time::Instant::now();
+
+        if let Err(e) = self.run_ingestion().await {
+            error!("Ingestion error: {}", e);
+        }
+
+        let elapsed_secs = started.elapsed().as_secs();
+        self.last_run_duration_secs = Some(elapsed_secs);
+
+        let interval_secs = self.config.scheduler.interval_minutes * 60;
+        if elapsed_secs > interval_secs {
+            warn!(
+                "Ingestion run took {}s, longer than the {}m interval; runs will back up. \
+                 Consider setting scheduler.interval_minutes to at least {}",
+                elapsed_secs,
+                self.config.scheduler.interval_minutes,
+                suggested_min_interval_minutes(elapsed_secs)
+            );
         }
     }
 
@@ -38,6 +238,11 @@ impl Scheduler {
             self.config.scheduler.initial_delay_seconds, self.config.scheduler.interval_minutes
         );
 
+        // Fail fast rather than polling forever against a listing we can't parse
+        Fetcher::from_config(&self.config.source)?
+            .check_listing_format()
+            .await?;
+
         // Initial delay
         tokio::select! {
             _ = tokio::time::sleep(initial_delay) => {},
@@ -48,9 +253,7 @@ impl Scheduler {
         }
 
         // Run immediately, then on interval
-        if let Err(e) = self.run_ingestion().await {
-            error!("Ingestion error: {}", e);
-        }
+        self.run_ingestion_timed().await;
 
         let mut ticker = interval(poll_interval);
         ticker.tick().await; // First tick is immediate, skip it
@@ -58,9 +261,7 @@ impl Scheduler {
         loop {
             tokio::select! {
                 _ = ticker.tick() => {
-                    if let Err(e) = self.run_ingestion().await {
-                        error!("Ingestion error: {}", e);
-                    }
+                    self.run_ingestion_timed().await;
                 }
                 _ = self.shutdown_rx.changed() => {
                     info!("Shutdown signal received, stopping scheduler");
@@ -72,33 +273,408 @@ impl Scheduler {
         Ok(())
     }
 
-    async fn run_ingestion(&self) -> Result<()> {
+    /// Process files as they appear or change in a local directory
+    ///
+    /// An alternative to polling for setups where files are pushed locally
+    /// (e.g. by rsync). Files already present are not processed until they
+    /// change. The whole session is recorded as one ingestion run.
+    pub async fn watch(&mut self, dir: &Path) -> Result<()> {
+        let mut fetcher = Fetcher::from_wget_mirror_with_product(dir, self.config.source.product)?;
+        let mut watcher = DirectoryWatcher::new(dir, WATCH_DEBOUNCE)?;
+        let run = self.repository.start_ingestion_run().await?;
+
+        info!("Watching {} for USCRN data files", dir.display());
+
+        loop {
+            let paths = tokio::select! {
+                batch = watcher.next_batch() => match batch {
+                    Some(paths) => paths,
+                    None => {
+                        warn!("Directory watcher stopped");
+                        break;
+                    }
+                },
+                _ = self.shutdown_rx.changed() => {
+                    info!("Shutdown signal received, stopping watcher");
+                    break;
+                }
+            };
+
+            for path in paths {
+                let Some(file_info) = fetcher.index_mirror_file(&path) else {
+                    debug!("Ignoring non-data file {}", path.display());
+                    continue;
+                };
+
+                if !self.config.locations.matches_file(&file_info.name)
+                    || self.config.source.is_skipped(&file_info.name)
+                {
+                    debug!("Ignoring filtered file {}", file_info.name);
+                    continue;
+                }
+
+                info!("Processing file: {}", file_info.name);
+                let stored = match self.repository.get_processed_file(&file_info.name).await {
+                    Ok(stored) => stored,
+                    Err(e) => {
+                        error!("Error looking up {}: {}", file_info.name, e);
+                        continue;
+                    }
+                };
+                let mut state = RunState {
+                    run_id: run.id,
+                    status_updates: Vec::new(),
+                };
+                match self
+                    .process_file(
+                        &fetcher,
+                        &file_info,
+                        stored.as_ref(),
+                        FileTrigger::Scheduled,
+                        &mut state,
+                    )
+                    .await
+                {
+                    Ok(FileOutcome::Processed(rows)) => {
+                        info!("Processed {} observations from {}", rows, file_info.name);
+                    }
+                    Ok(FileOutcome::Suspicious) => {
+                        warn!("Suspicious file: {}", file_info.name);
+                    }
+                    Ok(FileOutcome::Skipped) => {}
+                    Ok(FileOutcome::NotModified) => {
+                        debug!("{} not modified since last run", file_info.name);
+                    }
+                    Err(e) => {
+                        error!("Error processing {}: {}", file_info.name, e);
+                        self.record_file_error(&file_info.name, &e).await;
+                    }
+                }
+
+                if let Err(e) = self
+                    .repository
+                    .bulk_update_file_statuses(&state.status_updates)
+                    .await
+                {
+                    error!("Failed to record status for {}: {}", file_info.name, e);
+                }
+            }
+        }
+
+        self.repository.complete_ingestion_run(run.id).await?;
+        Ok(())
+    }
+
+    /// Ingest one named data file, recorded as its own ingestion run
+    ///
+    /// The file is looked up in its year's listing. Location filters and the
+    /// reprocess window do not apply, and the file is downloaded even if
+    /// unchanged, so any listed file can be ingested whole.
+    ///
+    /// # Returns
+    /// The observations stored; 0 if the file was skipped or unchanged
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if the name is not a data file name,
+    /// the file is not listed, or its content hash differs from a previous run
+    pub async fn ingest_file(&self, file_name: &str) -> Result<usize> {
+        let (year, _) = split_data_file_name(file_name).ok_or_else(|| {
+            AppError::InvalidData(format!(
+                "'{}' is not a data file name (e.g. CRNH0203-2024-TX_Austin_33_NW.txt)",
+                file_name
+            ))
+        })?;
+
+        let fetcher = Fetcher::from_config(&self.config.source)?;
+        let file_info = fetcher
+            .list_files_for_year(year, &LocationFilter::default())
+            .await?
+            .into_iter()
+            .find(|f| f.name == file_name)
+            .ok_or_else(|| {
+                AppError::InvalidData(format!("File {} is not listed for {}", file_name, year))
+            })?;
+        let stored = self.repository.get_processed_file(file_name).await?;
+
+        let run = self.repository.start_ingestion_run().await?;
+        let result = self
+            .ingest_file_info(
+                &fetcher,
+                &file_info,
+                stored.as_ref(),
+                FileTrigger::Explicit,
+                run.id,
+            )
+            .await;
+        self.repository.complete_ingestion_run(run.id).await?;
+        result
+    }
+
+    /// Process one file as part of ingestion run `run_id` and write its final status
+    ///
+    /// # Returns
+    /// The observations stored; 0 if the file was skipped or unchanged
+    ///
+    /// # Errors
+    /// Returns `AppError::InvalidData` if its content hash differs from `stored`
+    pub(crate) async fn ingest_file_info(
+        &self,
+        fetcher: &Fetcher,
+        file_info: &crate::fetcher::FileInfo,
+        stored: Option<&ProcessedFile>,
+        trigger: FileTrigger,
+        run_id: i32,
+    ) -> Result<usize> {
+        let mut state = RunState {
+            run_id,
+            status_updates: Vec::new(),
+        };
+        let outcome = self
+            .process_file(fetcher, file_info, stored, trigger, &mut state)
+            .await;
+        if let Err(e) = &outcome {
+            self.record_file_error(&file_info.name, e).await;
+        }
+        self.repository
+            .bulk_update_file_statuses(&state.status_updates)
+            .await?;
+
+        match outcome? {
+            FileOutcome::Processed(rows) => Ok(rows),
+            FileOutcome::Skipped | FileOutcome::NotModified => Ok(0),
+            FileOutcome::Suspicious => Err(AppError::InvalidData(format!(
+                "Content hash of {} differs from a previous run; nothing was stored",
+                file_info.name
+            ))),
+        }
+    }
+
+    /// Delete observations older than `retention.retain_days`
+    ///
+    /// A failure is logged rather than failing the run; the next run retries.
+    async fn enforce_retention(&self, cutoff: chrono::DateTime<chrono::Utc>) {
+        let retain_days = self.config.retention.retain_days;
+        match self.repository.delete_observations_before(cutoff).await {
+            Ok(deleted) => info!(
+                "Retention: deleted {} observations older than {} days",
+                deleted, retain_days
+            ),
+            Err(e) => warn!("Retention: failed to delete old observations: {}", e),
+        }
+    }
+
+    /// Estimate how long a full ingestion run would take
+    ///
+    /// Counts the files a run would process for each configured year: those
+    /// listed after location filtering, less `skip_files` and, for historical
+    /// years not in `force_reprocess_years`, files already processed. Then
+    /// applies `RunEstimateConfig::duration_for`. Performs no writes.
+    pub async fn estimate_run_duration(&self, fetcher: &dyn FetcherTrait) -> Result<Duration> {
+        let current_year = chrono::Utc::now().year();
+        // Estimates run before migrations; a fresh database has processed nothing
+        let migrated = self
+            .repository
+            .get_applied_migration_version()
+            .await?
+            .is_some();
+        let mut total_files = 0u64;
+        for year in self.years() {
+            let names: Vec<String> = fetcher
+                .list_files_for_year(year, &self.config.locations)
+                .await?
+                .into_iter()
+                .map(|f| f.name)
+                .filter(|name| !self.config.source.is_skipped(name))
+                .collect();
+
+            let reprocessed =
+                year == current_year || self.config.scheduler.force_reprocess_years.contains(&year);
+            let already_processed = if reprocessed || !migrated {
+                0
+            } else {
+                self.repository
+                    .batch_get_processed_files(&names)
+                    .await?
+                    .len()
+            };
+            total_files += (names.len() - already_processed) as u64;
+        }
+
+        info!("Estimating run duration: {} files to process", total_files);
+
+        Ok(self
+            .config
+            .scheduler
+            .estimate
+            .duration_for(total_files, self.config.source.request_delay_ms))
+    }
+
+    /// Run a single ingestion pass over every configured year
+    ///
+    /// Historical files already recorded as processed are skipped; current-year
+    /// files are always re-processed to pick up new hourly data.
+    pub async fn run_ingestion(&self) -> Result<RunReport> {
         info!("Starting ingestion run");
 
-        let fetcher = Fetcher::new(&self.config.source.base_url)?;
-        let years_to_process = self.config.source.years_to_fetch.get_years();
+        if let Some(cutoff) = self.config.retention.cutoff() {
+            self.enforce_retention(cutoff).await;
+        }
+
+        let mut report = RunReport::default();
 
-        info!("Processing years: {:?}", years_to_process);
+        let years_to_process = self.years();
+        let parallel_years = self.config.scheduler.parallel_years();
 
+        info!(
+            "Processing years: {:?} ({} at a time)",
+            years_to_process, parallel_years
+        );
+
+        let run = self.repository.start_ingestion_run().await?;
+        let mut state = RunState {
+            run_id: run.id,
+            status_updates: Vec::new(),
+        };
+
+        // Each year runs as its own task with its own fetcher and state, merged
+        // into the run's report and state as it finishes. Per-state download
+        // slots are shared, so parallel years cannot multiply the limit.
+        let state_permits = Arc::new(StateAwareSemaphore::new(
+            self.config.scheduler.max_concurrent_per_state(),
+        ));
+        let mut years = JoinSet::new();
         for year in years_to_process {
-            if let Err(e) = self.process_year(&fetcher, year).await {
-                error!("Error processing year {}: {}", year, e);
+            if years.len() >= parallel_years {
+                if let Some(result) = years.join_next().await {
+                    merge_year_result(result, &mut report, &mut state);
+                }
             }
+
+            let fetcher = Fetcher::from_config(&self.config.source)?;
+            let worker = self.year_worker();
+            let run_id = run.id;
+            let state_permits = Arc::clone(&state_permits);
+            years.spawn(async move {
+                let mut year_report = RunReport::default();
+                let mut year_state = RunState {
+                    run_id,
+                    status_updates: Vec::new(),
+                };
+                // Catch a panic here rather than in the JoinSet, so the
+                // statuses of files finished before it are still recorded
+                // instead of leaving those files in processing. This relies
+                // on the release profile's `panic = "unwind"`.
+                let result = AssertUnwindSafe(worker.process_year(
+                    &fetcher,
+                    year,
+                    &state_permits,
+                    &mut year_report,
+                    &mut year_state,
+                ))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|panic| {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    Err(AppError::InvalidData(format!(
+                        "Year processing panicked: {}",
+                        message
+                    )))
+                });
+                if result.is_ok() && year == chrono::Utc::now().year() {
+                    match worker.check_year_gaps(year).await {
+                        Ok(alerts) => year_report.gap_alerts = alerts,
+                        Err(e) => error!("Error checking gaps for year {}: {}", year, e),
+                    }
+                }
+                (year, result, year_report, year_state)
+            });
+        }
+        while let Some(result) = years.join_next().await {
+            merge_year_result(result, &mut report, &mut state);
+        }
+
+        self.repository
+            .bulk_update_file_statuses(&state.status_updates)
+            .await?;
+        info!(
+            "Recorded final status for {} files",
+            state.status_updates.len()
+        );
+        self.repository.complete_ingestion_run(run.id).await?;
+
+        if !report.suspicious_files.is_empty() {
+            warn!(
+                "Ingestion run found {} suspicious files: {:?}",
+                report.suspicious_files.len(),
+                report.suspicious_files
+            );
         }
 
         info!("Ingestion run completed");
-        Ok(())
+        Ok(report)
     }
 
-    async fn process_year(&self, fetcher: &Fetcher, year: i32) -> Result<()> {
+    /// Find and log runs of missing observations longer than
+    /// `scheduler.max_allowed_gap_hours` in `year`
+    pub async fn check_year_gaps(&self, year: i32) -> Result<Vec<GapAlert>> {
+        let gaps = self
+            .repository
+            .find_observation_gaps(year, self.config.scheduler.max_allowed_gap_hours)
+            .await?;
+
+        let alerts: Vec<GapAlert> = gaps
+            .into_iter()
+            .map(|gap| {
+                warn!(
+                    "Station {} is missing {} hourly observations between {} and {}",
+                    gap.wbanno, gap.missing_hours, gap.gap_start, gap.gap_end
+                );
+                GapAlert {
+                    critical: gap.missing_hours > CRITICAL_GAP_HOURS,
+                    gap,
+                }
+            })
+            .collect();
+
+        if !alerts.is_empty() {
+            warn!(
+                "Year {} has {} observation gaps longer than {} hours ({} critical)",
+                year,
+                alerts.len(),
+                self.config.scheduler.max_allowed_gap_hours,
+                alerts.iter().filter(|a| a.critical).count()
+            );
+        }
+
+        Ok(alerts)
+    }
+
+    async fn process_year(
+        &self,
+        fetcher: &Fetcher,
+        year: i32,
+        state_permits: &StateAwareSemaphore,
+        report: &mut RunReport,
+        state: &mut RunState,
+    ) -> Result<()> {
         let current_year = chrono::Utc::now().year();
         let is_current_year = year == current_year;
+        let force_reprocess = self.config.scheduler.force_reprocess_years.contains(&year);
 
         if is_current_year {
             info!(
                 "Processing year {} (current year - will re-process all files for updates)",
                 year
             );
+        } else if force_reprocess {
+            info!(
+                "Processing year {} (listed in force_reprocess_years - will re-process all files)",
+                year
+            );
         } else {
             info!(
                 "Processing year {} (historical - will skip processed files)",
@@ -110,37 +686,114 @@ impl Scheduler {
             .list_files_for_year(year, &self.config.locations)
             .await?;
 
-        // Fetch all processed files for this year in one query
+        let remote_file_names: Vec<String> = files.iter().map(|f| f.name.clone()).collect();
+
+        // Fetch the processed records for every listed file in one query
         // For current year, we'll still track but won't skip (to handle updates)
-        let processed_files: std::collections::HashSet<String> = self
+        let processed_files = self
             .repository
-            .get_processed_files_for_year(year)
+            .batch_get_processed_files(&remote_file_names)
+            .await?;
+
+        // Local records whose file has disappeared from NOAA (renamed or removed).
+        // Records excluded by the location filter are not in the listing either,
+        // so only files the filter still selects are reported.
+        let stale_files: Vec<String> = self
+            .repository
+            .get_files_not_in_remote(year, &remote_file_names)
             .await?
             .into_iter()
+            .map(|f| f.file_name)
+            .filter(|name| self.config.locations.matches_file(name))
             .collect();
+        if !stale_files.is_empty() {
+            warn!(
+                "{} processed files for {} are no longer listed on the remote: {:?}",
+                stale_files.len(),
+                year,
+                stale_files
+            );
+        }
 
         let mut processed_count = 0;
         let mut skipped_count = 0;
         let mut updated_count = 0;
 
+        let mut pending = Vec::new();
         for file_info in files {
-            let already_processed = processed_files.contains(&file_info.name);
+            // Explicitly excluded files are left unrecorded so removing them
+            // from skip_files lets the next run pick them up
+            if self.config.source.is_skipped(&file_info.name) {
+                debug!("Skipping file listed in skip_files: {}", file_info.name);
+                continue;
+            }
+
+            let stored = processed_files.get(&file_info.name);
+            let already_processed = stored.is_some();
 
             // Skip already-processed files ONLY for past years
-            // Current year files are always re-processed to capture new hourly data
-            if !is_current_year && already_processed {
+            // Current year files are always re-processed to capture new hourly data,
+            // and forced years are re-processed (e.g. after a parser fix)
+            if !is_current_year && !force_reprocess && already_processed {
                 skipped_count += 1;
                 continue;
             }
 
             if already_processed {
-                info!("Re-processing file (current year): {}", file_info.name);
+                info!("Re-processing file: {}", file_info.name);
             } else {
                 info!("Processing file: {}", file_info.name);
             }
+            pending.push((file_info, stored.cloned()));
+        }
+
+        // Up to max_concurrent_downloads files are in flight at once, each
+        // with its own status updates merged into the run state as it finishes
+        let run_id = state.run_id;
+        let trigger = if force_reprocess {
+            FileTrigger::Forced
+        } else {
+            FileTrigger::Scheduled
+        };
+        let request_delay_ms = self.config.source.request_delay_ms;
+        let mut downloads = futures::stream::iter(pending)
+            .map(|(file_info, stored)| async move {
+                let mut file_state = RunState {
+                    run_id,
+                    status_updates: Vec::new(),
+                };
+                let outcome = {
+                    let _permit = state_permits.acquire(&file_info.state).await;
+                    self.process_file(
+                        fetcher,
+                        &file_info,
+                        stored.as_ref(),
+                        trigger,
+                        &mut file_state,
+                    )
+                    .await
+                };
 
-            match self.process_file(fetcher, &file_info).await {
-                Ok(rows) => {
+                // Rate limiting: each download slot waits before its next file
+                if request_delay_ms > 0 {
+                    tokio::time::sleep(Duration::from_millis(request_delay_ms)).await;
+                }
+
+                (file_info, stored.is_some(), outcome, file_state)
+            })
+            .buffer_unordered(self.config.scheduler.max_concurrent_downloads);
+
+        while let Some((file_info, already_processed, outcome, file_state)) = downloads.next().await
+        {
+            state.status_updates.extend(file_state.status_updates);
+            match outcome {
+                Ok(FileOutcome::Suspicious) => {
+                    report.suspicious_files.push(file_info.name.clone());
+                }
+                Ok(FileOutcome::Skipped | FileOutcome::NotModified) => {
+                    skipped_count += 1;
+                }
+                Ok(FileOutcome::Processed(rows)) => {
                     info!("Processed {} observations from {}", rows, file_info.name);
                     if already_processed {
                         updated_count += 1;
@@ -150,19 +803,12 @@ impl Scheduler {
                 }
                 Err(e) => {
                     error!("Error processing {}: {}", file_info.name, e);
+                    self.record_file_error(&file_info.name, &e).await;
                 }
             }
-
-            // Rate limiting: delay between file downloads
-            if self.config.source.request_delay_ms > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_millis(
-                    self.config.source.request_delay_ms,
-                ))
-                .await;
-            }
         }
 
-        if is_current_year {
+        if is_current_year || force_reprocess {
             info!(
                 "Year {} complete: {} new files, {} updated files, {} skipped",
                 year, processed_count, updated_count, skipped_count
@@ -177,16 +823,191 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Store a file processing error for `Repository::get_recent_ingestion_errors`
+    ///
+    /// Failing to store it is only logged; the original error is already logged.
+    async fn record_file_error(&self, file_name: &str, error: &AppError) {
+        if let Err(e) = self
+            .repository
+            .record_ingestion_error(file_name, &error.to_string())
+            .await
+        {
+            warn!("Failed to record error for {}: {}", file_name, e);
+        }
+    }
+
+    /// Download, parse and store one file, logging structured start and
+    /// completion events for timing
     async fn process_file(
         &self,
         fetcher: &Fetcher,
         file_info: &crate::fetcher::FileInfo,
-    ) -> Result<usize> {
-        // Download file
-        let content = fetcher.download_file(&file_info.url).await?;
+        stored: Option<&ProcessedFile>,
+        trigger: FileTrigger,
+        state: &mut RunState,
+    ) -> Result<FileOutcome> {
+        let started = std::time::Instant::now();
+        info!(
+            file = %file_info.name,
+            year = file_info.year,
+            state = %file_info.state,
+            "Starting file processing"
+        );
 
-        // Parse observations
-        let (mut observations, parse_stats) = Parser::parse_file(&content)?;
+        let outcome = self
+            .process_file_contents(fetcher, file_info, stored, trigger, state)
+            .await?;
+
+        let observations = match outcome {
+            FileOutcome::Processed(rows) => {
+                metrics::record_file_processed();
+                rows
+            }
+            FileOutcome::Skipped | FileOutcome::NotModified => {
+                metrics::record_file_skipped();
+                0
+            }
+            FileOutcome::Suspicious => 0,
+        };
+        info!(
+            file = %file_info.name,
+            duration_ms = started.elapsed().as_millis() as u64,
+            observations,
+            "Completed file processing"
+        );
+
+        Ok(outcome)
+    }
+
+    async fn process_file_contents(
+        &self,
+        fetcher: &Fetcher,
+        file_info: &crate::fetcher::FileInfo,
+        stored: Option<&ProcessedFile>,
+        trigger: FileTrigger,
+        state: &mut RunState,
+    ) -> Result<FileOutcome> {
+        // Only a completed file can be skipped as unchanged; an interrupted
+        // or failed one must be downloaded again, as must a forced or
+        // explicitly requested one
+        let since = stored
+            .filter(|_| trigger == FileTrigger::Scheduled)
+            .filter(|f| {
+                f.processing_status.as_deref() == Some(ProcessingStatus::Completed.as_str())
+            })
+            .and_then(|f| f.last_modified);
+
+        // Download file; InvalidData here means the file itself was rejected
+        // (e.g. over max_file_size_bytes), so record it rather than retrying
+        let (content, file_size, last_modified, file_hash) = match fetcher
+            .download_file_if_modified(&file_info.url, since)
+            .await
+        {
+            Ok(DownloadResult::Downloaded {
+                content,
+                size,
+                last_modified,
+                hash,
+            }) => (content, size, last_modified, hash),
+            Ok(DownloadResult::NotModified) => {
+                info!("{} not modified since last run; skipping", file_info.name);
+                return Ok(FileOutcome::NotModified);
+            }
+            Err(AppError::InvalidData(reason)) => {
+                warn!("Skipping {}: {}", file_info.name, reason);
+                let skipped_file = NewProcessedFile {
+                    file_name: file_info.name.clone(),
+                    file_url: file_info.url.clone(),
+                    year: file_info.year,
+                    state: file_info.state.clone(),
+                    station_name: file_info.station_name.clone(),
+                    last_modified: None,
+                    rows_processed: 0,
+                    file_hash: None,
+                    observations_inserted: 0,
+                    observations_updated: 0,
+                    parse_failures: 0,
+                    processing_status: ProcessingStatus::Skipped.to_string(),
+                    data_quality_score: None,
+                    file_size_bytes: None,
+                };
+                self.repository.mark_file_processed(skipped_file).await?;
+                return Ok(FileOutcome::Skipped);
+            }
+            Err(e) => return Err(e),
+        };
+        let file_size_bytes = Some(file_size as i64);
+
+        // Current-year files are downloaded every run, and NOAA often serves
+        // the same content again before new data arrives; nothing would change
+        if trigger == FileTrigger::Scheduled
+            && file_info.year == chrono::Utc::now().year()
+            && stored.is_some_and(|f| {
+                f.processing_status.as_deref() == Some(ProcessingStatus::Completed.as_str())
+                    && f.file_hash.as_deref() == Some(file_hash.as_str())
+            })
+        {
+            info!(
+                "{} content unchanged since last run; skipping",
+                file_info.name
+            );
+            return Ok(FileOutcome::NotModified);
+        }
+
+        // Historical files should never change; a different hash means the
+        // source was tampered with or a mirror is misconfigured. Current-year
+        // files legitimately change every hour, so they are not checked.
+        if file_info.year < chrono::Utc::now().year() {
+            if let Some(stored_hash) = stored
+                .and_then(|f| f.file_hash.as_deref())
+                .filter(|stored_hash| *stored_hash != file_hash)
+            {
+                error!(
+                    "Hash mismatch for {}: stored {}, downloaded {}. Skipping file.",
+                    file_info.name, stored_hash, file_hash
+                );
+                return Ok(FileOutcome::Suspicious);
+            }
+        }
+
+        if self.config.source.product != DataProduct::Hourly02 {
+            let processed_file = NewProcessedFile {
+                file_name: file_info.name.clone(),
+                file_url: file_info.url.clone(),
+                year: file_info.year,
+                state: file_info.state.clone(),
+                station_name: file_info.station_name.clone(),
+                last_modified,
+                rows_processed: 0,
+                file_hash: Some(file_hash),
+                observations_inserted: 0,
+                observations_updated: 0,
+                parse_failures: 0,
+                processing_status: ProcessingStatus::Processing.to_string(),
+                data_quality_score: None,
+                file_size_bytes,
+            };
+            return self
+                .store_product_file(file_info, &content, processed_file, trigger, state)
+                .await;
+        }
+
+        // Parse observations, keeping the stats for a structured log if the file is rejected
+        let (mut observations, mut parse_stats) = Parser::parse_file_unchecked_with_limit(
+            &content,
+            self.config.source.field_separator,
+            self.config.source.max_observations_per_file,
+        );
+        if parse_stats.exceeds_threshold(DEFAULT_FAILURE_THRESHOLD) {
+            error!(
+                file = %file_info.name,
+                failure_rate = parse_stats.failure_rate,
+                threshold = DEFAULT_FAILURE_THRESHOLD,
+                parse_failures = parse_stats.parse_failures,
+                "Parse failure threshold exceeded"
+            );
+        }
+        parse_stats.check(DEFAULT_FAILURE_THRESHOLD)?;
 
         info!(
             "Parsed {} from {}: {} successful, {} failures ({:.1}% success rate)",
@@ -199,46 +1020,154 @@ impl Scheduler {
                 * 100.0
         );
 
-        // Filter observations by station (WBANNO) if configured
+        // For current-year files already fully stored, only keep observations
+        // inside the reprocess window. A new, failed or interrupted file is
+        // ingested whole, or its older rows would never be stored; so is a
+        // forced or explicitly requested one.
+        let stored_completed = trigger == FileTrigger::Scheduled
+            && stored.is_some_and(|f| {
+                f.processing_status.as_deref() == Some(ProcessingStatus::Completed.as_str())
+            });
+        if let Some(window_hours) = self
+            .config
+            .source
+            .reprocess_window_hours
+            .filter(|_| stored_completed)
+        {
+            // A window too large to represent covers every observation
+            let cutoff = i64::try_from(window_hours)
+                .ok()
+                .and_then(chrono::Duration::try_hours)
+                .and_then(|window| chrono::Utc::now().checked_sub_signed(window));
+            if let Some(cutoff) = cutoff.filter(|_| file_info.year == chrono::Utc::now().year()) {
+                let observations_before_window = observations.len();
+                observations.retain(|obs| obs.utc_datetime >= cutoff);
+
+                info!(
+                    "Reprocess window: kept {}/{} observations from the last {}h",
+                    observations.len(),
+                    observations_before_window,
+                    window_hours
+                );
+
+                if observations.is_empty() {
+                    info!(
+                        "No observations within reprocess window for {}, nothing to update",
+                        file_info.name
+                    );
+                    // Record the new hash and Last-Modified so an unchanged
+                    // file is skipped next run
+                    self.repository
+                        .mark_file_processed(NewProcessedFile {
+                            file_name: file_info.name.clone(),
+                            file_url: file_info.url.clone(),
+                            year: file_info.year,
+                            state: file_info.state.clone(),
+                            station_name: file_info.station_name.clone(),
+                            last_modified,
+                            rows_processed: 0,
+                            file_hash: Some(file_hash.clone()),
+                            observations_inserted: 0,
+                            observations_updated: 0,
+                            parse_failures: parse_stats.parse_failures as i32,
+                            processing_status: ProcessingStatus::Completed.to_string(),
+                            data_quality_score: None,
+                            file_size_bytes,
+                        })
+                        .await?;
+                    return Ok(FileOutcome::Processed(0));
+                }
+            }
+        }
+
+        // Rows retention would delete are not stored, or every run would
+        // insert again what the last one's retention pass removed
         let observations_before_filter = observations.len();
-        observations.retain(|obs| self.config.locations.matches_station(obs.wbanno));
+        if let Some(cutoff) = self.config.retention.cutoff() {
+            observations.retain(|obs| obs.utc_datetime >= cutoff);
+            let expired = observations_before_filter - observations.len();
+            if expired > 0 {
+                info!(
+                    "Retention: dropped {} observations from {} older than {} days",
+                    expired, file_info.name, self.config.retention.retain_days
+                );
+            }
+        }
 
-        if observations_before_filter > observations.len() {
+        // Filter observations by station (WBANNO) if configured; a file the
+        // caller named explicitly is stored whole
+        let observations_before_station_filter = observations.len();
+        if trigger != FileTrigger::Explicit {
+            observations.retain(|obs| self.config.locations.matches_station(obs.wbanno.into()));
+        }
+
+        if observations_before_station_filter > observations.len() {
             info!(
                 "Station filter: kept {}/{} observations matching configured stations",
                 observations.len(),
-                observations_before_filter
+                observations_before_station_filter
             );
         }
 
+        if self.config.source.require_complete_observations {
+            let observations_before_completeness = observations.len();
+            observations.retain(NewObservation::is_complete);
+            parse_stats.incomplete_observations =
+                observations_before_completeness - observations.len();
+
+            if parse_stats.incomplete_observations > 0 {
+                info!(
+                    "Dropped {} incomplete observations from {}",
+                    parse_stats.incomplete_observations, file_info.name
+                );
+            }
+        }
+
         if observations.is_empty() {
-            warn!(
-                "No observations remaining after filtering for {}",
-                file_info.name
-            );
+            // A file whose rows were all filtered out was handled correctly;
+            // only a file with nothing usable in it has failed
+            let status = if observations_before_filter > 0 {
+                info!(
+                    "No observations in {} pass the retention, station or completeness filters; skipping",
+                    file_info.name
+                );
+                ProcessingStatus::Skipped
+            } else {
+                warn!(
+                    "No observations remaining after filtering for {}",
+                    file_info.name
+                );
+                ProcessingStatus::Failed
+            };
 
-            // Mark file as processed with failure status
-            let failed_file = NewProcessedFile {
+            let empty_file = NewProcessedFile {
                 file_name: file_info.name.clone(),
                 file_url: file_info.url.clone(),
                 year: file_info.year,
                 state: file_info.state.clone(),
                 station_name: file_info.station_name.clone(),
-                last_modified: None,
+                last_modified,
                 rows_processed: 0,
-                file_hash: None,
+                file_hash: Some(file_hash.clone()),
                 observations_inserted: 0,
                 observations_updated: 0,
                 parse_failures: parse_stats.parse_failures as i32,
-                processing_status: "failed".to_string(),
+                processing_status: status.to_string(),
+                data_quality_score: None,
+                file_size_bytes,
             };
-            self.repository.mark_file_processed(failed_file).await?;
+            self.repository.mark_file_processed(empty_file).await?;
 
-            return Ok(0);
+            return Ok(match status {
+                ProcessingStatus::Skipped => FileOutcome::Skipped,
+                _ => FileOutcome::Processed(0),
+            });
         }
 
+        let data_quality_score = parse_stats.data_quality_score(observations.len());
+
         // Extract unique stations and batch upsert them
-        let mut seen_stations = std::collections::HashMap::new();
+        let mut seen_stations = HashMap::new();
         for obs in &observations {
             seen_stations
                 .entry(obs.wbanno)
@@ -248,6 +1177,9 @@ impl Scheduler {
                     state: file_info.state.clone(),
                     latitude: None,
                     longitude: None,
+                    elevation: None,
+                    commissioned_date: None,
+                    current_crx_version: None,
                 });
         }
 
@@ -257,21 +1189,37 @@ impl Scheduler {
             self.repository.batch_upsert_stations(&stations).await?;
         }
 
+        // Data files hold a single station, so one version applies to all
+        // rows; an older year's file does not replace a newer year's version
+        let latest_observation = observations.iter().map(|obs| obs.utc_datetime).max();
+        if let (Some(version), Some(as_of)) =
+            (most_common_crx_version(&observations), latest_observation)
+        {
+            for station in &stations {
+                self.repository
+                    .update_station_crx_version(station.wbanno, version, as_of)
+                    .await?;
+            }
+        }
+
         // Create preliminary processed_file record to get file_id
-        // Status is "processing" initially in case insertion fails
+        // Status stays "processing" until the end-of-run bulk update, so a
+        // failed insert or an interrupted run is visible
         let preliminary_file = NewProcessedFile {
             file_name: file_info.name.clone(),
             file_url: file_info.url.clone(),
             year: file_info.year,
             state: file_info.state.clone(),
             station_name: file_info.station_name.clone(),
-            last_modified: None,
+            last_modified,
             rows_processed: observations.len() as i32,
-            file_hash: None,
+            file_hash: Some(file_hash.clone()),
             observations_inserted: 0,
             observations_updated: 0,
             parse_failures: parse_stats.parse_failures as i32,
-            processing_status: "processing".to_string(),
+            processing_status: ProcessingStatus::Processing.to_string(),
+            data_quality_score,
+            file_size_bytes,
         };
 
         let file_id = self
@@ -280,9 +1228,10 @@ impl Scheduler {
             .await?;
 
         // Insert observations - this is the critical step
+        let observations = Repository::prepare_observations(observations, file_id);
         let insert_result = self
             .repository
-            .insert_observations(&observations, file_id)
+            .insert_observations(&observations, file_id, Some(state.run_id))
             .await?;
 
         info!(
@@ -293,24 +1242,245 @@ impl Scheduler {
             insert_result.total_rows_affected
         );
 
-        // Update processed_file record with final statistics
-        let final_file = NewProcessedFile {
-            file_name: file_info.name.clone(),
-            file_url: file_info.url.clone(),
-            year: file_info.year,
-            state: file_info.state.clone(),
-            station_name: file_info.station_name.clone(),
-            last_modified: None,
-            rows_processed: observations.len() as i32,
-            file_hash: None,
-            observations_inserted: insert_result.inserted as i32,
-            observations_updated: insert_result.updated as i32,
-            parse_failures: parse_stats.parse_failures as i32,
-            processing_status: "completed".to_string(),
+        // Final statistics are written for all files at the end of the run
+        state.status_updates.push((
+            file_info.name.clone(),
+            ProcessingStatus::Completed,
+            insert_result.inserted as i32,
+            insert_result.updated as i32,
+            parse_stats.parse_failures as i32,
+        ));
+
+        Ok(FileOutcome::Processed(insert_result.total_rows_affected))
+    }
+
+    /// Parse and store a subhourly01 (5-minute) or daily01 file
+    ///
+    /// The reprocess window, completeness filter and crx_version tracking
+    /// apply to hourly observations only; the station filter still applies
+    /// unless the file was requested explicitly. `processed_file` is the
+    /// file's record, completed here with the parse results.
+    async fn store_product_file(
+        &self,
+        file_info: &crate::fetcher::FileInfo,
+        content: &str,
+        mut processed_file: NewProcessedFile,
+        trigger: FileTrigger,
+        state: &mut RunState,
+    ) -> Result<FileOutcome> {
+        let separator = self.config.source.field_separator;
+        let max_observations = self.config.source.max_observations_per_file;
+        let (mut rows, parse_stats) = match self.config.source.product {
+            DataProduct::Daily01 => {
+                let (days, stats) = Parser::parse_daily_file(content, separator, max_observations)?;
+                (ProductRows::Daily(days), stats)
+            }
+            // Hourly02 files never get here; see process_file_contents
+            _ => {
+                let (observations, stats) =
+                    Parser::parse_subhourly_file(content, separator, max_observations)?;
+                (ProductRows::Subhourly(observations), stats)
+            }
         };
+        if trigger != FileTrigger::Explicit {
+            rows.retain_stations(&self.config.locations);
+        }
+
+        processed_file.rows_processed = rows.len() as i32;
+        processed_file.parse_failures = parse_stats.parse_failures as i32;
+        processed_file.data_quality_score = parse_stats.data_quality_score(rows.len());
+
+        if rows.is_empty() {
+            info!(
+                "No observations in {} match the station filter; skipping",
+                file_info.name
+            );
+            processed_file.processing_status = ProcessingStatus::Skipped.to_string();
+            self.repository.mark_file_processed(processed_file).await?;
+            return Ok(FileOutcome::Skipped);
+        }
+
+        let stations: Vec<NewStation> = rows
+            .wbannos()
+            .into_iter()
+            .map(|wbanno| NewStation {
+                wbanno,
+                name: Some(file_info.station_name.clone()),
+                state: file_info.state.clone(),
+                latitude: None,
+                longitude: None,
+                elevation: None,
+                commissioned_date: None,
+                current_crx_version: None,
+            })
+            .collect();
+        self.repository.batch_upsert_stations(&stations).await?;
+
+        let file_id = self.repository.mark_file_processed(processed_file).await?;
+        let inserted = match &rows {
+            ProductRows::Subhourly(observations) => {
+                self.repository
+                    .insert_subhourly_observations(observations, file_id)
+                    .await?
+            }
+            ProductRows::Daily(days) => {
+                self.repository
+                    .insert_daily_observations(days, file_id)
+                    .await?
+            }
+        };
+
+        info!(
+            "Inserted {} {} rows from {}",
+            inserted,
+            self.config.source.product.directory(),
+            file_info.name
+        );
 
-        self.repository.mark_file_processed(final_file).await?;
+        state.status_updates.push((
+            file_info.name.clone(),
+            ProcessingStatus::Completed,
+            inserted as i32,
+            0,
+            parse_stats.parse_failures as i32,
+        ));
 
-        Ok(insert_result.total_rows_affected)
+        Ok(FileOutcome::Processed(inserted))
+    }
+}
+
+/// Parsed rows of a product stored outside the hourly observations table
+enum ProductRows {
+    Subhourly(Vec<NewSubhourlyObservation>),
+    Daily(Vec<NewDailyObservation>),
+}
+
+impl ProductRows {
+    fn len(&self) -> usize {
+        match self {
+            ProductRows::Subhourly(observations) => observations.len(),
+            ProductRows::Daily(days) => days.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keep only rows from stations `filter` selects
+    fn retain_stations(&mut self, filter: &LocationFilter) {
+        match self {
+            ProductRows::Subhourly(observations) => {
+                observations.retain(|obs| filter.matches_station(obs.wbanno.into()))
+            }
+            ProductRows::Daily(days) => {
+                days.retain(|day| filter.matches_station(day.wbanno.into()))
+            }
+        }
+    }
+
+    /// Distinct stations, in order of first appearance
+    fn wbannos(&self) -> Vec<Wbanno> {
+        let all: Vec<Wbanno> = match self {
+            ProductRows::Subhourly(observations) => observations.iter().map(|o| o.wbanno).collect(),
+            ProductRows::Daily(days) => days.iter().map(|d| d.wbanno).collect(),
+        };
+        let mut wbannos = Vec::new();
+        for wbanno in all {
+            if !wbannos.contains(&wbanno) {
+                wbannos.push(wbanno);
+            }
+        }
+        wbannos
+    }
+}
+
+/// The `crx_version` reported by the most observations, if any report one
+///
+/// Ties go to the version reported most recently.
+fn most_common_crx_version(observations: &[NewObservation]) -> Option<&str> {
+    let mut counts: HashMap<&str, (usize, chrono::DateTime<chrono::Utc>)> = HashMap::new();
+    for obs in observations {
+        if let Some(version) = obs.crx_version.as_deref() {
+            let (count, latest) = counts.entry(version).or_insert((0, obs.utc_datetime));
+            *count += 1;
+            *latest = (*latest).max(obs.utc_datetime);
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|&(_, count_and_latest)| count_and_latest)
+        .map(|(version, _)| version)
+}
+
+/// Minimum interval (minutes) leaving 50% headroom over an observed run duration
+fn suggested_min_interval_minutes(run_duration_secs: u64) -> u64 {
+    (run_duration_secs * 3 / 2).div_ceil(60).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggested_min_interval_minutes() {
+        assert_eq!(suggested_min_interval_minutes(30 * 60), 45);
+        assert_eq!(suggested_min_interval_minutes(61), 2);
+        assert_eq!(suggested_min_interval_minutes(0), 1);
+    }
+
+    #[test]
+    fn test_most_common_crx_version() {
+        let (template, _) = Parser::parse_file(
+            include_str!("../tests/fixtures/uscrn_samples/crx_v3_38_fields.txt"),
+            crate::config::FieldSeparator::Whitespace,
+        )
+        .unwrap();
+        // One observation per hour, in the order given
+        let with_versions = |versions: &[Option<&str>]| -> Vec<NewObservation> {
+            versions
+                .iter()
+                .zip(0..)
+                .map(|(v, hour)| NewObservation {
+                    crx_version: v.map(str::to_string),
+                    utc_datetime: template[0].utc_datetime + chrono::Duration::hours(hour),
+                    ..template[0].clone()
+                })
+                .collect()
+        };
+
+        let observations = with_versions(&[Some("3.2"), Some("2.512"), None, Some("3.2")]);
+        assert_eq!(most_common_crx_version(&observations), Some("3.2"));
+
+        // A tie goes to the later version, not the higher string
+        let observations = with_versions(&[Some("9.2"), Some("10.1")]);
+        assert_eq!(most_common_crx_version(&observations), Some("10.1"));
+
+        assert_eq!(most_common_crx_version(&with_versions(&[None])), None);
+    }
+
+    #[tokio::test]
+    async fn test_state_aware_semaphore_limits_each_state() {
+        let permits = StateAwareSemaphore::new(2);
+
+        let ca_1 = permits.acquire("CA").await;
+        let _ca_2 = permits.acquire("CA").await;
+        assert_eq!(permits.available_permits("CA"), 0);
+
+        // A busy state does not block others
+        let _tx = tokio::time::timeout(Duration::from_millis(100), permits.acquire("TX"))
+            .await
+            .expect("TX should not wait on CA permits");
+        assert_eq!(permits.available_permits("TX"), 1);
+
+        // A third CA download waits until a CA slot is released
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), permits.acquire("CA"))
+                .await
+                .is_err()
+        );
+        drop(ca_1);
+        assert_eq!(permits.available_permits("CA"), 1);
     }
 }
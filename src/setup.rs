@@ -0,0 +1,331 @@
+use crate::config::{Config, DEFAULT_BASE_URL};
+use crate::db::Repository;
+use crate::error::{AppError, Result};
+use sqlx::postgres::PgPoolOptions;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Values collected by the setup wizard
+#[derive(Debug, Clone)]
+pub struct SetupAnswers {
+    pub host: String,
+    pub port: u16,
+    pub name: String,
+    pub user: String,
+    pub password: String,
+    pub base_url: String,
+    pub interval_minutes: u64,
+}
+
+impl Config {
+    /// Prompt on stdin for the required settings and write a config file
+    ///
+    /// The database connection is tested before writing; on failure the user
+    /// can choose to write the file anyway.
+    ///
+    /// # Arguments
+    /// * `output_path` - Where to write the generated `config.yaml`
+    pub async fn generate_interactive(output_path: &Path) -> Result<()> {
+        if output_path.exists()
+            && !confirm(&format!(
+                "{} already exists. Overwrite?",
+                output_path.display()
+            ))?
+        {
+            return Err(AppError::Config(format!(
+                "Setup cancelled; {} was left unchanged",
+                output_path.display()
+            )));
+        }
+
+        let answers = SetupAnswers::prompt()?;
+        let content = answers.render();
+
+        let config: Config = serde_yaml::from_str(&content)
+            .map_err(|e| AppError::Config(format!("Generated config is invalid: {}", e)))?;
+        config.validate()?;
+
+        println!("Testing database connection...");
+        match ping_database(&config).await {
+            Ok(()) => println!("Database connection OK"),
+            Err(e) => {
+                println!("Database connection failed: {}", e);
+                if !confirm("Write the config anyway?")? {
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(output_path, content)?;
+
+        // The file holds the database password in plain text
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(output_path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        println!("Wrote {}", output_path.display());
+        Ok(())
+    }
+}
+
+impl SetupAnswers {
+    /// Ask for each setting on stdin, re-prompting until the input is valid
+    pub fn prompt() -> Result<Self> {
+        let host = prompt_valid("Database host", Some("localhost"), non_empty)?;
+        let port = prompt_valid("Database port", Some("5432"), parse_port)?;
+        let name = prompt_valid("Database name", Some("uscrn"), non_empty)?;
+        let user = prompt_valid("Database user", None, non_empty)?;
+
+        let password = loop {
+            let password = rpassword::prompt_password("Database password: ")?;
+            match non_empty(&password) {
+                Ok(password) => break password,
+                Err(e) => println!("  {}", e),
+            }
+        };
+
+        let base_url = prompt_valid("NOAA base URL", Some(DEFAULT_BASE_URL), parse_base_url)?;
+        let interval_minutes =
+            prompt_valid("Scheduler interval (minutes)", Some("60"), parse_interval)?;
+
+        Ok(Self {
+            host,
+            port,
+            name,
+            user,
+            password,
+            base_url,
+            interval_minutes,
+        })
+    }
+
+    /// Render a complete config file, with optional settings commented out
+    pub fn render(&self) -> String {
+        format!(
+            r#"# USCRN Data Ingestion Service Configuration
+# Generated by `uscrn-ingest setup`
+
+# Database connection
+database:
+  host: {host}
+  port: {port}
+  name: {name}
+  user: {user}
+  password: {password}
+  max_connections: 5
+  # High-availability (optional): extra hosts tried after `host`
+  # replica_hosts: ["db-replica-1", "db-replica-2"]
+  # target_session_attrs: "primary"  # any, primary, standby, prefer-standby
+  # schema: "uscrn_prod"  # Keep tables in this schema instead of public
+  # conflict_strategy: upsert_on_wbanno_and_datetime  # or insert_ignore
+  # stream_chunk_size: 500
+
+# Polling configuration
+scheduler:
+  interval_minutes: {interval}
+  initial_delay_seconds: 10
+  # max_concurrent_downloads: 4
+  # max_concurrent_per_state: 4
+  # force_reprocess_years: []
+  # parallel_years: 1
+  # max_allowed_gap_hours: 6
+  # Per-file timings used by the `estimate` command
+  # estimate:
+  #   download_latency_ms: 1500
+  #   parsing_ms: 50
+  #   db_insert_ms: 400
+
+# Data source
+source:
+  base_url: {base_url}
+  years_to_fetch: "current"   # "all", "current", specific years [2023, 2024],
+                              # or every Nth year {{start: 2010, end: 2024, step: 2}}
+  request_delay_ms: 500
+  # allow_http: false
+  # max_file_size_bytes: 52428800
+  # reprocess_window_hours: 48
+  # field_separator: whitespace
+  # product: hourly02  # or subhourly01, daily01
+  # listing_timeout_secs: 30
+  # download_timeout_secs: 300
+  # content_cache_size: 0
+  # require_complete_observations: false
+  # min_expected_files_per_year: 100
+  # strict_file_count: false
+  # max_observations_per_file: 20000
+  # skip_files: []
+  # retry:
+  #   max_retries: 3
+  #   base_delay_ms: 1000
+  #   max_delay_ms: 30000
+  #   jitter_ms: 1000
+
+# Location filtering (empty arrays = collect all locations)
+locations:
+  states: []
+  stations: []
+  patterns: []
+
+# Prometheus metrics (optional)
+# metrics:
+#   enabled: false
+#   bind_address: "0.0.0.0:9000"
+
+# Delete old observations at the start of each run (optional)
+# retention:
+#   enabled: false
+#   retain_days: 3650
+
+# HTTP API (optional; needs a build with --features api)
+# api_server:
+#   enabled: false
+#   bind_address: "0.0.0.0:8080"
+"#,
+            host = yaml_string(&self.host),
+            port = self.port,
+            name = yaml_string(&self.name),
+            user = yaml_string(&self.user),
+            password = yaml_string(&self.password),
+            interval = self.interval_minutes,
+            base_url = yaml_string(&self.base_url),
+        )
+    }
+}
+
+async fn ping_database(config: &Config) -> Result<()> {
+    let pool = config
+        .database
+        .connect_with(
+            PgPoolOptions::new()
+                .max_connections(1)
+                .acquire_timeout(std::time::Duration::from_secs(10)),
+        )
+        .await?;
+    Repository::new(pool).ping().await
+}
+
+/// Quote a value as a YAML scalar so special characters survive
+fn yaml_string(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+fn read_line(label: &str) -> Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(AppError::Config(
+            "Setup aborted: no more input on stdin".to_string(),
+        ));
+    }
+    Ok(line.trim().to_string())
+}
+
+fn prompt_valid<T>(
+    label: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> std::result::Result<T, String>,
+) -> Result<T> {
+    let label = match default {
+        Some(default) => format!("{} [{}]: ", label, default),
+        None => format!("{}: ", label),
+    };
+
+    loop {
+        let input = read_line(&label)?;
+        let input = match (input.is_empty(), default) {
+            (true, Some(default)) => default.to_string(),
+            _ => input,
+        };
+        match parse(&input) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("  {}", e),
+        }
+    }
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    let answer = read_line(&format!("{} [y/N]: ", question))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn non_empty(input: &str) -> std::result::Result<String, String> {
+    if input.trim().is_empty() {
+        Err("A value is required".to_string())
+    } else {
+        Ok(input.to_string())
+    }
+}
+
+fn parse_port(input: &str) -> std::result::Result<u16, String> {
+    match input.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("'{}' is not a valid port (1-65535)", input)),
+    }
+}
+
+fn parse_base_url(input: &str) -> std::result::Result<String, String> {
+    let url = url::Url::parse(input).map_err(|e| format!("Invalid URL: {}", e))?;
+    if url.scheme() != "https" {
+        return Err("The base URL must use HTTPS".to_string());
+    }
+    Ok(input.to_string())
+}
+
+fn parse_interval(input: &str) -> std::result::Result<u64, String> {
+    match input.parse::<u64>() {
+        Ok(minutes) if minutes > 0 => Ok(minutes),
+        _ => Err(format!("'{}' is not a positive number of minutes", input)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers() -> SetupAnswers {
+        SetupAnswers {
+            host: "db.example.com".to_string(),
+            port: 5433,
+            name: "uscrn".to_string(),
+            user: "ingest".to_string(),
+            password: "p@ss: 'word' #1".to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            interval_minutes: 30,
+        }
+    }
+
+    #[test]
+    fn test_render_produces_valid_config() {
+        let content = answers().render();
+        let config: Config = serde_yaml::from_str(&content).unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.database.host, "db.example.com");
+        assert_eq!(config.database.port, 5433);
+        assert_eq!(config.database.password, "p@ss: 'word' #1");
+        assert_eq!(config.scheduler.interval_minutes, 30);
+        assert_eq!(config.source.base_url, DEFAULT_BASE_URL);
+        assert_eq!(config.source.max_file_size_bytes, None);
+    }
+
+    #[test]
+    fn test_input_validators() {
+        assert!(non_empty("  ").is_err());
+        assert_eq!(parse_port("5432"), Ok(5432));
+        assert!(parse_port("0").is_err());
+        assert!(parse_port("70000").is_err());
+        assert!(parse_base_url("http://www.ncei.noaa.gov/").is_err());
+        assert!(parse_base_url("not a url").is_err());
+        assert_eq!(parse_interval("15"), Ok(15));
+        assert!(parse_interval("0").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::db::models::{NewStation, Wbanno};
+use crate::error::{AppError, Result};
+use crate::fetcher::Fetcher;
+use chrono::NaiveDate;
+use tracing::{info, warn};
+
+/// Official NOAA USCRN station list
+pub const DEFAULT_STATIONS_URL: &str =
+    "https://www.ncei.noaa.gov/pub/data/uscrn/products/stations.tsv";
+
+/// Column layout resolved from the station list header row
+struct Columns {
+    wbanno: usize,
+    state: usize,
+    name: Option<usize>,
+    location: Option<usize>,
+    vector: Option<usize>,
+    latitude: usize,
+    longitude: usize,
+    elevation: Option<usize>,
+    commissioning: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &[&str]) -> Result<Self> {
+        let find = |names: &[&str]| {
+            header
+                .iter()
+                .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+        };
+        let require = |names: &[&str]| {
+            find(names).ok_or_else(|| {
+                AppError::Parse(format!(
+                    "Station list header missing required column '{}'",
+                    names[0]
+                ))
+            })
+        };
+
+        Ok(Self {
+            wbanno: require(&["WBANNO", "WBAN"])?,
+            state: require(&["STATE"])?,
+            name: find(&["NAME"]),
+            location: find(&["LOCATION"]),
+            vector: find(&["VECTOR"]),
+            latitude: require(&["LATITUDE"])?,
+            longitude: require(&["LONGITUDE"])?,
+            elevation: find(&["ELEVATION"]),
+            commissioning: find(&["COMMISSIONING"]),
+        })
+    }
+}
+
+pub struct StationMetadataLoader;
+
+impl StationMetadataLoader {
+    /// Download and parse the NOAA station list
+    ///
+    /// # Arguments
+    /// * `url` - The station list URL (must be from allowed NOAA hosts)
+    pub async fn load_from_url(url: &str) -> Result<Vec<NewStation>> {
+        let fetcher = Fetcher::new(url)?;
+        let content = fetcher.download_file(url).await?;
+        let stations = Self::parse(&content)?;
+        info!("Loaded {} stations from {}", stations.len(), url);
+        Ok(stations)
+    }
+
+    /// Parse the NOAA station list
+    ///
+    /// The first non-empty line is a header naming each column. Tab-separated
+    /// files are split on tabs; otherwise columns are treated as fixed-width,
+    /// starting at the offset of each header label.
+    pub fn parse(content: &str) -> Result<Vec<NewStation>> {
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+
+        let header_line = lines
+            .next()
+            .ok_or_else(|| AppError::Parse("Station list is empty".to_string()))?;
+
+        let tab_separated = header_line.contains('\t');
+        let offsets = if tab_separated {
+            Vec::new()
+        } else {
+            header_offsets(header_line)
+        };
+
+        let header = split_line(header_line, tab_separated, &offsets);
+        let columns = Columns::from_header(&header)?;
+
+        let mut stations = Vec::new();
+        for (line_num, line) in lines.enumerate() {
+            let fields = split_line(line, tab_separated, &offsets);
+            match parse_station(&fields, &columns) {
+                Ok(station) => stations.push(station),
+                Err(e) => warn!(
+                    "Skipping station list line {}: {} - {}",
+                    line_num + 2,
+                    e,
+                    line
+                ),
+            }
+        }
+
+        Ok(stations)
+    }
+}
+
+/// Start offsets of each whitespace-delimited label in a fixed-width header
+fn header_offsets(header: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut in_label = false;
+    for (i, c) in header.char_indices() {
+        if c.is_whitespace() {
+            in_label = false;
+        } else if !in_label {
+            offsets.push(i);
+            in_label = true;
+        }
+    }
+    offsets
+}
+
+fn split_line<'a>(line: &'a str, tab_separated: bool, offsets: &[usize]) -> Vec<&'a str> {
+    if tab_separated {
+        return line.split('\t').map(str::trim).collect();
+    }
+
+    offsets
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = offsets.get(i + 1).copied().unwrap_or(line.len());
+            line.get(start.min(line.len())..end.min(line.len()))
+                .unwrap_or("")
+                .trim()
+        })
+        .collect()
+}
+
+fn parse_station(fields: &[&str], columns: &Columns) -> Result<NewStation> {
+    let field = |idx: usize| fields.get(idx).copied().unwrap_or("");
+    let optional = |idx: Option<usize>| idx.map(field).filter(|s| !s.is_empty());
+
+    let wbanno = field(columns.wbanno)
+        .parse::<i32>()
+        .map(Wbanno)
+        .map_err(|_| AppError::Parse(format!("Invalid WBANNO '{}'", field(columns.wbanno))))?;
+
+    let state = field(columns.state).to_uppercase();
+    if state.len() != 2 {
+        return Err(AppError::Parse(format!("Invalid state '{}'", state)));
+    }
+
+    // Build the name the same way USCRN data filenames do (e.g. Bodega_6_WSW)
+    // so it matches names recorded during ingestion, falling back to NAME
+    let parts: Vec<&str> = [optional(columns.location), optional(columns.vector)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let name = if parts.is_empty() {
+        optional(columns.name).map(str::to_string)
+    } else {
+        Some(parts.join(" "))
+    }
+    .map(|n| n.split_whitespace().collect::<Vec<_>>().join("_"));
+
+    Ok(NewStation {
+        wbanno,
+        name,
+        state,
+        latitude: parse_coordinate(field(columns.latitude)),
+        longitude: parse_coordinate(field(columns.longitude)),
+        elevation: optional(columns.elevation).and_then(parse_coordinate),
+        commissioned_date: optional(columns.commissioning).and_then(parse_date),
+        current_crx_version: None,
+    })
+}
+
+fn parse_coordinate(s: &str) -> Option<f64> {
+    s.parse::<f64>().ok()
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y%m%d", "%m/%d/%Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tab_separated() {
+        let content = "WBAN\tCOUNTRY\tSTATE\tLOCATION\tVECTOR\tNAME\tLATITUDE\tLONGITUDE\tELEVATION\tSTATUS\tCOMMISSIONING\n\
+                       93245\tUS\tCA\tBodega\t6 WSW\tCA Bodega 6 WSW\t38.32\t-123.07\t62\tCommissioned\t2004-06-15\n\
+                       03761\tUS\tPA\tAvondale\t2 N\tPA Avondale 2 N\t39.86\t-75.78\t348\tCommissioned\t2008-06-17\n";
+
+        let stations = StationMetadataLoader::parse(content).unwrap();
+        assert_eq!(stations.len(), 2);
+
+        let avondale = &stations[1];
+        assert_eq!(avondale.wbanno, Wbanno(3761));
+        assert_eq!(avondale.state, "PA");
+        assert_eq!(avondale.name, Some("Avondale_2_N".to_string()));
+        assert_eq!(avondale.latitude, Some(39.86));
+        assert_eq!(avondale.longitude, Some(-75.78));
+        assert_eq!(avondale.elevation, Some(348.0));
+        assert_eq!(
+            avondale.commissioned_date,
+            NaiveDate::from_ymd_opt(2008, 6, 17)
+        );
+    }
+
+    #[test]
+    fn test_parse_fixed_width() {
+        let content = "WBANNO STATE LOCATION  VECTOR LATITUDE LONGITUDE ELEVATION COMMISSIONING\n\
+                       93245  CA    Bodega    6 WSW  38.32    -123.07   62        20040615\n\
+                       53104  NC    Asheville 8 SSW  35.49    -82.61    2148\n";
+
+        let stations = StationMetadataLoader::parse(content).unwrap();
+        assert_eq!(stations.len(), 2);
+
+        let bodega = &stations[0];
+        assert_eq!(bodega.wbanno, Wbanno(93245));
+        assert_eq!(bodega.name, Some("Bodega_6_WSW".to_string()));
+        assert_eq!(bodega.elevation, Some(62.0));
+        assert_eq!(
+            bodega.commissioned_date,
+            NaiveDate::from_ymd_opt(2004, 6, 15)
+        );
+
+        assert_eq!(stations[1].commissioned_date, None);
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_rows() {
+        let content = "WBAN\tSTATE\tLATITUDE\tLONGITUDE\n\
+                       abc\tCA\t38.0\t-123.0\n\
+                       93245\tCA\t38.32\t-123.07\n";
+
+        let stations = StationMetadataLoader::parse(content).unwrap();
+        assert_eq!(stations.len(), 1);
+        assert_eq!(stations[0].name, None);
+    }
+
+    #[test]
+    fn test_parse_missing_required_column() {
+        let content = "WBAN\tSTATE\tLATITUDE\n93245\tCA\t38.32\n";
+        let err = StationMetadataLoader::parse(content).unwrap_err();
+        assert!(err.to_string().contains("LONGITUDE"));
+    }
+}
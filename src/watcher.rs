@@ -0,0 +1,148 @@
+use crate::error::Result;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Delays handling of a path until it has stopped changing
+///
+/// rsync and similar tools emit several create/modify events per file; each
+/// event restarts that path's quiet period.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record a change to `path` at `now`
+    pub fn record(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now);
+    }
+
+    /// When the earliest pending path settles, if any are pending
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().min().map(|last| *last + self.delay)
+    }
+
+    /// Remove and return paths with no changes for the debounce delay
+    pub fn take_settled(&mut self, now: Instant) -> Vec<PathBuf> {
+        let settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= self.delay)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in &settled {
+            self.pending.remove(path);
+        }
+
+        settled
+    }
+}
+
+/// Watches a directory tree for new or modified `.txt` data files
+pub struct DirectoryWatcher {
+    // Dropping the watcher stops event delivery
+    _watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<PathBuf>,
+    debouncer: Debouncer,
+}
+
+impl DirectoryWatcher {
+    /// Start watching `dir` recursively
+    ///
+    /// # Errors
+    /// Returns `AppError::Io` if the platform watcher cannot be created
+    pub fn new(dir: &Path, debounce: Duration) -> Result<Self> {
+        let (tx, events) = mpsc::unbounded_channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        if path.extension().is_some_and(|ext| ext == "txt") {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("File watch error: {}", e),
+            })
+            .map_err(std::io::Error::other)?;
+
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(std::io::Error::other)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            debouncer: Debouncer::new(debounce),
+        })
+    }
+
+    /// Wait for the next batch of files that have stopped changing
+    ///
+    /// Returns `None` once the underlying watcher has stopped.
+    pub async fn next_batch(&mut self) -> Option<Vec<PathBuf>> {
+        loop {
+            let deadline = self.debouncer.next_deadline();
+
+            tokio::select! {
+                event = self.events.recv() => {
+                    let path = event?;
+                    debug!("Change detected: {}", path.display());
+                    self.debouncer.record(path, Instant::now());
+                }
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    let settled = self.debouncer.take_settled(Instant::now());
+                    if !settled.is_empty() {
+                        return Some(settled);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_period() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_secs(2));
+        let path = PathBuf::from("CRNH0203-2024-CA_Test.txt");
+
+        assert_eq!(debouncer.next_deadline(), None);
+
+        debouncer.record(path.clone(), start);
+        debouncer.record(path.clone(), start + Duration::from_secs(1));
+        assert_eq!(
+            debouncer.next_deadline(),
+            Some(start + Duration::from_secs(3))
+        );
+
+        // The second event restarted the quiet period
+        assert!(debouncer
+            .take_settled(start + Duration::from_secs(2))
+            .is_empty());
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_secs(3)),
+            vec![path]
+        );
+        assert_eq!(debouncer.next_deadline(), None);
+    }
+}
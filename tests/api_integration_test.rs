@@ -0,0 +1,114 @@
+#![cfg(feature = "api")]
+
+mod common;
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uscrn_ingest::api::router;
+use uscrn_ingest::db::models::NewStation;
+use uscrn_ingest::db::Repository;
+
+use common::{make_observations, make_processed_file, make_station};
+
+/// Serve the API on an ephemeral port, returning its base URL
+async fn spawn_api(repository: Repository) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Bind failed");
+    let address = listener.local_addr().expect("No local address");
+    tokio::spawn(async move {
+        axum::serve(listener, router(Arc::new(repository)))
+            .await
+            .expect("API server failed");
+    });
+    format!("http://{}", address)
+}
+
+async fn get_json(url: &str) -> (u16, serde_json::Value) {
+    let response = reqwest::get(url).await.expect("Request failed");
+    let status = response.status().as_u16();
+    let body = response.text().await.expect("Failed to read body");
+    (status, serde_json::from_str(&body).expect("Invalid JSON"))
+}
+
+/// Test the station endpoints, including the state filter and a missing station
+#[sqlx::test]
+async fn test_station_endpoints(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.batch_upsert_stations(&[
+        make_station(53104),
+        NewStation {
+            state: "TX".to_string(),
+            ..make_station(3047)
+        },
+    ])
+    .await
+    .expect("Station insert failed");
+    let base_url = spawn_api(repo).await;
+
+    let (status, body) = get_json(&format!("{}/api/v1/stations", base_url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (status, body) = get_json(&format!("{}/api/v1/stations?state=tx", base_url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body[0]["wbanno"], 3047);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    let (status, body) = get_json(&format!("{}/api/v1/stations/53104", base_url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["wbanno"], 53104);
+
+    let (status, body) = get_json(&format!("{}/api/v1/stations/99999", base_url)).await;
+    assert_eq!(status, 404);
+    assert!(body["error"].as_str().unwrap().contains("99999"));
+}
+
+/// Test observation and daily queries honor the range and limit
+#[sqlx::test]
+async fn test_observation_endpoints(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("api.txt", 2024))
+        .await
+        .expect("File insert failed");
+    let base: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    repo.insert_observations(&make_observations(53104, base, 48), file_id, None)
+        .await
+        .expect("Insert failed");
+    let base_url = spawn_api(repo).await;
+
+    let station_url = format!("{}/api/v1/stations/53104", base_url);
+    let range = "start=2024-01-01T00:00:00Z&end=2024-01-03T00:00:00Z";
+
+    let (status, body) = get_json(&format!("{}/observations?{}", station_url, range)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 48);
+    assert_eq!(body[0]["utc_datetime"], "2024-01-01T00:00:00Z");
+
+    let (status, body) = get_json(&format!("{}/observations?{}&limit=5", station_url, range)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 5);
+
+    let (status, _) = get_json(&format!("{}/observations?{}&limit=0", station_url, range)).await;
+    assert_eq!(status, 400);
+
+    let (status, body) = get_json(&format!("{}/daily?{}", station_url, range)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["date"], "2024-01-01");
+
+    let (status, body) = get_json(&format!("{}/health", base_url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["db_connected"], true);
+    assert_eq!(body["total_observations"], 48);
+
+    let (status, body) = get_json(&format!("{}/healthz", base_url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["db_connected"], true);
+    assert_eq!(body["total_observations"], 48);
+}
@@ -0,0 +1,99 @@
+//! Shared fixture builders for integration tests
+//!
+//! Each builder fills in sensible defaults; override individual fields with
+//! struct update syntax, e.g. `NewStation { state: "NC".to_string(), ..make_station(53104) }`.
+
+// Each test crate compiles this module separately and uses only some builders
+#![allow(dead_code)]
+
+use chrono::{DateTime, Duration, Utc};
+use uscrn_ingest::db::models::{
+    NewObservation, NewProcessedFile, NewStation, ProcessingStatus, Wbanno,
+};
+
+/// Station in CA named "Test Station" with no location metadata
+pub fn make_station(wbanno: i32) -> NewStation {
+    NewStation {
+        wbanno: Wbanno(wbanno),
+        name: Some("Test Station".to_string()),
+        state: "CA".to_string(),
+        latitude: None,
+        longitude: None,
+        elevation: None,
+        commissioned_date: None,
+        current_crx_version: None,
+    }
+}
+
+/// Processed file record in the `processing` state with zeroed counters
+pub fn make_processed_file(file_name: &str, year: i32) -> NewProcessedFile {
+    NewProcessedFile {
+        file_name: file_name.to_string(),
+        file_url: format!("https://example.com/{}", file_name),
+        year,
+        state: "CA".to_string(),
+        station_name: "Test Station".to_string(),
+        last_modified: None,
+        rows_processed: 0,
+        file_hash: None,
+        observations_inserted: 0,
+        observations_updated: 0,
+        parse_failures: 0,
+        processing_status: ProcessingStatus::Processing.to_string(),
+        data_quality_score: None,
+        file_size_bytes: None,
+    }
+}
+
+/// Observation at `dt` with only `t_hr_avg` (20.0) populated
+pub fn make_observation(wbanno: i32, dt: DateTime<Utc>) -> NewObservation {
+    NewObservation {
+        wbanno: Wbanno(wbanno),
+        utc_datetime: dt,
+        lst_datetime: dt,
+        crx_version: Some("3".to_string()),
+        t_calc: None,
+        t_hr_avg: Some(20.0),
+        t_max: None,
+        t_min: None,
+        p_calc: None,
+        solarad: None,
+        solarad_flag: None,
+        solarad_max: None,
+        solarad_max_flag: None,
+        solarad_min: None,
+        solarad_min_flag: None,
+        sur_temp_type: None,
+        sur_temp: None,
+        sur_temp_flag: None,
+        sur_temp_max: None,
+        sur_temp_max_flag: None,
+        sur_temp_min: None,
+        sur_temp_min_flag: None,
+        rh_hr_avg: None,
+        rh_hr_avg_flag: None,
+        soil_moisture_5: None,
+        soil_moisture_10: None,
+        soil_moisture_20: None,
+        soil_moisture_50: None,
+        soil_moisture_100: None,
+        soil_temp_5: None,
+        soil_temp_10: None,
+        soil_temp_20: None,
+        soil_temp_50: None,
+        soil_temp_100: None,
+        source_file_id: None,
+        ingestion_run_id: None,
+    }
+}
+
+/// `count` consecutive hourly observations starting at `base_time`
+pub fn make_observations(
+    wbanno: i32,
+    base_time: DateTime<Utc>,
+    count: usize,
+) -> Vec<NewObservation> {
+    (0..count)
+        .map(|i| make_observation(wbanno, base_time + Duration::hours(i as i64)))
+        .collect()
+}
@@ -1,7 +1,20 @@
-use chrono::Utc;
+mod common;
+
+use chrono::{Datelike, Utc};
+use futures::TryStreamExt;
 use sqlx::PgPool;
-use uscrn_ingest::db::models::{NewObservation, NewProcessedFile, NewStation};
+use uscrn_ingest::config::{ConflictStrategy, FieldSeparator};
+use uscrn_ingest::db::models::{
+    ExportColumns, FileCountMismatch, IntegrityReport, NewObservation, NewProcessedFile,
+    NewStation, Observation, ProcessingStatus, Wbanno, SOIL_DEPTHS_CM,
+};
 use uscrn_ingest::db::Repository;
+use uscrn_ingest::error::AppError;
+use uscrn_ingest::export;
+use uscrn_ingest::gap_detector::{detect_gaps, GapInterval};
+use uscrn_ingest::parser::Parser;
+
+use common::{make_observation, make_observations, make_processed_file, make_station};
 
 /// Test station upsert - insert new station
 #[sqlx::test]
@@ -9,11 +22,9 @@ async fn test_upsert_new_station(pool: PgPool) {
     let repo = Repository::new(pool.clone());
 
     let station = NewStation {
-        wbanno: 12345,
-        name: Some("Test Station".to_string()),
-        state: "CA".to_string(),
         latitude: Some(37.7749),
         longitude: Some(-122.4194),
+        ..make_station(12345)
     };
 
     // Insert station
@@ -42,21 +53,19 @@ async fn test_upsert_updates_existing_station(pool: PgPool) {
 
     // Insert initial station
     let station = NewStation {
-        wbanno: 12345,
         name: Some("Original Name".to_string()),
-        state: "CA".to_string(),
         latitude: Some(37.0),
         longitude: Some(-122.0),
+        ..make_station(12345)
     };
     repo.upsert_station(station).await.expect("Insert failed");
 
     // Update with new data
     let updated_station = NewStation {
-        wbanno: 12345,
         name: Some("Updated Name".to_string()),
-        state: "CA".to_string(),
         latitude: Some(38.0),
         longitude: Some(-123.0),
+        ..make_station(12345)
     };
     repo.upsert_station(updated_station)
         .await
@@ -83,25 +92,24 @@ async fn test_batch_upsert_stations(pool: PgPool) {
 
     let stations = vec![
         NewStation {
-            wbanno: 1001,
             name: Some("Station A".to_string()),
-            state: "CA".to_string(),
             latitude: Some(37.0),
             longitude: Some(-122.0),
+            ..make_station(1001)
         },
         NewStation {
-            wbanno: 1002,
             name: Some("Station B".to_string()),
             state: "TX".to_string(),
             latitude: Some(30.0),
             longitude: Some(-97.0),
+            ..make_station(1002)
         },
         NewStation {
-            wbanno: 1003,
             name: Some("Station C".to_string()),
             state: "NY".to_string(),
             latitude: Some(40.0),
             longitude: Some(-74.0),
+            ..make_station(1003)
         },
     ];
 
@@ -124,31 +132,15 @@ async fn test_insert_observations(pool: PgPool) {
     let repo = Repository::new(pool.clone());
 
     // First insert a station
-    let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
-        state: "CA".to_string(),
-        latitude: None,
-        longitude: None,
-    };
+    let station = make_station(53104);
     repo.upsert_station(station)
         .await
         .expect("Station insert failed");
 
     // Create a processed file to get file_id
     let file = NewProcessedFile {
-        file_name: "test_file.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
-        state: "CA".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 2,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_file.txt", 2024)
     };
     let file_id = repo
         .mark_file_processed(file)
@@ -157,7 +149,7 @@ async fn test_insert_observations(pool: PgPool) {
 
     // Create test observations
     let observations = vec![NewObservation {
-        wbanno: 53104,
+        wbanno: Wbanno(53104),
         utc_datetime: Utc::now(),
         lst_datetime: Utc::now(),
         crx_version: Some("3".to_string()),
@@ -192,14 +184,16 @@ async fn test_insert_observations(pool: PgPool) {
         soil_temp_50: Some(13.5),
         soil_temp_100: Some(13.0),
         source_file_id: None,
+        ingestion_run_id: None,
     }];
 
     let result = repo
-        .insert_observations(&observations, file_id)
+        .insert_observations(&observations, file_id, None)
         .await
         .expect("Observation insert failed");
 
     assert_eq!(result.total_rows_affected, 1);
+    assert_eq!((result.inserted, result.updated), (1, 0));
 
     // Verify observation was inserted
     let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations WHERE wbanno = $1")
@@ -217,31 +211,15 @@ async fn test_upsert_observation_updates_existing(pool: PgPool) {
     let repo = Repository::new(pool.clone());
 
     // Insert station
-    let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
-        state: "CA".to_string(),
-        latitude: None,
-        longitude: None,
-    };
+    let station = make_station(53104);
     repo.upsert_station(station)
         .await
         .expect("Station insert failed");
 
     // Create processed file
     let file = NewProcessedFile {
-        file_name: "test_file.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
-        state: "CA".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 1,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_file.txt", 2024)
     };
     let file_id = repo
         .mark_file_processed(file)
@@ -251,88 +229,19 @@ async fn test_upsert_observation_updates_existing(pool: PgPool) {
     let timestamp = Utc::now();
 
     // Insert initial observation
-    let observation = vec![NewObservation {
-        wbanno: 53104,
-        utc_datetime: timestamp,
-        lst_datetime: timestamp,
-        crx_version: Some("3".to_string()),
-        t_hr_avg: Some(20.0),
-        t_calc: None,
-        t_max: None,
-        t_min: None,
-        p_calc: None,
-        solarad: None,
-        solarad_flag: None,
-        solarad_max: None,
-        solarad_max_flag: None,
-        solarad_min: None,
-        solarad_min_flag: None,
-        sur_temp_type: None,
-        sur_temp: None,
-        sur_temp_flag: None,
-        sur_temp_max: None,
-        sur_temp_max_flag: None,
-        sur_temp_min: None,
-        sur_temp_min_flag: None,
-        rh_hr_avg: None,
-        rh_hr_avg_flag: None,
-        soil_moisture_5: None,
-        soil_moisture_10: None,
-        soil_moisture_20: None,
-        soil_moisture_50: None,
-        soil_moisture_100: None,
-        soil_temp_5: None,
-        soil_temp_10: None,
-        soil_temp_20: None,
-        soil_temp_50: None,
-        soil_temp_100: None,
-        source_file_id: None,
-    }];
+    let observation = vec![make_observation(53104, timestamp)];
 
-    repo.insert_observations(&observation, file_id)
+    repo.insert_observations(&observation, file_id, None)
         .await
         .expect("Initial insert failed");
 
     // Update with new temperature
     let updated_observation = vec![NewObservation {
-        wbanno: 53104,
-        utc_datetime: timestamp,
-        lst_datetime: timestamp,
-        crx_version: Some("3".to_string()),
         t_hr_avg: Some(25.0), // Updated value
-        t_calc: None,
-        t_max: None,
-        t_min: None,
-        p_calc: None,
-        solarad: None,
-        solarad_flag: None,
-        solarad_max: None,
-        solarad_max_flag: None,
-        solarad_min: None,
-        solarad_min_flag: None,
-        sur_temp_type: None,
-        sur_temp: None,
-        sur_temp_flag: None,
-        sur_temp_max: None,
-        sur_temp_max_flag: None,
-        sur_temp_min: None,
-        sur_temp_min_flag: None,
-        rh_hr_avg: None,
-        rh_hr_avg_flag: None,
-        soil_moisture_5: None,
-        soil_moisture_10: None,
-        soil_moisture_20: None,
-        soil_moisture_50: None,
-        soil_moisture_100: None,
-        soil_temp_5: None,
-        soil_temp_10: None,
-        soil_temp_20: None,
-        soil_temp_50: None,
-        soil_temp_100: None,
-        source_file_id: None,
+        ..make_observation(53104, timestamp)
     }];
 
-    repo.insert_observations(&updated_observation, file_id)
+    repo.insert_observations(&updated_observation, file_id, None)
         .await
         .expect("Update failed");
 
@@ -357,37 +266,82 @@ async fn test_upsert_observation_updates_existing(pool: PgPool) {
     assert_eq!(temp, Some(25.0));
 }
 
+/// Test re-inserting an observation keeps created_at but bumps ingested_at
+#[sqlx::test]
+async fn test_upsert_preserves_created_at(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("created_at.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let timestamp = Utc::now();
+    let ingested_at = |pool: PgPool| async move {
+        sqlx::query_scalar::<_, chrono::DateTime<Utc>>(
+            "SELECT ingested_at FROM observations WHERE wbanno = $1 AND utc_datetime = $2",
+        )
+        .bind(53104)
+        .bind(timestamp)
+        .fetch_one(&pool)
+        .await
+        .expect("ingested_at query failed")
+    };
+
+    assert_eq!(
+        repo.get_observation_created_at(Wbanno(53104), timestamp)
+            .await
+            .expect("Query failed"),
+        None
+    );
+
+    repo.insert_observations(&[make_observation(53104, timestamp)], file_id, None)
+        .await
+        .expect("Initial insert failed");
+    let created_at = repo
+        .get_observation_created_at(Wbanno(53104), timestamp)
+        .await
+        .expect("Query failed")
+        .expect("Observation should exist");
+    let first_ingested_at = ingested_at(pool.clone()).await;
+
+    tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+
+    let updated = NewObservation {
+        t_hr_avg: Some(25.0),
+        ..make_observation(53104, timestamp)
+    };
+    repo.insert_observations(&[updated], file_id, None)
+        .await
+        .expect("Update failed");
+
+    assert_eq!(
+        repo.get_observation_created_at(Wbanno(53104), timestamp)
+            .await
+            .expect("Query failed"),
+        Some(created_at)
+    );
+    assert!(ingested_at(pool.clone()).await > first_ingested_at);
+}
+
 /// Test batch insert with large number of observations
 #[sqlx::test]
 async fn test_large_batch_insert(pool: PgPool) {
     let repo = Repository::new(pool.clone());
 
     // Insert station
-    let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
-        state: "CA".to_string(),
-        latitude: None,
-        longitude: None,
-    };
+    let station = make_station(53104);
     repo.upsert_station(station)
         .await
         .expect("Station insert failed");
 
     // Create processed file
     let file = NewProcessedFile {
-        file_name: "large_test.txt".to_string(),
-        file_url: "https://example.com/large_test.txt".to_string(),
-        year: 2024,
-        state: "CA".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 2000,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("large_test.txt", 2024)
     };
     let file_id = repo
         .mark_file_processed(file)
@@ -395,51 +349,10 @@ async fn test_large_batch_insert(pool: PgPool) {
         .expect("File insert failed");
 
     // Create 2000 observations (tests batching logic)
-    let mut observations = Vec::new();
-    let base_time = Utc::now();
-
-    for i in 0..2000 {
-        observations.push(NewObservation {
-            wbanno: 53104,
-            utc_datetime: base_time + chrono::Duration::hours(i),
-            lst_datetime: base_time + chrono::Duration::hours(i),
-            crx_version: Some("3".to_string()),
-            t_hr_avg: Some(20.0 + (i as f32) * 0.1),
-            t_calc: None,
-            t_max: None,
-            t_min: None,
-            p_calc: None,
-            solarad: None,
-            solarad_flag: None,
-            solarad_max: None,
-            solarad_max_flag: None,
-            solarad_min: None,
-            solarad_min_flag: None,
-            sur_temp_type: None,
-            sur_temp: None,
-            sur_temp_flag: None,
-            sur_temp_max: None,
-            sur_temp_max_flag: None,
-            sur_temp_min: None,
-            sur_temp_min_flag: None,
-            rh_hr_avg: None,
-            rh_hr_avg_flag: None,
-            soil_moisture_5: None,
-            soil_moisture_10: None,
-            soil_moisture_20: None,
-            soil_moisture_50: None,
-            soil_moisture_100: None,
-            soil_temp_5: None,
-            soil_temp_10: None,
-            soil_temp_20: None,
-            soil_temp_50: None,
-            soil_temp_100: None,
-            source_file_id: None,
-        });
-    }
+    let observations = make_observations(53104, Utc::now(), 2000);
 
     let result = repo
-        .insert_observations(&observations, file_id)
+        .insert_observations(&observations, file_id, None)
         .await
         .expect("Batch insert failed");
 
@@ -461,18 +374,13 @@ async fn test_mark_file_processed(pool: PgPool) {
     let repo = Repository::new(pool.clone());
 
     let file = NewProcessedFile {
-        file_name: "CRNH0203-2024-CA_Test.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
-        state: "CA".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 100,
         file_hash: Some("abc123".to_string()),
         observations_inserted: 95,
         observations_updated: 5,
         parse_failures: 2,
         processing_status: "completed".to_string(),
+        ..make_processed_file("CRNH0203-2024-CA_Test.txt", 2024)
     };
 
     let _file_id = repo
@@ -514,18 +422,11 @@ async fn test_get_processed_files_for_year(pool: PgPool) {
     for year in [2022, 2023, 2024] {
         for i in 1..=3 {
             let file = NewProcessedFile {
-                file_name: format!("file_{}_y{}.txt", i, year),
-                file_url: format!("https://example.com/file_{}.txt", i),
-                year,
-                state: "CA".to_string(),
                 station_name: "Test".to_string(),
-                last_modified: None,
                 rows_processed: 10,
-                file_hash: None,
                 observations_inserted: 10,
-                observations_updated: 0,
-                parse_failures: 0,
                 processing_status: "completed".to_string(),
+                ..make_processed_file(&format!("file_{}_y{}.txt", i, year), year)
             };
             repo.mark_file_processed(file)
                 .await
@@ -552,3 +453,2414 @@ async fn test_get_processed_files_for_year(pool: PgPool) {
 
     assert_eq!(files_2024.len(), 3);
 }
+
+/// Test connecting with `replica_hosts` and `target_session_attrs = primary`
+/// set, failing over from an unreachable first host
+#[tokio::test]
+async fn test_connect_with_replica_hosts_and_target_session_attrs() {
+    use sqlx::postgres::PgPoolOptions;
+    use uscrn_ingest::config::DatabaseConfig;
+
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let parsed = url::Url::parse(&database_url).expect("Invalid DATABASE_URL");
+
+    let reachable_host = parsed
+        .host_str()
+        .expect("DATABASE_URL missing host")
+        .to_string();
+    let config = DatabaseConfig {
+        // Reserved TLD, so this host never resolves
+        host: "primary.invalid".to_string(),
+        port: parsed.port().unwrap_or(5432),
+        name: parsed.path().trim_start_matches('/').to_string(),
+        user: parsed.username().to_string(),
+        password: parsed.password().unwrap_or_default().to_string(),
+        max_connections: 1,
+        replica_hosts: vec![reachable_host],
+        target_session_attrs: Some("primary".to_string()),
+        schema: None,
+        conflict_strategy: ConflictStrategy::default(),
+        stream_chunk_size: 500,
+    };
+
+    let pool = config
+        .connect_with(PgPoolOptions::new().max_connections(config.max_connections))
+        .await
+        .expect("Failed to connect with replica_hosts");
+
+    let in_recovery = sqlx::query_scalar::<_, bool>("SELECT pg_is_in_recovery()")
+        .fetch_one(&pool)
+        .await
+        .expect("Query failed");
+
+    assert!(!in_recovery, "primary session should not be in recovery");
+
+    // The only reachable host is a primary, so no standby matches
+    let standby_only = DatabaseConfig {
+        target_session_attrs: Some("standby".to_string()),
+        ..config
+    };
+    match standby_only
+        .connect_with(PgPoolOptions::new().max_connections(1))
+        .await
+    {
+        Err(AppError::Config(message)) => assert!(message.contains("standby")),
+        other => panic!("Expected a config error, got {:?}", other.map(|_| ())),
+    }
+}
+
+/// Test lowest quality files are returned first, unscored files last
+#[sqlx::test]
+async fn test_get_lowest_quality_files(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    for (name, score) in [
+        ("good.txt", Some(0.98)),
+        ("unscored.txt", None),
+        ("poor.txt", Some(0.42)),
+        ("fair.txt", Some(0.75)),
+    ] {
+        let file = NewProcessedFile {
+            station_name: "Test".to_string(),
+            rows_processed: 10,
+            observations_inserted: 10,
+            processing_status: "completed".to_string(),
+            data_quality_score: score,
+            ..make_processed_file(name, 2024)
+        };
+        repo.mark_file_processed(file)
+            .await
+            .expect("File insert failed");
+    }
+
+    let files = repo
+        .get_lowest_quality_files(10)
+        .await
+        .expect("Query failed");
+
+    let names: Vec<&str> = files.iter().map(|f| f.file_name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["poor.txt", "fair.txt", "good.txt", "unscored.txt"]
+    );
+    assert_eq!(files[0].data_quality_score, Some(0.42));
+
+    let limited = repo
+        .get_lowest_quality_files(1)
+        .await
+        .expect("Query failed");
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].file_name, "poor.txt");
+}
+
+/// Test foreign key integrity check detects orphans and unused stations
+#[sqlx::test]
+async fn test_check_foreign_key_integrity(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    let report = repo
+        .check_foreign_key_integrity()
+        .await
+        .expect("Integrity check failed");
+    assert!(report.is_clean());
+
+    // Station with no observations
+    repo.upsert_station(NewStation {
+        name: Some("Unused".to_string()),
+        ..make_station(1001)
+    })
+    .await
+    .expect("Station insert failed");
+
+    // Completed file with rows but no observations
+    repo.mark_file_processed(NewProcessedFile {
+        station_name: "Test".to_string(),
+        rows_processed: 10,
+        observations_inserted: 10,
+        processing_status: "completed".to_string(),
+        ..make_processed_file("empty.txt", 2024)
+    })
+    .await
+    .expect("File insert failed");
+
+    // Observation for a missing station (simulates a dropped constraint after migration)
+    sqlx::query("ALTER TABLE observations DROP CONSTRAINT observations_wbanno_fkey")
+        .execute(&pool)
+        .await
+        .expect("Drop constraint failed");
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime) VALUES (9999, NOW(), NOW())",
+    )
+    .execute(&pool)
+    .await
+    .expect("Orphan insert failed");
+
+    let report = repo
+        .check_foreign_key_integrity()
+        .await
+        .expect("Integrity check failed");
+
+    assert_eq!(
+        report,
+        IntegrityReport {
+            orphaned_observations: 1,
+            orphaned_processed_files: 1,
+            stations_without_observations: 1,
+        }
+    );
+    assert!(!report.is_clean());
+}
+
+/// Test migration version check against the embedded migrations
+#[sqlx::test]
+async fn test_check_migration_version(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    let applied = repo
+        .get_applied_migration_version()
+        .await
+        .expect("Version query failed");
+    assert_eq!(applied, Repository::latest_local_migration_version());
+    repo.check_migration_version()
+        .await
+        .expect("Up-to-date schema should pass");
+
+    // Simulate a database migrated by a newer binary
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+         VALUES (9999, 'future', TRUE, '\\x00', 0)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Insert failed");
+
+    let result = repo.check_migration_version().await;
+    assert!(matches!(result, Err(AppError::Database(_))));
+}
+
+/// Test provenance backfill matches observations to files by station and year
+#[sqlx::test]
+async fn test_backfill_source_file_ids(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(NewStation {
+        name: Some("Bodega_6_WSW".to_string()),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    let file_id = repo
+        .mark_file_processed(NewProcessedFile {
+            station_name: "Bodega_6_WSW".to_string(),
+            rows_processed: 2,
+            observations_inserted: 2,
+            processing_status: "completed".to_string(),
+            ..make_processed_file("CRNH0203-2024-CA_Bodega_6_WSW.txt", 2024)
+        })
+        .await
+        .expect("File insert failed");
+
+    // Files cover a UTC year, hour-ending: the 2024 file ends with the hour
+    // ending 2025-01-01 00:00 and the hour ending 2024-01-01 00:00 belongs to
+    // 2023. Another station with an unmatched name has one linked row.
+    repo.upsert_station(make_station(53105))
+        .await
+        .expect("Station insert failed");
+    let other_file_id = repo
+        .mark_file_processed(NewProcessedFile {
+            station_name: "Renamed".to_string(),
+            ..make_processed_file("CRNH0203-2024-CA_Renamed.txt", 2024)
+        })
+        .await
+        .expect("File insert failed");
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, source_file_id) VALUES \
+         (53104, '2024-03-01 12:00:00+00', '2024-03-01 04:00:00+00', NULL), \
+         (53104, '2025-01-01 00:00:00+00', '2024-12-31 16:00:00+00', NULL), \
+         (53104, '2024-01-01 00:00:00+00', '2023-12-31 16:00:00+00', NULL), \
+         (53104, '2023-06-01 12:00:00+00', '2023-06-01 04:00:00+00', NULL), \
+         (53105, '2024-05-01 12:00:00+00', '2024-05-01 04:00:00+00', $1), \
+         (53105, '2024-05-01 13:00:00+00', '2024-05-01 05:00:00+00', NULL)",
+    )
+    .bind(other_file_id)
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let would_update = repo
+        .backfill_source_file_ids(true)
+        .await
+        .expect("Dry run failed");
+    assert_eq!(would_update, 3);
+
+    let null_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM observations WHERE source_file_id IS NULL",
+    )
+    .fetch_one(&pool)
+    .await
+    .expect("Count failed");
+    assert_eq!(null_count, 5, "dry run must not modify rows");
+
+    let updated = repo
+        .backfill_source_file_ids(false)
+        .await
+        .expect("Backfill failed");
+    assert_eq!(updated, 3);
+
+    let linked =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations WHERE source_file_id = $1")
+            .bind(file_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Count failed");
+    assert_eq!(linked, 2);
+
+    let linked_other =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations WHERE source_file_id = $1")
+            .bind(other_file_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Count failed");
+    assert_eq!(linked_other, 2);
+
+    // Second run finds nothing left to update
+    assert_eq!(repo.backfill_source_file_ids(false).await.unwrap(), 0);
+}
+
+/// Test quality flag filtering excludes observations with any flag above the limit
+#[sqlx::test]
+async fn test_get_observations_by_quality_flag(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    for wbanno in [53104, 53105] {
+        repo.upsert_station(NewStation {
+            name: Some("Test".to_string()),
+            ..make_station(wbanno)
+        })
+        .await
+        .expect("Station insert failed");
+    }
+
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, solarad_flag, rh_hr_avg_flag) VALUES \
+         (53104, '2024-03-01 10:00:00+00', '2024-03-01 02:00:00+00', 0, 0), \
+         (53104, '2024-03-01 11:00:00+00', '2024-03-01 03:00:00+00', 0, 3), \
+         (53104, '2024-03-01 12:00:00+00', '2024-03-01 04:00:00+00', NULL, NULL), \
+         (53105, '2024-03-01 10:00:00+00', '2024-03-01 02:00:00+00', 1, 0), \
+         (53104, '2024-04-01 10:00:00+00', '2024-04-01 02:00:00+00', 0, 0)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let start = "2024-03-01T00:00:00Z".parse().unwrap();
+    let end = "2024-03-02T00:00:00Z".parse().unwrap();
+
+    let good = repo
+        .get_observations_by_quality_flag(None, 0, start, end)
+        .await
+        .expect("Query failed");
+    assert_eq!(good.len(), 2);
+    assert!(good.iter().all(|o| o.wbanno == Wbanno(53104)));
+
+    let lenient = repo
+        .get_observations_by_quality_flag(None, 1, start, end)
+        .await
+        .expect("Query failed");
+    assert_eq!(lenient.len(), 3);
+
+    let one_station = repo
+        .get_observations_by_quality_flag(Some(Wbanno(53105)), 3, start, end)
+        .await
+        .expect("Query failed");
+    assert_eq!(one_station.len(), 1);
+}
+
+/// Test file hash collision detection against the stored hash
+#[sqlx::test]
+async fn test_check_file_hash_collision(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    // Unknown file has nothing to collide with
+    let result = repo
+        .check_file_hash_collision("CRNH0203-2023-CA_Test.txt", "abc123")
+        .await
+        .expect("Check failed");
+    assert_eq!(result, None);
+
+    repo.mark_file_processed(NewProcessedFile {
+        station_name: "Test".to_string(),
+        rows_processed: 10,
+        file_hash: Some("abc123".to_string()),
+        observations_inserted: 10,
+        processing_status: "completed".to_string(),
+        ..make_processed_file("CRNH0203-2023-CA_Test.txt", 2023)
+    })
+    .await
+    .expect("File insert failed");
+
+    let matching = repo
+        .check_file_hash_collision("CRNH0203-2023-CA_Test.txt", "abc123")
+        .await
+        .expect("Check failed");
+    assert_eq!(matching, None);
+
+    let collision = repo
+        .check_file_hash_collision("CRNH0203-2023-CA_Test.txt", "def456")
+        .await
+        .expect("Check failed");
+    assert_eq!(collision, Some("abc123".to_string()));
+}
+
+/// Test nearest station search filters by radius and sorts by distance
+#[sqlx::test]
+async fn test_find_nearest_stations(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    let station = |wbanno: i32, state: &str, lat: Option<f64>, lon: Option<f64>| NewStation {
+        name: Some(format!("Station {}", wbanno)),
+        state: state.to_string(),
+        latitude: lat,
+        longitude: lon,
+        ..make_station(wbanno)
+    };
+
+    repo.batch_upsert_stations(&[
+        // Asheville 8 SSW, ~10 km from the search point
+        station(53877, "NC", Some(35.49), Some(-82.61)),
+        // Asheville 13 S, ~17 km away
+        station(53878, "NC", Some(35.42), Some(-82.56)),
+        // Bodega, CA - far outside the radius
+        station(93245, "CA", Some(38.32), Some(-123.07)),
+        // No coordinates recorded
+        station(99999, "NC", None, None),
+    ])
+    .await
+    .expect("Batch upsert failed");
+
+    let nearby = repo
+        .find_nearest_stations(35.57, -82.55, 50.0, 10)
+        .await
+        .expect("Nearest station query failed");
+
+    let wbannos: Vec<Wbanno> = nearby.iter().map(|(s, _)| s.wbanno).collect();
+    assert_eq!(wbannos, vec![Wbanno(53877), Wbanno(53878)]);
+    assert!(nearby[0].1 < nearby[1].1);
+    assert!(nearby[1].1 < 50.0);
+
+    // Distance matches the model's Haversine calculation
+    let expected = nearby[0].0.distance_km(35.57, -82.55).unwrap();
+    assert!((nearby[0].1 - expected).abs() < f64::EPSILON);
+
+    let limited = repo
+        .find_nearest_stations(35.57, -82.55, 5000.0, 1)
+        .await
+        .expect("Nearest station query failed");
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].0.wbanno, Wbanno(53877));
+}
+
+/// Test local file records missing from the remote listing are reported
+#[sqlx::test]
+async fn test_get_files_not_in_remote(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for (name, year) in [
+        ("kept.txt", 2023),
+        ("renamed.txt", 2023),
+        ("removed.txt", 2023),
+        ("other_year.txt", 2022),
+    ] {
+        let file = NewProcessedFile {
+            station_name: "Test".to_string(),
+            rows_processed: 10,
+            observations_inserted: 10,
+            processing_status: "completed".to_string(),
+            ..make_processed_file(name, year)
+        };
+        repo.mark_file_processed(file)
+            .await
+            .expect("File insert failed");
+    }
+
+    let remote = vec!["kept.txt".to_string(), "renamed_v2.txt".to_string()];
+    let stale = repo
+        .get_files_not_in_remote(2023, &remote)
+        .await
+        .expect("Query failed");
+
+    let names: Vec<&str> = stale.iter().map(|f| f.file_name.as_str()).collect();
+    assert_eq!(names, vec!["removed.txt", "renamed.txt"]);
+
+    // An empty listing reports every record for the year
+    let all = repo
+        .get_files_not_in_remote(2023, &[])
+        .await
+        .expect("Query failed");
+    assert_eq!(all.len(), 3);
+}
+
+/// Test soil readings can be looked up by depth
+#[sqlx::test]
+async fn test_observation_soil_values_by_depth(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, \
+         soil_moisture_5, soil_moisture_10, soil_moisture_20, soil_moisture_50, soil_moisture_100, \
+         soil_temp_5, soil_temp_10, soil_temp_20, soil_temp_50) VALUES \
+         (53104, '2024-03-01 10:00:00+00', '2024-03-01 02:00:00+00', \
+          0.05, 0.10, 0.20, 0.50, 1.00, 5.0, 10.0, 20.0, 50.0)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let obs = sqlx::query_as::<_, Observation>("SELECT * FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Observation query failed");
+
+    for depth in SOIL_DEPTHS_CM {
+        assert_eq!(
+            obs.soil_moisture_at_depth_cm(depth).unwrap(),
+            Some(depth as f32 / 100.0)
+        );
+    }
+    assert_eq!(obs.soil_temp_at_depth_cm(50).unwrap(), Some(50.0));
+    assert_eq!(obs.soil_temp_at_depth_cm(100).unwrap(), None);
+
+    match obs.soil_moisture_at_depth_cm(30) {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("30 cm")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+    assert!(obs.soil_temp_at_depth_cm(0).is_err());
+}
+
+/// Test the latest-observation query is served by the descending index
+#[sqlx::test]
+async fn test_latest_observation_query_uses_index(pool: PgPool) {
+    let mut conn = pool.acquire().await.expect("Failed to acquire connection");
+
+    // With an empty table the planner would otherwise prefer a seq scan
+    sqlx::query("SET enable_seqscan = off")
+        .execute(&mut *conn)
+        .await
+        .expect("Failed to disable seq scans");
+
+    let plan: Vec<String> = sqlx::query_scalar(
+        "EXPLAIN SELECT DISTINCT ON (wbanno) * FROM observations ORDER BY wbanno, utc_datetime DESC",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .expect("EXPLAIN failed");
+    let plan = plan.join("\n");
+
+    assert!(
+        plan.contains("idx_observations_wbanno_utc_desc"),
+        "Unexpected plan:\n{}",
+        plan
+    );
+    assert!(!plan.contains("Sort"), "Unexpected plan:\n{}", plan);
+}
+
+/// Test final file statuses are applied in one bulk update
+#[sqlx::test]
+async fn test_bulk_update_file_statuses(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for name in ["a.txt", "b.txt"] {
+        let file = NewProcessedFile {
+            station_name: "Test".to_string(),
+            rows_processed: 10,
+            ..make_processed_file(name, 2024)
+        };
+        repo.mark_file_processed(file)
+            .await
+            .expect("File insert failed");
+    }
+
+    repo.bulk_update_file_statuses(&[
+        ("a.txt".to_string(), ProcessingStatus::Completed, 8, 2, 1),
+        ("b.txt".to_string(), ProcessingStatus::Failed, 0, 0, 10),
+        (
+            "missing.txt".to_string(),
+            ProcessingStatus::Completed,
+            1,
+            0,
+            0,
+        ),
+    ])
+    .await
+    .expect("Bulk update failed");
+
+    let a = repo
+        .get_processed_file("a.txt")
+        .await
+        .expect("Query failed")
+        .expect("File should exist");
+    assert_eq!(a.processing_status.as_deref(), Some("completed"));
+    assert_eq!(a.observations_inserted, Some(8));
+    assert_eq!(a.observations_updated, Some(2));
+    assert_eq!(a.parse_failures, Some(1));
+
+    let b = repo
+        .get_processed_file("b.txt")
+        .await
+        .expect("Query failed")
+        .expect("File should exist");
+    assert_eq!(b.processing_status.as_deref(), Some("failed"));
+
+    // Unknown files are not created
+    assert!(repo
+        .get_processed_file("missing.txt")
+        .await
+        .expect("Query failed")
+        .is_none());
+
+    repo.bulk_update_file_statuses(&[])
+        .await
+        .expect("Empty update should succeed");
+}
+
+/// Test observations record the ingestion run that last wrote them
+#[sqlx::test]
+async fn test_get_observations_from_run(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    let file_id = repo
+        .mark_file_processed(NewProcessedFile {
+            state: "NC".to_string(),
+            station_name: "Test".to_string(),
+            rows_processed: 2,
+            ..make_processed_file("run.txt", 2024)
+        })
+        .await
+        .expect("File insert failed");
+
+    let (observations, _) = Parser::parse_file(
+        include_str!("fixtures/uscrn_samples/crx_v3_38_fields.txt"),
+        FieldSeparator::Whitespace,
+    )
+    .expect("Parse failed");
+
+    let first = repo.start_ingestion_run().await.expect("Run start failed");
+    assert!(first.completed_at.is_none());
+    repo.insert_observations(&observations, file_id, Some(first.id))
+        .await
+        .expect("Observation insert failed");
+    assert_eq!(
+        repo.get_observations_from_run(first.id)
+            .await
+            .expect("Query failed")
+            .len(),
+        2
+    );
+
+    // Re-ingesting the same rows moves them to the newer run
+    let second = repo.start_ingestion_run().await.expect("Run start failed");
+    repo.insert_observations(&observations[..1], file_id, Some(second.id))
+        .await
+        .expect("Observation upsert failed");
+    repo.complete_ingestion_run(second.id)
+        .await
+        .expect("Run completion failed");
+
+    let from_second = repo
+        .get_observations_from_run(second.id)
+        .await
+        .expect("Query failed");
+    assert_eq!(from_second.len(), 1);
+    assert_eq!(from_second[0].ingestion_run_id, Some(second.id));
+    assert_eq!(
+        repo.get_observations_from_run(first.id)
+            .await
+            .expect("Query failed")
+            .len(),
+        1
+    );
+}
+
+/// Test per-station aggregate statistics
+#[sqlx::test]
+async fn test_get_observation_stats(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    let empty = repo
+        .get_observation_stats(Wbanno(53104))
+        .await
+        .expect("Stats query failed");
+    assert_eq!(empty.total_count, 0);
+    assert_eq!(empty.complete_soil_pct, 0.0);
+    assert_eq!(empty.avg_t_hr_avg, None);
+    assert_eq!(empty.observation_span_days, None);
+
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, \
+         t_hr_avg, t_min, t_max, p_calc, soil_moisture_5) VALUES \
+         (53104, '2024-03-01 00:00:00+00', '2024-02-29 19:00:00+00', 10.0, 8.0, 12.0, 1.5, 0.2), \
+         (53104, '2024-03-04 12:00:00+00', '2024-03-04 07:00:00+00', 20.0, 15.0, 25.0, 2.5, NULL)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let stats = repo
+        .get_observation_stats(Wbanno(53104))
+        .await
+        .expect("Stats query failed");
+    assert_eq!(stats.wbanno, Wbanno(53104));
+    assert_eq!(stats.total_count, 2);
+    assert_eq!(stats.complete_soil_pct, 0.5);
+    assert_eq!(stats.avg_t_hr_avg, Some(15.0));
+    assert_eq!(stats.min_t_min, Some(8.0));
+    assert_eq!(stats.max_t_max, Some(25.0));
+    assert_eq!(stats.total_precip, Some(4.0));
+    assert_eq!(stats.observation_span_days, Some(3));
+}
+
+/// Test sharp soil moisture changes are found between consecutive observations
+#[sqlx::test]
+async fn test_detect_soil_moisture_anomalies(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, soil_moisture_10) VALUES \
+         (53104, '2024-05-01 00:00:00+00', '2024-04-30 19:00:00+00', 0.20), \
+         (53104, '2024-05-01 01:00:00+00', '2024-04-30 20:00:00+00', 0.21), \
+         (53104, '2024-05-01 02:00:00+00', '2024-04-30 21:00:00+00', 0.35), \
+         (53104, '2024-05-01 03:00:00+00', '2024-04-30 22:00:00+00', NULL), \
+         (53104, '2024-05-01 04:00:00+00', '2024-04-30 23:00:00+00', 0.30), \
+         (53104, '2024-05-01 05:00:00+00', '2024-05-01 00:00:00+00', 0.24)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let events = repo
+        .detect_soil_moisture_anomalies(Wbanno(53104), 10, 0.05)
+        .await
+        .expect("Anomaly query failed");
+
+    // The rise into 02:00 is largest; gaps around the NULL reading are not compared
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].previous, 0.21);
+    assert_eq!(events[0].current, 0.35);
+    assert!((events[0].delta - 0.14).abs() < 1e-6);
+    assert_eq!(events[1].previous, 0.30);
+    assert_eq!(events[1].current, 0.24);
+
+    match repo
+        .detect_soil_moisture_anomalies(Wbanno(53104), 30, 0.05)
+        .await
+    {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("30 cm")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+}
+
+/// Test a station's current firmware version is recorded and kept on upsert
+#[sqlx::test]
+async fn test_update_station_crx_version(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    let station = NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    };
+    repo.upsert_station(station.clone())
+        .await
+        .expect("Station insert failed");
+
+    let now = Utc::now();
+    repo.update_station_crx_version(Wbanno(53104), "3.2", now)
+        .await
+        .expect("Version update failed");
+
+    // A later upsert without a version leaves it untouched
+    repo.upsert_station(station)
+        .await
+        .expect("Station upsert failed");
+
+    // A version from before the station's newest observation is ignored
+    let file_id = repo
+        .mark_file_processed(make_processed_file("crx.txt", 2024))
+        .await
+        .expect("File insert failed");
+    repo.insert_observations(&[make_observation(53104, now)], file_id, None)
+        .await
+        .expect("Observation insert failed");
+    repo.update_station_crx_version(Wbanno(53104), "2.1", now - chrono::Duration::days(365))
+        .await
+        .expect("Version update failed");
+
+    let version = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT current_crx_version FROM stations WHERE wbanno = $1",
+    )
+    .bind(53104)
+    .fetch_one(&pool)
+    .await
+    .expect("Query failed");
+    assert_eq!(version.as_deref(), Some("3.2"));
+}
+
+/// Test old observations move to the archive table and can be read back
+#[sqlx::test]
+async fn test_archive_observations(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, t_hr_avg) VALUES \
+         (53104, '2021-06-01 00:00:00+00', '2021-05-31 19:00:00+00', 21.0), \
+         (53104, '2022-12-31 23:00:00+00', '2022-12-31 18:00:00+00', 1.0), \
+         (53104, '2023-01-01 00:00:00+00', '2022-12-31 19:00:00+00', 2.0)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+
+    let archived = repo
+        .archive_observations(2023)
+        .await
+        .expect("Archive failed");
+    assert_eq!(archived, 2);
+
+    let remaining = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count query failed");
+    assert_eq!(remaining, 1);
+
+    let start = "2022-01-01T00:00:00Z".parse().unwrap();
+    let end = "2023-01-01T00:00:00Z".parse().unwrap();
+    let from_archive = repo
+        .get_from_archive(Wbanno(53104), start, end)
+        .await
+        .expect("Archive query failed");
+    assert_eq!(from_archive.len(), 1);
+    assert_eq!(from_archive[0].t_hr_avg, Some(1.0));
+
+    // Nothing left to move on a second pass
+    assert_eq!(repo.archive_observations(2023).await.unwrap(), 0);
+
+    // A re-ingested row replaces its older archived copy
+    sqlx::query(
+        "INSERT INTO observations (wbanno, utc_datetime, lst_datetime, t_hr_avg) VALUES \
+         (53104, '2022-12-31 23:00:00+00', '2022-12-31 18:00:00+00', 1.5)",
+    )
+    .execute(&pool)
+    .await
+    .expect("Observation insert failed");
+    assert_eq!(repo.archive_observations(2023).await.unwrap(), 1);
+    let from_archive = repo
+        .get_from_archive(Wbanno(53104), start, end)
+        .await
+        .expect("Archive query failed");
+    assert_eq!(from_archive.len(), 1);
+    assert_eq!(from_archive[0].t_hr_avg, Some(1.5));
+}
+
+/// Test processed file records are fetched for many names in one call
+#[sqlx::test]
+async fn test_batch_get_processed_files(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for (name, hash) in [("a.txt", "hash-a"), ("b.txt", "hash-b")] {
+        repo.mark_file_processed(NewProcessedFile {
+            station_name: "Test".to_string(),
+            rows_processed: 10,
+            file_hash: Some(hash.to_string()),
+            observations_inserted: 10,
+            processing_status: ProcessingStatus::Completed.to_string(),
+            ..make_processed_file(name, 2023)
+        })
+        .await
+        .expect("File insert failed");
+    }
+
+    let files = repo
+        .batch_get_processed_files(&["a.txt".to_string(), "missing.txt".to_string()])
+        .await
+        .expect("Query failed");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files["a.txt"].file_hash.as_deref(), Some("hash-a"));
+    assert!(!files.contains_key("missing.txt"));
+
+    assert!(repo
+        .batch_get_processed_files(&[])
+        .await
+        .expect("Query failed")
+        .is_empty());
+}
+
+/// Test prepared observations carry the source file id they are stored with
+#[sqlx::test]
+async fn test_prepare_observations(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    let file_id = repo
+        .mark_file_processed(NewProcessedFile {
+            state: "NC".to_string(),
+            station_name: "Test".to_string(),
+            rows_processed: 2,
+            ..make_processed_file("prepared.txt", 2024)
+        })
+        .await
+        .expect("File insert failed");
+
+    let (observations, _) = Parser::parse_file(
+        include_str!("fixtures/uscrn_samples/crx_v3_38_fields.txt"),
+        FieldSeparator::Whitespace,
+    )
+    .expect("Parse failed");
+    assert!(observations.iter().all(|o| o.source_file_id.is_none()));
+
+    let observations = Repository::prepare_observations(observations, file_id);
+    assert!(observations
+        .iter()
+        .all(|o| o.source_file_id == Some(file_id)));
+
+    repo.upsert_station(NewStation {
+        name: Some("Test".to_string()),
+        state: "NC".to_string(),
+        ..make_station(53104)
+    })
+    .await
+    .expect("Station insert failed");
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let stored =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations WHERE source_file_id = $1")
+            .bind(file_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Count query failed");
+    assert_eq!(stored, observations.len() as i64);
+}
+
+/// Test health_report counts and its stable JSON field names
+#[sqlx::test]
+async fn test_health_report(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("stuck.txt", 2024))
+        .await
+        .expect("File insert failed");
+    repo.mark_file_processed(NewProcessedFile {
+        processing_status: ProcessingStatus::Failed.to_string(),
+        ..make_processed_file("broken.txt", 2024)
+    })
+    .await
+    .expect("File insert failed");
+
+    let base_time = Utc::now() - chrono::Duration::hours(3);
+    repo.insert_observations(&make_observations(53104, base_time, 3), file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let report = repo.health_report().await.expect("Health report failed");
+
+    assert!(report.db_connected);
+    assert_eq!(
+        report.schema_version,
+        Repository::latest_local_migration_version()
+    );
+    assert_eq!(report.total_stations, 1);
+    assert_eq!(report.total_observations, 3);
+    assert_eq!(
+        report.latest_observation_at.map(|t| t.timestamp()),
+        Some((base_time + chrono::Duration::hours(2)).timestamp())
+    );
+    assert_eq!(report.oldest_unprocessed_file.as_deref(), Some("stuck.txt"));
+    assert_eq!(report.files_in_error_state, 1);
+
+    let json = serde_json::to_value(&report).expect("Serialization failed");
+    let keys: Vec<&str> = json
+        .as_object()
+        .expect("Report should serialize to an object")
+        .keys()
+        .map(String::as_str)
+        .collect();
+    for key in [
+        "db_connected",
+        "schema_version",
+        "total_stations",
+        "total_observations",
+        "latest_observation_at",
+        "oldest_unprocessed_file",
+        "files_in_error_state",
+        "pool_idle",
+        "pool_active",
+    ] {
+        assert!(keys.contains(&key), "missing {}", key);
+    }
+}
+
+/// Test get_failed_processed_files lists failed files but not skipped ones
+#[sqlx::test]
+async fn test_get_failed_processed_files(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for (name, status) in [
+        ("failed.txt", ProcessingStatus::Failed),
+        ("skipped.txt", ProcessingStatus::Skipped),
+        ("completed.txt", ProcessingStatus::Completed),
+    ] {
+        repo.mark_file_processed(NewProcessedFile {
+            processing_status: status.to_string(),
+            ..make_processed_file(name, 2024)
+        })
+        .await
+        .expect("File insert failed");
+    }
+
+    let failed = repo
+        .get_failed_processed_files()
+        .await
+        .expect("Query failed");
+    let names: Vec<&str> = failed.iter().map(|f| f.file_name.as_str()).collect();
+    assert_eq!(names, vec!["failed.txt"]);
+}
+
+/// Test get_failed_files returns every non-completed file, filtered by since,
+/// and retry_failed_files returns only the failed names
+#[sqlx::test]
+async fn test_get_failed_files(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    for (name, status) in [
+        ("failed.txt", ProcessingStatus::Failed),
+        ("processing.txt", ProcessingStatus::Processing),
+        ("skipped.txt", ProcessingStatus::Skipped),
+        ("completed.txt", ProcessingStatus::Completed),
+    ] {
+        repo.mark_file_processed(NewProcessedFile {
+            processing_status: status.to_string(),
+            ..make_processed_file(name, 2024)
+        })
+        .await
+        .expect("File insert failed");
+    }
+    sqlx::query(
+        "UPDATE processed_files SET processed_at = NOW() - INTERVAL '2 days' \
+         WHERE file_name = 'processing.txt'",
+    )
+    .execute(&pool)
+    .await
+    .expect("Update failed");
+
+    let all = repo.get_failed_files(None).await.expect("Query failed");
+    let mut names: Vec<&str> = all.iter().map(|f| f.file_name.as_str()).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["failed.txt", "processing.txt", "skipped.txt"]);
+
+    let recent = repo
+        .get_failed_files(Some(Utc::now() - chrono::Duration::days(1)))
+        .await
+        .expect("Query failed");
+    let mut names: Vec<&str> = recent.iter().map(|f| f.file_name.as_str()).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["failed.txt", "skipped.txt"]);
+
+    let retry = repo.retry_failed_files().await.expect("Query failed");
+    assert_eq!(retry, vec!["failed.txt".to_string()]);
+}
+
+/// Test refresh_daily_aggregates rolls observations up into station_daily_summary
+#[sqlx::test]
+async fn test_refresh_daily_aggregates(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("daily.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    // 30 hours starting at midnight: 24 on the first day, 6 on the second
+    let midnight = chrono::DateTime::parse_from_rfc3339("2024-06-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let observations: Vec<NewObservation> = make_observations(53104, midnight, 30)
+        .into_iter()
+        .enumerate()
+        .map(|(i, obs)| NewObservation {
+            t_hr_avg: Some(if i < 24 { 10.0 } else { 20.0 }),
+            p_calc: Some(0.5),
+            ..obs
+        })
+        .collect();
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    repo.refresh_daily_aggregates()
+        .await
+        .expect("Refresh failed");
+
+    let rows = sqlx::query_as::<_, (chrono::NaiveDate, Option<f32>, Option<f32>)>(
+        "SELECT obs_date, avg_temp, total_precip FROM station_daily_summary \
+         WHERE wbanno = $1 ORDER BY obs_date",
+    )
+    .bind(53104)
+    .fetch_all(&pool)
+    .await
+    .expect("Summary query failed");
+
+    let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            (day(1), Some(10.0), Some(12.0)),
+            (day(2), Some(20.0), Some(3.0)),
+        ]
+    );
+}
+
+/// Test a configured schema gets its own tables, leaving public untouched
+#[sqlx::test]
+async fn test_run_migrations_in_schema(pool: PgPool) {
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use uscrn_ingest::config::DatabaseConfig;
+
+    // The connection string carries the schema as the session search_path
+    let config: DatabaseConfig = serde_yaml::from_str(
+        "host: localhost\nname: uscrn\nuser: test\npassword: test\nschema: tenant_a\n",
+    )
+    .expect("Invalid config");
+    let parsed: PgConnectOptions = config
+        .connection_string()
+        .parse()
+        .expect("Invalid connection string");
+    assert_eq!(parsed.get_options(), Some("-c search_path=tenant_a"));
+
+    let tenant_pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_with(
+            (*pool.connect_options())
+                .clone()
+                .options([("search_path", "tenant_a")]),
+        )
+        .await
+        .expect("Connect failed");
+    let tenant_repo = Repository::new(tenant_pool).with_schema(config.schema.clone());
+
+    tenant_repo
+        .run_migrations()
+        .await
+        .expect("Migrations failed");
+    tenant_repo
+        .upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+
+    let count = |table: &'static str| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&pool)
+                .await
+                .expect("Count query failed")
+        }
+    };
+    assert_eq!(count("tenant_a.stations").await, 1);
+    assert_eq!(count("public.stations").await, 0);
+}
+
+/// Test listen_observations streams newly inserted rows
+#[sqlx::test]
+async fn test_listen_observations(pool: PgPool) {
+    use futures::StreamExt;
+
+    let repo = Repository::new(pool);
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("live.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let stream = repo.listen_observations().await.expect("Listen failed");
+    let mut stream = Box::pin(stream);
+
+    let timestamp = chrono::DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    repo.insert_observations(&[make_observation(53104, timestamp)], file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let received = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+        .await
+        .expect("No notification received")
+        .expect("Stream ended")
+        .expect("Invalid notification");
+
+    assert_eq!(received.wbanno, Wbanno(53104));
+    assert_eq!(received.utc_datetime, timestamp);
+    assert_eq!(received.t_hr_avg, Some(20.0));
+    assert_eq!(received.source_file_id, Some(file_id));
+}
+
+/// Test recorded ingestion errors keep each file's latest error, newest first,
+/// with retry counts
+#[sqlx::test]
+async fn test_get_recent_ingestion_errors(pool: PgPool) {
+    let repo = Repository::new(pool);
+    let before = Utc::now() - chrono::Duration::seconds(1);
+
+    repo.record_ingestion_error("a.txt", "download failed")
+        .await
+        .expect("Record failed");
+    repo.record_ingestion_error("b.txt", "parse failed")
+        .await
+        .expect("Record failed");
+    repo.record_ingestion_error("a.txt", "download failed again")
+        .await
+        .expect("Record failed");
+
+    let errors = repo
+        .get_recent_ingestion_errors(before, 10)
+        .await
+        .expect("Query failed");
+    let summary: Vec<(&str, i32)> = errors
+        .iter()
+        .map(|e| (e.file_name.as_str(), e.retry_count))
+        .collect();
+    assert_eq!(summary, vec![("a.txt", 1), ("b.txt", 0)]);
+    assert_eq!(errors[0].error_message, "download failed again");
+
+    let limited = repo
+        .get_recent_ingestion_errors(before, 1)
+        .await
+        .expect("Query failed");
+    assert_eq!(limited.len(), 1);
+
+    let none = repo
+        .get_recent_ingestion_errors(Utc::now() + chrono::Duration::hours(1), 10)
+        .await
+        .expect("Query failed");
+    assert!(none.is_empty());
+}
+
+/// Test runs of missing hours are found per station within the requested year
+#[sqlx::test]
+async fn test_find_observation_gaps(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for wbanno in [53104, 53105] {
+        repo.upsert_station(make_station(wbanno))
+            .await
+            .expect("Station insert failed");
+    }
+    let file_id = repo
+        .mark_file_processed(make_processed_file("gaps.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let base_time = "2024-03-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    let mut observations = make_observations(53104, base_time, 2);
+    // 10 missing hours, then 3 missing hours
+    observations.extend(make_observations(
+        53104,
+        base_time + chrono::Duration::hours(12),
+        1,
+    ));
+    observations.extend(make_observations(
+        53104,
+        base_time + chrono::Duration::hours(16),
+        1,
+    ));
+    // Another station with no gaps, and a gap that spans into the next year
+    observations.extend(make_observations(53105, base_time, 5));
+    observations.push(make_observation(
+        53105,
+        "2025-01-02T00:00:00Z".parse().unwrap(),
+    ));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let gaps = repo
+        .find_observation_gaps(2024, 6)
+        .await
+        .expect("Gap query failed");
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].wbanno, Wbanno(53104));
+    assert_eq!(gaps[0].gap_start, base_time + chrono::Duration::hours(1));
+    assert_eq!(gaps[0].gap_end, base_time + chrono::Duration::hours(12));
+    assert_eq!(gaps[0].missing_hours, 10);
+
+    let gaps = repo
+        .find_observation_gaps(2024, 2)
+        .await
+        .expect("Gap query failed");
+    let missing: Vec<i64> = gaps.iter().map(|g| g.missing_hours).collect();
+    assert_eq!(missing, vec![10, 3]);
+}
+
+/// Test each conflict strategy's handling of an already stored observation
+#[sqlx::test]
+async fn test_insert_observations_conflict_strategies(pool: PgPool) {
+    let t_hr_avg = |pool: PgPool| async move {
+        sqlx::query_scalar::<_, Option<f32>>("SELECT t_hr_avg FROM observations")
+            .fetch_all(&pool)
+            .await
+            .expect("t_hr_avg query failed")
+    };
+
+    let setup = Repository::new(pool.clone());
+    setup
+        .upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = setup
+        .mark_file_processed(make_processed_file("conflicts.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let timestamp = Utc::now();
+    let original = make_observation(53104, timestamp);
+    let revised = NewObservation {
+        t_hr_avg: Some(25.0),
+        ..original.clone()
+    };
+
+    let ignore =
+        Repository::new(pool.clone()).with_conflict_strategy(ConflictStrategy::InsertIgnore);
+    ignore
+        .insert_observations(&[original], file_id, None)
+        .await
+        .expect("Initial insert failed");
+    let result = ignore
+        .insert_observations(std::slice::from_ref(&revised), file_id, None)
+        .await
+        .expect("Ignored insert failed");
+    assert_eq!(result.total_rows_affected, 0);
+    assert_eq!(t_hr_avg(pool.clone()).await, vec![Some(20.0)]);
+
+    // The default upsert applies revisions, whatever their logger version
+    let upsert = Repository::new(pool.clone());
+    let other_version = NewObservation {
+        crx_version: Some("2".to_string()),
+        ..revised
+    };
+    let result = upsert
+        .insert_observations(&[other_version], file_id, None)
+        .await
+        .expect("Upsert failed");
+    assert_eq!(result.total_rows_affected, 1);
+    assert_eq!((result.inserted, result.updated), (0, 1));
+    assert_eq!(t_hr_avg(pool.clone()).await, vec![Some(25.0)]);
+}
+
+/// Test CSV export writes only the selected columns within the range
+#[sqlx::test]
+async fn test_export_observations_csv(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("export.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let base_time = "2024-06-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    let mut observations = make_observations(53104, base_time, 3);
+    observations[0].soil_moisture_5 = Some(0.25);
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let columns = ExportColumns {
+        precipitation: false,
+        solar: false,
+        surface_temp: false,
+        relative_humidity: false,
+        soil_temp: false,
+        ..ExportColumns::default()
+    };
+    let mut csv = Vec::new();
+    let written = repo
+        .export_observations_csv(
+            Wbanno(53104),
+            base_time,
+            base_time + chrono::Duration::hours(2),
+            columns,
+            &mut csv,
+        )
+        .await
+        .expect("Export failed");
+    assert_eq!(written, 2);
+
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "utc_datetime,lst_datetime,t_calc,t_hr_avg,t_max,t_min,\
+         soil_moisture_5,soil_moisture_10,soil_moisture_20,soil_moisture_50,soil_moisture_100"
+    );
+    assert_eq!(
+        lines[1],
+        "2024-06-01T00:00:00Z,2024-06-01T00:00:00Z,,20,,,0.25,,,,"
+    );
+    assert_eq!(lines.len(), 3);
+
+    let no_timestamp = ExportColumns {
+        timestamp: false,
+        ..ExportColumns::default()
+    };
+    match repo
+        .export_observations_csv(
+            Wbanno(53104),
+            base_time,
+            base_time,
+            no_timestamp,
+            &mut Vec::new(),
+        )
+        .await
+    {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("timestamp")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+}
+
+/// Test JSONL and CSV export of one station or all stations
+#[sqlx::test]
+async fn test_export_observations_jsonl_and_csv(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    let file_id = repo
+        .mark_file_processed(make_processed_file("export.txt", 2024))
+        .await
+        .expect("File insert failed");
+    let base_time = "2024-06-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    for wbanno in [53104, 53105] {
+        repo.upsert_station(make_station(wbanno))
+            .await
+            .expect("Station insert failed");
+        repo.insert_observations(&make_observations(wbanno, base_time, 3), file_id, None)
+            .await
+            .expect("Observation insert failed");
+    }
+    let end = base_time + chrono::Duration::hours(2);
+
+    let mut jsonl = Vec::new();
+    let written = export::export_observations_jsonl(
+        &repo,
+        Some(Wbanno(53104)),
+        None,
+        base_time,
+        end,
+        &mut jsonl,
+    )
+    .await
+    .expect("Export failed");
+    assert_eq!(written, 2);
+    let jsonl = String::from_utf8(jsonl).unwrap();
+    let lines: Vec<serde_json::Value> = jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line should be a JSON object"))
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["wbanno"], 53104);
+    assert_eq!(lines[1]["utc_datetime"], "2024-06-01T01:00:00Z");
+
+    let mut all = Vec::new();
+    let written = export::export_observations_jsonl(&repo, None, None, base_time, end, &mut all)
+        .await
+        .expect("Export failed");
+    assert_eq!(written, 4);
+
+    let columns = export::parse_columns("temperature").unwrap();
+    let mut csv = Vec::new();
+    let written =
+        export::export_observations_csv(&repo, None, None, base_time, end, columns, &mut csv)
+            .await
+            .expect("Export failed");
+    assert_eq!(written, 4);
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "wbanno,utc_datetime,lst_datetime,t_calc,t_hr_avg,t_max,t_min"
+    );
+    assert!(lines[1].starts_with("53104,2024-06-01T00:00:00Z,2024-06-01T00:00:00Z,"));
+    assert!(lines[4].starts_with("53105,2024-06-01T01:00:00Z,"));
+    assert_eq!(lines.len(), 5);
+
+    // max_flag drops observations with any flag above it
+    sqlx::query(
+        "UPDATE observations SET solarad_flag = 3 \
+         WHERE wbanno = 53104 AND utc_datetime = $1",
+    )
+    .bind(base_time)
+    .execute(&pool)
+    .await
+    .expect("Update failed");
+    // One row per chunk, so the flag filter is applied across pages
+    let chunked = Repository::new(pool).with_stream_chunk_size(1);
+    let mut flagged = Vec::new();
+    let written = export::export_observations_jsonl(
+        &chunked,
+        Some(Wbanno(53104)),
+        Some(0),
+        base_time,
+        end,
+        &mut flagged,
+    )
+    .await
+    .expect("Export failed");
+    assert_eq!(written, 1);
+    let line: serde_json::Value =
+        serde_json::from_str(String::from_utf8(flagged).unwrap().trim_end()).unwrap();
+    assert_eq!(line["utc_datetime"], "2024-06-01T01:00:00Z");
+}
+
+/// Test QC flags can be set, listed and removed, and survive re-ingestion
+#[sqlx::test]
+async fn test_observation_flags(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("flags.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let observation = make_observation(53104, Utc::now());
+    repo.insert_observations(std::slice::from_ref(&observation), file_id, None)
+        .await
+        .expect("Observation insert failed");
+    let observation_id: i64 = sqlx::query_scalar("SELECT id FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Id query failed");
+
+    repo.flag_observation(
+        observation_id,
+        "spike",
+        2,
+        "Jump of 15C in one hour",
+        "qc-bot",
+    )
+    .await
+    .expect("Flag failed");
+    repo.flag_observation(
+        observation_id,
+        "stuck_sensor",
+        1,
+        "Unchanged for 12h",
+        "analyst",
+    )
+    .await
+    .expect("Flag failed");
+
+    // Re-ingesting the observation keeps its flags
+    repo.insert_observations(&[observation], file_id, None)
+        .await
+        .expect("Observation upsert failed");
+
+    let flags = repo
+        .get_observation_flags(observation_id)
+        .await
+        .expect("Query failed");
+    assert_eq!(flags.len(), 2);
+    assert_eq!(flags[0].flag_type, "spike");
+    assert_eq!(flags[0].severity, 2);
+    assert_eq!(flags[0].flagged_by, "qc-bot");
+    assert_eq!(flags[1].flag_type, "stuck_sensor");
+
+    repo.unflag_observation(flags[0].id)
+        .await
+        .expect("Unflag failed");
+    let flags = repo
+        .get_observation_flags(observation_id)
+        .await
+        .expect("Query failed");
+    assert_eq!(flags.len(), 1);
+    assert_eq!(flags[0].flag_type, "stuck_sensor");
+
+    assert!(repo
+        .flag_observation(observation_id + 1, "spike", 1, "No such row", "qc-bot")
+        .await
+        .is_err());
+}
+
+/// Test file sizes for a year are listed largest first, skipping unknown sizes
+#[sqlx::test]
+async fn test_get_file_size_trends(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for (name, year, size) in [
+        ("small.txt", 2024, Some(1_000)),
+        ("unknown.txt", 2024, None),
+        ("large.txt", 2024, Some(900_000)),
+        ("other-year.txt", 2023, Some(5_000_000)),
+    ] {
+        repo.mark_file_processed(NewProcessedFile {
+            file_size_bytes: size,
+            ..make_processed_file(name, year)
+        })
+        .await
+        .expect("File insert failed");
+    }
+
+    let sizes = repo.get_file_size_trends(2024).await.expect("Query failed");
+    assert_eq!(
+        sizes,
+        vec![
+            ("large.txt".to_string(), 900_000),
+            ("small.txt".to_string(), 1_000)
+        ]
+    );
+}
+
+/// Test observations are streamed across chunk boundaries without gaps or repeats
+#[sqlx::test]
+async fn test_stream_observations_chunks(pool: PgPool) {
+    let repo = Repository::new(pool).with_stream_chunk_size(2);
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("stream.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let base_time = "2024-06-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    repo.insert_observations(&make_observations(53104, base_time, 7), file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let start = base_time + chrono::Duration::hours(1);
+    let end = base_time + chrono::Duration::hours(6);
+    let streamed: Vec<Observation> = repo
+        .stream_observations(Wbanno(53104), start, end)
+        .try_collect()
+        .await
+        .expect("Stream failed");
+    let hours: Vec<i64> = streamed
+        .iter()
+        .map(|o| (o.utc_datetime - base_time).num_hours())
+        .collect();
+    assert_eq!(hours, vec![1, 2, 3, 4, 5]);
+
+    let mut csv = Vec::new();
+    let written = repo
+        .export_observations_csv(
+            Wbanno(53104),
+            start,
+            end,
+            ExportColumns::default(),
+            &mut csv,
+        )
+        .await
+        .expect("Export failed");
+    assert_eq!(written, 5);
+    assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 6);
+}
+
+/// Compare CSV export times across chunk sizes for 100,000 rows
+///
+/// Run with `cargo test --test database_integration_test bench_export_chunk_sizes -- --ignored --nocapture`
+#[sqlx::test]
+#[ignore]
+async fn bench_export_chunk_sizes(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("bench.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    const ROWS: usize = 100_000;
+    let base_time = "2010-01-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    repo.insert_observations(&make_observations(53104, base_time, ROWS), file_id, None)
+        .await
+        .expect("Observation insert failed");
+    let end = base_time + chrono::Duration::hours(ROWS as i64);
+
+    for chunk_size in [100, 500, 1000, 5000] {
+        let repo = Repository::new(pool.clone()).with_stream_chunk_size(chunk_size);
+        let started = std::time::Instant::now();
+        let written = repo
+            .export_observations_csv(
+                Wbanno(53104),
+                base_time,
+                end,
+                ExportColumns::default(),
+                &mut std::io::sink(),
+            )
+            .await
+            .expect("Export failed");
+        assert_eq!(written, ROWS);
+        println!(
+            "chunk size {:>5}: {:>6} ms",
+            chunk_size,
+            started.elapsed().as_millis()
+        );
+    }
+}
+
+/// Test years and states with data are listed and counted per combination
+#[sqlx::test]
+async fn test_data_coverage(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+    let expiring = Repository::new(pool.clone()).with_coverage_cache_ttl(std::time::Duration::ZERO);
+
+    assert!(repo.list_years_with_data().await.unwrap().is_empty());
+    assert!(expiring.list_years_with_data().await.unwrap().is_empty());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    repo.upsert_station(NewStation {
+        state: "NC".to_string(),
+        ..make_station(53105)
+    })
+    .await
+    .expect("Station insert failed");
+    // A station without observations is not reported
+    repo.upsert_station(NewStation {
+        state: "TX".to_string(),
+        ..make_station(53106)
+    })
+    .await
+    .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("coverage.txt", 2023))
+        .await
+        .expect("File insert failed");
+
+    let mut observations = make_observations(53104, "2023-12-31T22:00:00Z".parse().unwrap(), 3);
+    observations.extend(make_observations(
+        53105,
+        "2024-03-01T00:00:00Z".parse().unwrap(),
+        2,
+    ));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let fresh = Repository::new(pool);
+    assert_eq!(
+        fresh.list_years_with_data().await.unwrap(),
+        vec![2023, 2024]
+    );
+    assert_eq!(
+        fresh.list_states_with_data().await.unwrap(),
+        vec!["CA", "NC"]
+    );
+    assert_eq!(
+        fresh.count_observations_by_year_and_state().await.unwrap(),
+        vec![
+            (2023, "CA".to_string(), 2),
+            (2024, "CA".to_string(), 1),
+            (2024, "NC".to_string(), 2)
+        ]
+    );
+
+    // The first repository cached the empty result; an expired cache re-queries
+    assert!(repo.list_years_with_data().await.unwrap().is_empty());
+    assert_eq!(
+        expiring.list_years_with_data().await.unwrap(),
+        vec![2023, 2024]
+    );
+}
+
+/// Test a file's rows_processed is compared with its stored observations
+#[sqlx::test]
+async fn test_verify_file_observation_count(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(NewProcessedFile {
+            rows_processed: 3,
+            ..make_processed_file("counted.txt", 2024)
+        })
+        .await
+        .expect("File insert failed");
+    repo.insert_observations(&make_observations(53104, Utc::now(), 3), file_id, None)
+        .await
+        .expect("Observation insert failed");
+
+    let verification = repo
+        .verify_file_observation_count("counted.txt")
+        .await
+        .expect("Verification failed");
+    assert_eq!(verification.expected, 3);
+    assert_eq!(verification.actual, 3);
+    assert!(verification.matches);
+
+    sqlx::query("DELETE FROM observations WHERE utc_datetime = (SELECT MIN(utc_datetime) FROM observations)")
+        .execute(&pool)
+        .await
+        .expect("Delete failed");
+    let verification = repo
+        .verify_file_observation_count("counted.txt")
+        .await
+        .expect("Verification failed");
+    assert_eq!(verification.actual, 2);
+    assert!(!verification.matches);
+
+    assert_eq!(
+        repo.list_processed_file_names().await.unwrap(),
+        vec!["counted.txt"]
+    );
+    match repo.verify_file_observation_count("missing.txt").await {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("missing.txt")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+}
+
+/// Test count mismatches are found for completed hourly02 files only,
+/// counting archived rows and allowing for retention and the reprocess window
+#[sqlx::test]
+async fn test_find_file_count_mismatches(pool: PgPool) {
+    let repo = Repository::new(pool);
+    let current_year = Utc::now().year();
+
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let june_2023 = "2023-06-01T00:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    let files = [
+        ("CRNH0203-2023-NC_Match.txt", 2023, 3, june_2023, 3),
+        (
+            "CRNH0203-2023-NC_Short.txt",
+            2023,
+            5,
+            june_2023 + chrono::Duration::days(1),
+            3,
+        ),
+        ("CRNS0101-05-2023-NC_Subhourly.txt", 2023, 5, june_2023, 0),
+        (
+            "CRNH0203-current-NC_Windowed.txt",
+            current_year,
+            1,
+            Utc::now() - chrono::Duration::hours(4),
+            3,
+        ),
+    ];
+    for (name, year, rows_processed, base, stored) in files {
+        let name = name.replace("current", &current_year.to_string());
+        let file_id = repo
+            .mark_file_processed(NewProcessedFile {
+                rows_processed,
+                processing_status: ProcessingStatus::Completed.to_string(),
+                ..make_processed_file(&name, year)
+            })
+            .await
+            .expect("File insert failed");
+        repo.insert_observations(&make_observations(53104, base, stored), file_id, None)
+            .await
+            .expect("Observation insert failed");
+    }
+    // Archived rows still count towards their file
+    repo.archive_observations(2024)
+        .await
+        .expect("Archive failed");
+
+    let names = |mismatches: Vec<FileCountMismatch>| -> Vec<String> {
+        mismatches.into_iter().map(|m| m.file_name).collect()
+    };
+    let windowed = format!("CRNH0203-{}-NC_Windowed.txt", current_year);
+
+    let mismatches = repo
+        .find_file_count_mismatches(None, false)
+        .await
+        .expect("Query failed");
+    assert_eq!(
+        names(mismatches),
+        vec!["CRNH0203-2023-NC_Short.txt".to_string(), windowed]
+    );
+
+    let mismatches = repo
+        .find_file_count_mismatches(None, true)
+        .await
+        .expect("Query failed");
+    assert_eq!(names(mismatches), vec!["CRNH0203-2023-NC_Short.txt"]);
+
+    // 2023 rows may have been removed by retention
+    let mismatches = repo
+        .find_file_count_mismatches(Some(Utc::now() - chrono::Duration::days(30)), true)
+        .await
+        .expect("Query failed");
+    assert!(mismatches.is_empty());
+}
+
+/// Test station lookups by WBANNO, state and last update time
+#[sqlx::test]
+async fn test_station_lookups(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    repo.batch_upsert_stations(&[
+        NewStation {
+            state: "NC".to_string(),
+            ..make_station(53877)
+        },
+        NewStation {
+            state: "NC".to_string(),
+            ..make_station(53878)
+        },
+        NewStation {
+            state: "CA".to_string(),
+            ..make_station(93245)
+        },
+    ])
+    .await
+    .expect("Upsert failed");
+
+    let station = repo.get_station(Wbanno(53877)).await.expect("Query failed");
+    assert_eq!(station.map(|s| s.state), Some("NC".to_string()));
+    assert!(repo
+        .get_station(Wbanno(11111))
+        .await
+        .expect("Query failed")
+        .is_none());
+
+    let nc: Vec<_> = repo
+        .get_stations_by_state("NC")
+        .await
+        .expect("Query failed")
+        .into_iter()
+        .map(|s| s.wbanno)
+        .collect();
+    assert_eq!(nc, vec![Wbanno(53877), Wbanno(53878)]);
+
+    // A no-op upsert does not count as an update; a real change does
+    let checkpoint = repo
+        .get_station(Wbanno(93245))
+        .await
+        .expect("Query failed")
+        .expect("Station missing")
+        .updated_at;
+    repo.upsert_station(NewStation {
+        state: "CA".to_string(),
+        ..make_station(93245)
+    })
+    .await
+    .expect("Upsert failed");
+    repo.update_station_crx_version(Wbanno(53878), "2.4", Utc::now())
+        .await
+        .expect("Update failed");
+
+    let updated: Vec<_> = repo
+        .get_stations_updated_since(checkpoint)
+        .await
+        .expect("Query failed")
+        .into_iter()
+        .map(|s| s.wbanno)
+        .collect();
+    assert_eq!(updated, vec![Wbanno(53878)]);
+}
+
+/// Test station health statistics over a span with a missing hour
+#[sqlx::test]
+async fn test_compute_station_statistics(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Upsert failed");
+
+    let missing = repo.compute_station_statistics(Wbanno(53104)).await;
+    assert!(matches!(missing, Err(AppError::InvalidData(_))));
+
+    let file_id = repo
+        .mark_file_processed(make_processed_file("health.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    // Hours 0, 1 and 3 of a four-hour span; hour 1 has no soil moisture
+    let base: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let observations: Vec<_> = [0, 1, 3]
+        .into_iter()
+        .map(|hour| NewObservation {
+            soil_moisture_5: (hour != 1).then_some(0.25),
+            ..make_observation(53104, base + chrono::Duration::hours(hour))
+        })
+        .collect();
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let stats = repo
+        .compute_station_statistics(Wbanno(53104))
+        .await
+        .expect("Statistics failed");
+
+    assert_eq!(stats.total_records, 3);
+    assert_eq!(stats.year_range, (2024, 2024));
+    assert!((stats.uptime_pct - 0.75).abs() < 1e-9);
+    assert!((stats.sensors.soil_moisture - 2.0 / 3.0).abs() < 1e-9);
+}
+
+/// Test processed file summary groups by year and state and counts statuses
+#[sqlx::test]
+async fn test_get_processed_files_summary(pool: PgPool) {
+    let repo = Repository::new(pool);
+
+    for (name, year, state, status, rows, failures) in [
+        ("a.txt", 2024, "CA", ProcessingStatus::Completed, 100, 2),
+        ("b.txt", 2024, "CA", ProcessingStatus::Failed, 0, 50),
+        ("c.txt", 2024, "CA", ProcessingStatus::Skipped, 0, 0),
+        ("d.txt", 2024, "TX", ProcessingStatus::Completed, 80, 0),
+        ("e.txt", 2023, "CA", ProcessingStatus::Completed, 90, 1),
+    ] {
+        repo.mark_file_processed(NewProcessedFile {
+            state: state.to_string(),
+            processing_status: status.to_string(),
+            rows_processed: rows,
+            parse_failures: failures,
+            ..make_processed_file(name, year)
+        })
+        .await
+        .expect("File insert failed");
+    }
+
+    let summary = repo
+        .get_processed_files_summary()
+        .await
+        .expect("Summary failed");
+
+    let keys: Vec<_> = summary.iter().map(|s| (s.year, s.state.as_str())).collect();
+    assert_eq!(keys, vec![(2024, "CA"), (2024, "TX"), (2023, "CA")]);
+
+    let ca_2024 = &summary[0];
+    assert_eq!(ca_2024.total_files, 3);
+    assert_eq!(ca_2024.completed_files, 1);
+    assert_eq!(ca_2024.failed_files, 1);
+    assert_eq!(ca_2024.skipped_files, 1);
+    assert_eq!(ca_2024.total_observations, 100);
+    assert_eq!(ca_2024.total_parse_failures, 52);
+}
+
+/// Test 5-minute observations are stored and upserted on re-insert
+#[sqlx::test]
+async fn test_insert_subhourly_observations(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("CRNS0101-05-2024-CA_Test.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let content = "53104 20240115 1405 20240115 0605 2.623 -81.74 36.53 4.3 0.0 45 0 1.2 C 0 81 0 0.215 3.1 1020 0 2.47 0\n\
+                   53104 20240115 1410 20240115 0610 2.623 -81.74 36.53 4.4 0.0 46 0 1.3 C 0 80 0 0.215 3.1 1020 0 2.10 0";
+    let (observations, _) = Parser::parse_subhourly_file(content, FieldSeparator::Whitespace, None)
+        .expect("Parse failed");
+
+    let rows = repo
+        .insert_subhourly_observations(&observations, file_id)
+        .await
+        .expect("Insert failed");
+    assert_eq!(rows, 2);
+
+    repo.insert_subhourly_observations(&observations, file_id)
+        .await
+        .expect("Re-insert failed");
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM subhourly_observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count failed");
+    assert_eq!(count, 2);
+}
+
+/// Test range reads return only the station's observations inside the bounds
+#[sqlx::test]
+async fn test_get_observations_for_station_in_range(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.batch_upsert_stations(&[make_station(53104), make_station(53105)])
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("range.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    // One observation at noon on each of five days, plus another station's
+    let base: chrono::DateTime<Utc> = "2024-03-01T12:00:00Z".parse().unwrap();
+    let mut observations: Vec<_> = (0..5)
+        .map(|day| make_observation(53104, base + chrono::Duration::days(day)))
+        .collect();
+    observations.push(make_observation(53105, base + chrono::Duration::days(2)));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let start = base + chrono::Duration::days(1);
+    let end = base + chrono::Duration::days(3);
+    let found = repo
+        .get_observations_for_station_in_range(Wbanno(53104), start, end)
+        .await
+        .expect("Query failed");
+
+    let times: Vec<_> = found.iter().map(|o| o.utc_datetime).collect();
+    assert_eq!(
+        times,
+        vec![start, base + chrono::Duration::days(2), end],
+        "bounds are inclusive and other stations are excluded"
+    );
+}
+
+/// Test daily aggregates group two days of hourly observations by UTC day
+#[sqlx::test]
+async fn test_get_daily_aggregates(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.batch_upsert_stations(&[make_station(53104)])
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("daily.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    // Day one is steady at 10C; day two rises from 0C by one degree an hour
+    let base: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let mut observations = make_observations(53104, base, 48);
+    for (hour, obs) in observations.iter_mut().enumerate() {
+        let t = if hour < 24 { 10.0 } else { (hour - 24) as f32 };
+        obs.t_hr_avg = Some(t);
+        obs.t_max = Some(t + 1.0);
+        obs.t_min = Some(t - 1.0);
+        obs.p_calc = Some(if hour < 24 { 0.5 } else { 0.0 });
+        obs.rh_hr_avg = Some(50.0);
+    }
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let days = repo
+        .get_daily_aggregates(Wbanno(53104), base, base + chrono::Duration::hours(47))
+        .await
+        .expect("Query failed");
+
+    assert_eq!(days.len(), 2);
+    assert_eq!(
+        days[0].date,
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+    );
+    assert_eq!(days[0].t_avg, Some(10.0));
+    assert_eq!(days[0].t_max, Some(11.0));
+    assert_eq!(days[0].t_min, Some(9.0));
+    assert_eq!(days[0].precip_total, Some(12.0));
+    assert_eq!(days[0].avg_rh, Some(50.0));
+    assert_eq!(days[0].avg_solarad, None);
+
+    assert_eq!(
+        days[1].date,
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+    );
+    assert_eq!(days[1].t_avg, Some(11.5));
+    assert_eq!(days[1].t_max, Some(24.0));
+    assert_eq!(days[1].t_min, Some(-1.0));
+    assert_eq!(days[1].precip_total, Some(0.0));
+}
+
+/// Test daily summaries are stored and upserted on re-insert
+#[sqlx::test]
+async fn test_insert_daily_observations(pool: PgPool) {
+    let repo = Repository::new(pool.clone());
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("CRND0103-2024-CA_Test.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let content = "53104 20240115 2.623 -81.74 36.53 8.3 -2.6 2.9 3.1 0.0 10.53 C 9.9 -3.4 2.6 92.3 51.1 74.4 0.215 0.220 0.230 0.240 0.250 3.1 3.5 4.0 5.2 7.8\n\
+                   53104 20240116 2.623 -81.74 36.53 9.1 -1.0 4.1 4.0 2.5 8.20 C 10.2 -2.1 3.3 95.0 60.2 80.1 0.230 0.225 0.231 0.240 0.250 3.4 3.6 4.0 5.2 7.8";
+    let (days, _) =
+        Parser::parse_daily_file(content, FieldSeparator::Whitespace, None).expect("Parse failed");
+
+    let rows = repo
+        .insert_daily_observations(&days, file_id)
+        .await
+        .expect("Insert failed");
+    assert_eq!(rows, 2);
+
+    repo.insert_daily_observations(&days, file_id)
+        .await
+        .expect("Re-insert failed");
+    let (count, precip): (i64, Option<f32>) =
+        sqlx::query_as("SELECT COUNT(*), SUM(p_daily_calc) FROM daily_observations")
+            .fetch_one(&pool)
+            .await
+            .expect("Query failed");
+    assert_eq!(count, 2);
+    assert_eq!(precip, Some(2.5));
+}
+
+/// Test only the newest observation of each station is returned, with its station
+#[sqlx::test]
+async fn test_get_latest_observation_per_station(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.batch_upsert_stations(&[make_station(53104), make_station(3047), make_station(4990)])
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("latest.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let base: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let mut observations = make_observations(53104, base, 5);
+    observations.extend(make_observations(3047, base, 3));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let latest = repo
+        .get_latest_observation_per_station()
+        .await
+        .expect("Query failed");
+
+    // Station 4990 has no observations
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[0].0.wbanno, Wbanno(3047));
+    assert_eq!(latest[0].1.wbanno, Wbanno(3047));
+    assert_eq!(latest[0].1.utc_datetime, base + chrono::Duration::hours(2));
+    assert_eq!(latest[1].0.wbanno, Wbanno(53104));
+    assert_eq!(latest[1].1.wbanno, Wbanno(53104));
+    assert_eq!(latest[1].1.utc_datetime, base + chrono::Duration::hours(4));
+}
+
+/// Test missing hours are grouped into gaps, including at the range edges
+#[sqlx::test]
+async fn test_detect_gaps(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("gaps.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    // Hours 2-3, 5-7 and 10 of 0-10 are missing
+    let base: chrono::DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+    let hour = |h: i64| base + chrono::Duration::hours(h);
+    let mut observations = make_observations(53104, base, 2);
+    observations.push(make_observation(53104, hour(4)));
+    observations.extend(make_observations(53104, hour(8), 2));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let gaps = detect_gaps(
+        &repo,
+        Wbanno(53104),
+        base,
+        hour(10),
+        chrono::Duration::hours(1),
+    )
+    .await
+    .expect("Gap detection failed");
+    assert_eq!(
+        gaps,
+        vec![
+            GapInterval {
+                start: hour(2),
+                end: hour(3),
+                missing_count: 2,
+            },
+            GapInterval {
+                start: hour(5),
+                end: hour(7),
+                missing_count: 3,
+            },
+            GapInterval {
+                start: hour(10),
+                end: hour(10),
+                missing_count: 1,
+            },
+        ]
+    );
+
+    // A station with no observations is one gap covering the whole range
+    let gaps = detect_gaps(
+        &repo,
+        Wbanno(3047),
+        base,
+        hour(10),
+        chrono::Duration::hours(1),
+    )
+    .await
+    .expect("Gap detection failed");
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].missing_count, 11);
+
+    assert!(matches!(
+        detect_gaps(
+            &repo,
+            Wbanno(53104),
+            base,
+            hour(10),
+            chrono::Duration::zero()
+        )
+        .await,
+        Err(AppError::InvalidData(_))
+    ));
+}
+
+/// Test observations before the cutoff are deleted and later ones kept
+#[sqlx::test]
+async fn test_delete_observations_before(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.upsert_station(make_station(53104))
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("retention.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let base: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    repo.insert_observations(&make_observations(53104, base, 10), file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let deleted = repo
+        .delete_observations_before(base + chrono::Duration::hours(4))
+        .await
+        .expect("Delete failed");
+    assert_eq!(deleted, 4);
+
+    let remaining = repo
+        .get_observations_for_station_in_range(
+            Wbanno(53104),
+            base,
+            base + chrono::Duration::hours(9),
+        )
+        .await
+        .expect("Query failed");
+    assert_eq!(remaining.len(), 6);
+    assert_eq!(remaining[0].utc_datetime, base + chrono::Duration::hours(4));
+}
+
+/// Test coverage reports each station's count and time range
+#[sqlx::test]
+async fn test_get_data_coverage_summary(pool: PgPool) {
+    let repo = Repository::new(pool);
+    repo.batch_upsert_stations(&[make_station(53104), make_station(3047), make_station(4990)])
+        .await
+        .expect("Station insert failed");
+    let file_id = repo
+        .mark_file_processed(make_processed_file("coverage.txt", 2024))
+        .await
+        .expect("File insert failed");
+
+    let jan: chrono::DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let jun: chrono::DateTime<Utc> = "2024-06-01T12:00:00Z".parse().unwrap();
+    let mut observations = make_observations(53104, jan, 24);
+    observations.extend(make_observations(3047, jun, 3));
+    repo.insert_observations(&observations, file_id, None)
+        .await
+        .expect("Insert failed");
+
+    let coverage = repo
+        .get_data_coverage_summary()
+        .await
+        .expect("Query failed");
+
+    assert_eq!(coverage.len(), 3);
+    assert_eq!(coverage[0].wbanno, Wbanno(3047));
+    assert_eq!(coverage[0].observation_count, 3);
+    assert_eq!(coverage[0].earliest_utc, Some(jun));
+    assert_eq!(
+        coverage[0].latest_utc,
+        Some(jun + chrono::Duration::hours(2))
+    );
+
+    // Station 4990 has no observations
+    assert_eq!(coverage[1].wbanno, Wbanno(4990));
+    assert_eq!(coverage[1].observation_count, 0);
+    assert_eq!(coverage[1].earliest_utc, None);
+
+    assert_eq!(coverage[2].wbanno, Wbanno(53104));
+    assert_eq!(coverage[2].state, "CA");
+    assert_eq!(coverage[2].observation_count, 24);
+    assert_eq!(coverage[2].earliest_utc, Some(jan));
+    assert_eq!(
+        coverage[2].latest_utc,
+        Some(jan + chrono::Duration::hours(23))
+    );
+}
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use uscrn_ingest::error::AppError;
 use uscrn_ingest::fetcher::Fetcher;
 use wiremock::matchers::{method, path};
@@ -148,3 +149,565 @@ async fn test_empty_location_filter_matches_all() {
     assert!(filter.matches_station(12345));
     assert!(filter.is_empty());
 }
+
+/// Test that the year listing is fetched once and served from cache afterwards
+#[tokio::test]
+async fn test_list_years_is_cached() {
+    let mock_server = MockServer::start().await;
+
+    let listing = r#"<html><body>
+        <a href="2023/">2023/</a>
+        <a href="2024/">2024/</a>
+        <a href="README.txt">README.txt</a>
+    </body></html>"#;
+
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(listing))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri()).expect("Failed to create fetcher");
+
+    let first = fetcher.list_years().await.expect("First listing failed");
+    let second = fetcher.list_years().await.expect("Second listing failed");
+
+    assert_eq!(first, vec![2023, 2024]);
+    assert_eq!(first, second);
+
+    // MockServer verifies the `expect(1)` on drop
+}
+
+/// Test that a forced refresh bypasses the year listing cache
+#[tokio::test]
+async fn test_list_years_force_refresh() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"<a href="2024/">2024/</a>"#))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri()).expect("Failed to create fetcher");
+
+    fetcher.list_years().await.expect("Listing failed");
+    fetcher.list_years().await.expect("Cached listing failed");
+    let refreshed = fetcher
+        .list_years_force_refresh()
+        .await
+        .expect("Refresh failed");
+
+    assert_eq!(refreshed, vec![2024]);
+}
+
+/// Test a cached year listing older than the TTL is fetched again
+#[tokio::test]
+async fn test_list_years_cache_expires() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"<a href="2024/">2024/</a>"#))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .years_cache_ttl(Duration::ZERO);
+
+    fetcher.list_years().await.expect("Listing failed");
+    let years = fetcher.list_years().await.expect("Expired listing failed");
+
+    assert_eq!(years, vec![2024]);
+}
+
+/// Test the listing format canary against a recognisable and a changed listing
+#[tokio::test]
+async fn test_check_listing_format() {
+    use chrono::Datelike;
+
+    let mock_server = MockServer::start().await;
+    let year_path = format!("/{}/", chrono::Utc::now().year());
+
+    Mock::given(method("GET"))
+        .and(path(year_path.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<a href="CRNH0203-2024-CA_Bodega_6_WSW.txt">CRNH0203-2024-CA_Bodega_6_WSW.txt</a>"#,
+        ))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(year_path.as_str()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(r#"<div data-file="CRNH0203-2024-CA_Bodega_6_WSW.txt"></div>"#),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri()).expect("Failed to create fetcher");
+
+    fetcher
+        .check_listing_format()
+        .await
+        .expect("Recognisable listing should pass");
+
+    match fetcher.check_listing_format().await {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("format may have changed")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+}
+
+/// Test a missing listing passes with a warning, and an empty new-year
+/// listing passes when the previous year still links to data files
+#[tokio::test]
+async fn test_check_listing_format_tolerates_missing_and_new_year_listings() {
+    use chrono::Datelike;
+
+    let current_year = chrono::Utc::now().year();
+    let fetcher_for = |server: &MockServer| {
+        Fetcher::new(&server.uri())
+            .expect("Failed to create fetcher")
+            .allow_http(true)
+    };
+
+    // Nothing mounted: every listing is a 404
+    let missing = MockServer::start().await;
+    fetcher_for(&missing)
+        .check_listing_format()
+        .await
+        .expect("Missing listings should only warn");
+
+    let new_year = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/", current_year)))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+        .mount(&new_year)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/", current_year - 1)))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<a href="CRNH0203-2024-CA_Bodega_6_WSW.txt">CRNH0203-2024-CA_Bodega_6_WSW.txt</a>"#,
+        ))
+        .mount(&new_year)
+        .await;
+    fetcher_for(&new_year)
+        .check_listing_format()
+        .await
+        .expect("Previous year's listing should pass");
+}
+
+/// Test a subhourly01 fetcher lists only 5-minute data files
+#[tokio::test]
+async fn test_list_files_for_subhourly_product() {
+    use uscrn_ingest::config::DataProduct;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/2024/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<a href="CRNS0101-05-2024-CA_Bodega_6_WSW.txt">a</a>
+               <a href="CRNH0203-2024-CA_Bodega_6_WSW.txt">b</a>"#,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .product(DataProduct::Subhourly01);
+    let files = fetcher
+        .list_files_for_year(2024, &Default::default())
+        .await
+        .expect("Listing failed");
+
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].name, "CRNS0101-05-2024-CA_Bodega_6_WSW.txt");
+    assert_eq!(files[0].state, "CA");
+    assert_eq!(files[0].station_name, "Bodega_6_WSW");
+}
+
+/// Test listing and reading files from a local wget mirror
+#[tokio::test]
+async fn test_fetcher_from_wget_mirror() {
+    use uscrn_ingest::config::LocationFilter;
+
+    let mirror = tempfile::tempdir().expect("Failed to create temp dir");
+    let products = mirror
+        .path()
+        .join("www.ncei.noaa.gov/pub/data/uscrn/products/hourly02");
+
+    for (year, name, content) in [
+        (2023, "CRNH0203-2023-CA_Bodega_6_WSW.txt", "bodega 2023"),
+        (2024, "CRNH0203-2024-CA_Bodega_6_WSW.txt", "bodega 2024"),
+        (2024, "CRNH0203-2024-TX_Austin_33_NW.txt", "austin 2024"),
+    ] {
+        let dir = products.join(year.to_string());
+        std::fs::create_dir_all(&dir).expect("Failed to create year dir");
+        std::fs::write(dir.join(name), content).expect("Failed to write file");
+    }
+    std::fs::write(products.join("index.html"), "<html></html>").expect("Write failed");
+
+    let fetcher = Fetcher::from_wget_mirror(mirror.path()).expect("Failed to index mirror");
+
+    assert_eq!(fetcher.list_years().await.unwrap(), vec![2023, 2024]);
+
+    let filter = LocationFilter {
+        states: vec!["TX".to_string()],
+        stations: vec![],
+        patterns: vec![],
+    };
+    let files = fetcher
+        .list_files_for_year(2024, &filter)
+        .await
+        .expect("Listing failed");
+
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].state, "TX");
+    assert_eq!(files[0].station_name, "Austin_33_NW");
+
+    let content = fetcher
+        .download_file(&files[0].url)
+        .await
+        .expect("Read failed");
+    assert_eq!(content, "austin 2024");
+
+    // Paths outside the index are an error, not a rejected file
+    let result = fetcher.download_file("file:///etc/passwd").await;
+    assert!(matches!(result, Err(AppError::Io(_))));
+}
+
+/// Test files added after the mirror scan can be indexed and read
+#[tokio::test]
+async fn test_fetcher_index_mirror_file() {
+    let mirror = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut fetcher = Fetcher::from_wget_mirror(mirror.path()).expect("Failed to index mirror");
+    assert!(fetcher.list_years().await.unwrap().is_empty());
+
+    let path = mirror.path().join("CRNH0203-2024-NC_Asheville_8_SSW.txt");
+    std::fs::write(&path, "asheville 2024").expect("Failed to write file");
+
+    let file_info = fetcher
+        .index_mirror_file(&path)
+        .expect("Data file should be indexed");
+    assert_eq!(file_info.year, 2024);
+    assert_eq!(file_info.state, "NC");
+
+    // Re-indexing a modified file does not list it twice
+    fetcher.index_mirror_file(&path);
+    let files = fetcher
+        .list_files_for_year(2024, &Default::default())
+        .await
+        .expect("Listing failed");
+    assert_eq!(files.len(), 1);
+
+    let content = fetcher
+        .download_file(&file_info.url)
+        .await
+        .expect("Read failed");
+    assert_eq!(content, "asheville 2024");
+
+    let other = mirror.path().join("notes.txt");
+    std::fs::write(&other, "notes").expect("Failed to write file");
+    assert!(fetcher.index_mirror_file(&other).is_none());
+}
+
+/// Write a config pointing at a mock server and load it through `Config::load`
+fn load_mock_config(
+    base_url: &str,
+    allow_http: bool,
+) -> uscrn_ingest::error::Result<uscrn_ingest::config::Config> {
+    let yaml = format!(
+        r#"
+database:
+  host: localhost
+  name: test
+  user: test
+  password: test
+scheduler:
+  interval_minutes: 60
+source:
+  base_url: "{}"
+  years_to_fetch: "current"
+  allow_http: {}
+"#,
+        base_url, allow_http
+    );
+    let file = tempfile::NamedTempFile::new().expect("Failed to create temp config");
+    std::fs::write(file.path(), yaml).expect("Failed to write temp config");
+    uscrn_ingest::config::Config::load(file.path())
+}
+
+/// Test a wiremock-backed config downloads over HTTP when allow_http is set
+#[tokio::test]
+async fn test_allow_http_config_downloads_from_mock_server() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/test.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("mock content"))
+        .mount(&mock_server)
+        .await;
+
+    let config = load_mock_config(&mock_server.uri(), true).expect("Config should load");
+    let fetcher = Fetcher::from_config(&config.source).expect("Failed to create fetcher");
+
+    let content = fetcher
+        .download_file(&format!("{}/test.txt", mock_server.uri()))
+        .await
+        .expect("Download failed");
+
+    assert_eq!(content, "mock content");
+}
+
+/// Test HTTP base URLs are still rejected without allow_http
+#[tokio::test]
+async fn test_http_config_rejected_without_allow_http() {
+    let result = load_mock_config("http://127.0.0.1:8080", false);
+
+    match result {
+        Err(AppError::Config(msg)) => assert!(msg.contains("must use HTTPS")),
+        other => panic!("Expected Config error, got: {:?}", other.map(|_| ())),
+    }
+}
+
+/// Test downloads larger than max_file_size_bytes are rejected from Content-Length
+#[tokio::test]
+async fn test_fetcher_rejects_file_over_size_limit() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/large.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(2048)))
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .allow_http(true)
+        .max_file_size_bytes(Some(1024));
+
+    let result = fetcher
+        .download_file(&format!("{}/large.txt", mock_server.uri()))
+        .await;
+
+    match result {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("max_file_size_bytes")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+
+    // Files under the limit still download
+    let fetcher = fetcher.max_file_size_bytes(Some(4096));
+    let content = fetcher
+        .download_file(&format!("{}/large.txt", mock_server.uri()))
+        .await
+        .expect("Download failed");
+    assert_eq!(content.len(), 2048);
+}
+
+/// Test a chunked download without Content-Length is cut off at max_file_size_bytes
+#[tokio::test]
+async fn test_fetcher_limits_chunked_download_size() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Bind failed");
+    let address = listener.local_addr().expect("No local address");
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.expect("Accept failed");
+        let mut request = [0u8; 1024];
+        let _ = socket.read(&mut request).await;
+        let chunk = "x".repeat(512);
+        let mut response =
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+                .to_string();
+        for _ in 0..4 {
+            response.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+        }
+        response.push_str("0\r\n\r\n");
+        let _ = socket.write_all(response.as_bytes()).await;
+    });
+
+    let base = format!("http://{}", address);
+    let fetcher = Fetcher::new(&base)
+        .expect("Failed to create fetcher")
+        .allow_http(true)
+        .max_file_size_bytes(Some(1024));
+
+    match fetcher
+        .download_file(&format!("{}/chunked.txt", base))
+        .await
+    {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("max_file_size_bytes")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+}
+
+/// Test downloads use their own timeout rather than the shorter listing timeout
+#[tokio::test]
+async fn test_download_uses_download_timeout() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/slow.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("slow content")
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .allow_http(true)
+        .listing_timeout(Duration::from_millis(100))
+        .download_timeout(Duration::from_secs(5));
+
+    let content = fetcher
+        .download_file(&format!("{}/slow.txt", mock_server.uri()))
+        .await
+        .expect("Download failed");
+    assert_eq!(content, "slow content");
+}
+
+/// Test repeated downloads of the same URL are served from the content cache
+#[tokio::test]
+async fn test_download_content_cache() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cached.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("cached content"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .allow_http(true)
+        .content_cache_size(4);
+    let url = format!("{}/cached.txt", mock_server.uri());
+
+    let first = fetcher.download_file(&url).await.expect("Download failed");
+    let second = fetcher.download_file(&url).await.expect("Download failed");
+    assert_eq!(first, "cached content");
+    assert_eq!(first, second);
+
+    // MockServer verifies the `expect(1)` on drop
+}
+
+/// Test conditional downloads send If-Modified-Since and handle 304 Not Modified
+#[tokio::test]
+async fn test_download_file_if_modified() {
+    use chrono::{DateTime, Utc};
+    use uscrn_ingest::fetcher::DownloadResult;
+    use wiremock::matchers::header_exists;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/data.txt"))
+        .and(header_exists("If-Modified-Since"))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/data.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("fresh content")
+                .insert_header("Last-Modified", "Mon, 01 Jan 2024 00:00:00 GMT"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .allow_http(true);
+    let url = format!("{}/data.txt", mock_server.uri());
+    let last_modified = "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+    let first = fetcher
+        .download_file_if_modified(&url, None)
+        .await
+        .expect("Download failed");
+    assert_eq!(
+        first,
+        DownloadResult::Downloaded {
+            content: "fresh content".to_string(),
+            size: 13,
+            last_modified: Some(last_modified),
+            hash: uscrn_ingest::fetcher::content_hash("fresh content"),
+        }
+    );
+
+    let second = fetcher
+        .download_file_if_modified(&url, Some(last_modified))
+        .await
+        .expect("Download failed");
+    assert_eq!(second, DownloadResult::NotModified);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("If-Modified-Since").is_none());
+    assert_eq!(
+        requests[1].headers.get("If-Modified-Since").unwrap(),
+        "Mon, 01 Jan 2024 00:00:00 GMT"
+    );
+}
+
+/// Test short unfiltered year listings fail only with strict_file_count
+#[tokio::test]
+async fn test_min_expected_files_per_year() {
+    use uscrn_ingest::config::LocationFilter;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/2024/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<a href="CRNH0203-2024-CA_Bodega_6_WSW.txt">a</a>
+               <a href="CRNH0203-2024-NC_Asheville_8_SSW.txt">b</a>"#,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = Fetcher::new(&mock_server.uri())
+        .expect("Failed to create fetcher")
+        .allow_http(true)
+        .min_expected_files_per_year(Some(3));
+    let unfiltered = LocationFilter::default();
+
+    // Short listings only warn by default
+    let files = fetcher
+        .list_files_for_year(2024, &unfiltered)
+        .await
+        .expect("Listing failed");
+    assert_eq!(files.len(), 2);
+
+    let fetcher = fetcher.strict_file_count(true);
+    match fetcher.list_files_for_year(2024, &unfiltered).await {
+        Err(AppError::InvalidData(msg)) => assert!(msg.contains("min_expected_files_per_year")),
+        other => panic!("Expected InvalidData error, got: {:?}", other),
+    }
+
+    // Filtered listings are expected to be short
+    let filtered = LocationFilter {
+        states: vec!["CA".to_string()],
+        ..LocationFilter::default()
+    };
+    let files = fetcher
+        .list_files_for_year(2024, &filtered)
+        .await
+        .expect("Listing failed");
+    assert_eq!(files.len(), 1);
+}
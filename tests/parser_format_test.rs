@@ -0,0 +1,149 @@
+use uscrn_ingest::config::FieldSeparator;
+use uscrn_ingest::db::models::{NewObservation, Wbanno};
+use uscrn_ingest::parser::Parser;
+
+/// A sample file for one CRX logger version and the fields it should populate
+struct FormatCase {
+    name: &'static str,
+    content: &'static str,
+    crx_version: &'static str,
+    has_soil_moisture: bool,
+    has_soil_temp: bool,
+}
+
+const CASES: &[FormatCase] = &[
+    FormatCase {
+        name: "CRX version 1 (28 fields)",
+        content: include_str!("fixtures/uscrn_samples/crx_v1_28_fields.txt"),
+        crx_version: "1.301",
+        has_soil_moisture: false,
+        has_soil_temp: false,
+    },
+    FormatCase {
+        name: "CRX version 2 (33 fields)",
+        content: include_str!("fixtures/uscrn_samples/crx_v2_33_fields.txt"),
+        crx_version: "2.512",
+        has_soil_moisture: true,
+        has_soil_temp: false,
+    },
+    FormatCase {
+        name: "CRX version 3 (38 fields)",
+        content: include_str!("fixtures/uscrn_samples/crx_v3_38_fields.txt"),
+        crx_version: "3.2",
+        has_soil_moisture: true,
+        has_soil_temp: true,
+    },
+];
+
+fn soil_moisture(obs: &NewObservation) -> [Option<f32>; 5] {
+    [
+        obs.soil_moisture_5,
+        obs.soil_moisture_10,
+        obs.soil_moisture_20,
+        obs.soil_moisture_50,
+        obs.soil_moisture_100,
+    ]
+}
+
+fn soil_temp(obs: &NewObservation) -> [Option<f32>; 5] {
+    [
+        obs.soil_temp_5,
+        obs.soil_temp_10,
+        obs.soil_temp_20,
+        obs.soil_temp_50,
+        obs.soil_temp_100,
+    ]
+}
+
+/// Test every known CRX version populates the fields its format carries
+#[test]
+fn test_parse_each_crx_version() {
+    for case in CASES {
+        let (observations, stats) = Parser::parse_file(case.content, FieldSeparator::Whitespace)
+            .unwrap_or_else(|e| panic!("{}: parse failed: {}", case.name, e));
+
+        assert_eq!(observations.len(), 2, "{}", case.name);
+        assert_eq!(stats.parse_failures, 0, "{}", case.name);
+
+        for obs in &observations {
+            assert_eq!(obs.wbanno, Wbanno(53104), "{}", case.name);
+            assert_eq!(
+                obs.crx_version.as_deref(),
+                Some(case.crx_version),
+                "{}",
+                case.name
+            );
+
+            // Core meteorological fields are present in every version
+            assert!(obs.t_hr_avg.is_some(), "{}: t_hr_avg", case.name);
+            assert!(obs.p_calc.is_some(), "{}: p_calc", case.name);
+            assert!(obs.solarad.is_some(), "{}: solarad", case.name);
+            assert!(obs.rh_hr_avg.is_some(), "{}: rh_hr_avg", case.name);
+
+            assert!(
+                soil_moisture(obs)
+                    .iter()
+                    .all(|v| v.is_some() == case.has_soil_moisture),
+                "{}: soil moisture presence",
+                case.name
+            );
+            assert!(
+                soil_temp(obs)
+                    .iter()
+                    .all(|v| v.is_some() == case.has_soil_temp),
+                "{}: soil temperature presence",
+                case.name
+            );
+        }
+    }
+}
+
+/// Test CRX version 2 soil moisture values are read from the right columns
+#[test]
+fn test_parse_crx_v2_soil_moisture_values() {
+    let (observations, _) =
+        Parser::parse_file(CASES[1].content, FieldSeparator::Whitespace).expect("Parse failed");
+    let obs = &observations[0];
+
+    assert_eq!(
+        soil_moisture(obs),
+        [
+            Some(0.215),
+            Some(0.230),
+            Some(0.251),
+            Some(0.287),
+            Some(0.312)
+        ]
+    );
+}
+
+/// Test CRX version 3 soil temperature values are read from the right columns
+#[test]
+fn test_parse_crx_v3_soil_temp_values() {
+    let (observations, _) =
+        Parser::parse_file(CASES[2].content, FieldSeparator::Whitespace).expect("Parse failed");
+    let obs = &observations[0];
+
+    assert_eq!(
+        soil_temp(obs),
+        [Some(18.4), Some(17.9), Some(17.1), Some(15.8), Some(14.2)]
+    );
+}
+
+/// Test a hypothetical future version with extra trailing fields parses without panicking
+#[test]
+fn test_parse_unknown_future_version() {
+    let content = include_str!("fixtures/uscrn_samples/crx_v4_45_fields.txt");
+
+    let (observations, stats) =
+        Parser::parse_file(content, FieldSeparator::Whitespace).expect("Parse failed");
+
+    assert_eq!(observations.len(), 2);
+    assert_eq!(stats.parse_failures, 0);
+
+    // Known fields still map to the same columns; extra fields are ignored
+    let obs = &observations[0];
+    assert_eq!(obs.crx_version.as_deref(), Some("4.0"));
+    assert_eq!(obs.soil_moisture_5, Some(0.215));
+    assert_eq!(obs.soil_temp_100, Some(14.2));
+}
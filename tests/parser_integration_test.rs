@@ -1,8 +1,13 @@
+mod common;
+
 use sqlx::PgPool;
+use uscrn_ingest::config::FieldSeparator;
 use uscrn_ingest::db::models::{NewProcessedFile, NewStation};
 use uscrn_ingest::db::Repository;
 use uscrn_ingest::parser::Parser;
 
+use common::{make_processed_file, make_station};
+
 /// Test parsing a complete USCRN data file and inserting into database
 #[sqlx::test]
 async fn test_parse_and_insert_complete_flow(pool: PgPool) {
@@ -14,7 +19,8 @@ async fn test_parse_and_insert_complete_flow(pool: PgPool) {
 53104 20240115 1500 20240115 0700 3   -81.74    36.53  -9999.0     4.5     5.2     4.0     0.0    52.3 0    65.4 0    42.1 0 C     1.8 0     2.5 0    -0.2 0    78.5 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
 
     // Parse the file
-    let (observations, stats) = Parser::parse_file(file_content).expect("Parse failed");
+    let (observations, stats) =
+        Parser::parse_file(file_content, FieldSeparator::Whitespace).expect("Parse failed");
 
     assert_eq!(observations.len(), 2);
     assert_eq!(stats.parsed_successfully, 2);
@@ -22,11 +28,10 @@ async fn test_parse_and_insert_complete_flow(pool: PgPool) {
 
     // Insert station first
     let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
         state: "NC".to_string(),
         latitude: Some(36.53),
         longitude: Some(-81.74),
+        ..make_station(53104)
     };
     repo.upsert_station(station)
         .await
@@ -34,18 +39,10 @@ async fn test_parse_and_insert_complete_flow(pool: PgPool) {
 
     // Create processed file record
     let file = NewProcessedFile {
-        file_name: "test_file.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
         state: "NC".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: observations.len() as i32,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
         parse_failures: stats.parse_failures as i32,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_file.txt", 2024)
     };
     let file_id = repo
         .mark_file_processed(file)
@@ -54,7 +51,7 @@ async fn test_parse_and_insert_complete_flow(pool: PgPool) {
 
     // Insert observations
     let result = repo
-        .insert_observations(&observations, file_id)
+        .insert_observations(&observations, file_id, None)
         .await
         .expect("Insert failed");
 
@@ -92,15 +89,13 @@ async fn test_parse_missing_values_stored_as_null(pool: PgPool) {
     let file_content = "\
 53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
 
-    let (observations, _) = Parser::parse_file(file_content).expect("Parse failed");
+    let (observations, _) =
+        Parser::parse_file(file_content, FieldSeparator::Whitespace).expect("Parse failed");
 
     // Insert station
     let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
         state: "NC".to_string(),
-        latitude: None,
-        longitude: None,
+        ..make_station(53104)
     };
     repo.upsert_station(station)
         .await
@@ -108,25 +103,16 @@ async fn test_parse_missing_values_stored_as_null(pool: PgPool) {
 
     // Create processed file
     let file = NewProcessedFile {
-        file_name: "test_missing.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
         state: "NC".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 1,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_missing.txt", 2024)
     };
     let file_id = repo
         .mark_file_processed(file)
         .await
         .expect("File insert failed");
 
-    repo.insert_observations(&observations, file_id)
+    repo.insert_observations(&observations, file_id, None)
         .await
         .expect("Insert failed");
 
@@ -153,7 +139,7 @@ invalid line without enough fields
 another invalid line
 yet another invalid line";
 
-    let result = Parser::parse_file(file_content);
+    let result = Parser::parse_file(file_content, FieldSeparator::Whitespace);
 
     assert!(result.is_err());
     let err = result.unwrap_err();
@@ -164,7 +150,7 @@ yet another invalid line";
 #[tokio::test]
 async fn test_parse_empty_file() {
     let file_content = "";
-    let result = Parser::parse_file(file_content);
+    let result = Parser::parse_file(file_content, FieldSeparator::Whitespace);
 
     // Empty file returns Ok with empty observations
     assert!(result.is_ok());
@@ -177,7 +163,7 @@ async fn test_parse_empty_file() {
 #[tokio::test]
 async fn test_parse_whitespace_only_file() {
     let file_content = "   \n\n  \n\n";
-    let result = Parser::parse_file(file_content);
+    let result = Parser::parse_file(file_content, FieldSeparator::Whitespace);
 
     // Whitespace-only file returns Ok with empty observations
     assert!(result.is_ok());
@@ -195,11 +181,11 @@ invalid line
 53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
 
     // Default threshold (10%) should reject
-    let result = Parser::parse_file(file_content);
+    let result = Parser::parse_file(file_content, FieldSeparator::Whitespace);
     assert!(result.is_err());
 
     // Higher threshold (60%) should accept
-    let result = Parser::parse_file_with_threshold(file_content, 0.60);
+    let result = Parser::parse_file_with_threshold(file_content, FieldSeparator::Whitespace, 0.60);
     assert!(result.is_ok());
     let (observations, stats) = result.unwrap();
     assert_eq!(observations.len(), 1);
@@ -216,63 +202,43 @@ async fn test_reimport_deduplicates_observations(pool: PgPool) {
 
     // Insert station
     let station = NewStation {
-        wbanno: 53104,
-        name: Some("Test Station".to_string()),
         state: "NC".to_string(),
-        latitude: None,
-        longitude: None,
+        ..make_station(53104)
     };
     repo.upsert_station(station)
         .await
         .expect("Station insert failed");
 
     // First import
-    let (observations, _) = Parser::parse_file(file_content).expect("Parse failed");
+    let (observations, _) =
+        Parser::parse_file(file_content, FieldSeparator::Whitespace).expect("Parse failed");
 
     let file1 = NewProcessedFile {
-        file_name: "test_dedup.txt".to_string(),
-        file_url: "https://example.com/test.txt".to_string(),
-        year: 2024,
         state: "NC".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 1,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_dedup.txt", 2024)
     };
     let file_id1 = repo
         .mark_file_processed(file1)
         .await
         .expect("File insert failed");
 
-    repo.insert_observations(&observations, file_id1)
+    repo.insert_observations(&observations, file_id1, None)
         .await
         .expect("First insert failed");
 
     // Second import (simulating re-processing same file)
     let file2 = NewProcessedFile {
-        file_name: "test_dedup_v2.txt".to_string(),
-        file_url: "https://example.com/test_v2.txt".to_string(),
-        year: 2024,
         state: "NC".to_string(),
-        station_name: "Test Station".to_string(),
-        last_modified: None,
         rows_processed: 1,
-        file_hash: None,
-        observations_inserted: 0,
-        observations_updated: 0,
-        parse_failures: 0,
-        processing_status: "processing".to_string(),
+        ..make_processed_file("test_dedup_v2.txt", 2024)
     };
     let file_id2 = repo
         .mark_file_processed(file2)
         .await
         .expect("File insert failed");
 
-    repo.insert_observations(&observations, file_id2)
+    repo.insert_observations(&observations, file_id2, None)
         .await
         .expect("Second insert failed");
 
@@ -285,3 +251,26 @@ async fn test_reimport_deduplicates_observations(pool: PgPool) {
 
     assert_eq!(count, 1, "Should have deduplicated the observation");
 }
+
+/// Test completeness checks on parsed observations with and without missing values
+#[test]
+fn test_observation_completeness() {
+    let complete = include_str!("fixtures/uscrn_samples/crx_v3_38_fields.txt");
+    let (observations, _) =
+        Parser::parse_file(complete, FieldSeparator::Whitespace).expect("Parse failed");
+    assert!(observations
+        .iter()
+        .all(|o| o.is_complete() && o.is_soil_complete()));
+
+    // Core readings present, every soil value missing
+    let no_soil = "\
+53104 20240115 1400 20240115 0600 3   -81.74    36.53  -9999.0     4.1     4.9     3.4     0.0    45.5 0    58.6 0    35.9 0 C     1.1 0     2.1 0    -0.5 0    81.9 0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0   -9999.0";
+    let (observations, _) =
+        Parser::parse_file(no_soil, FieldSeparator::Whitespace).expect("Parse failed");
+    assert!(observations[0].is_complete());
+    assert!(!observations[0].is_soil_complete());
+
+    let mut missing_rh = observations[0].clone();
+    missing_rh.rh_hr_avg = None;
+    assert!(!missing_rh.is_complete());
+}
@@ -0,0 +1,205 @@
+use uscrn_ingest::config::FieldSeparator;
+use uscrn_ingest::db::models::{NewObservation, Wbanno};
+use uscrn_ingest::parser::columns::*;
+use uscrn_ingest::parser::Parser;
+
+/// One column of the NOAA hourly02 format as documented in the product README
+struct ColumnSpec {
+    col: usize,
+    name: &'static str,
+    units: &'static str,
+    /// Valid range for numeric columns (None for identifiers and codes)
+    range: Option<(f64, f64)>,
+    /// Value used for this column in the sample line
+    sample: &'static str,
+}
+
+#[rustfmt::skip]
+const SPEC: &[ColumnSpec] = &[
+    ColumnSpec { col: WBANNO_COL, name: "WBANNO", units: "XXXXX", range: None, sample: "53104" },
+    ColumnSpec { col: UTC_DATE_COL, name: "UTC_DATE", units: "YYYYMMDD", range: None, sample: "20240115" },
+    ColumnSpec { col: UTC_TIME_COL, name: "UTC_TIME", units: "HHmm", range: None, sample: "1300" },
+    ColumnSpec { col: LST_DATE_COL, name: "LST_DATE", units: "YYYYMMDD", range: None, sample: "20240115" },
+    ColumnSpec { col: LST_TIME_COL, name: "LST_TIME", units: "HHmm", range: None, sample: "0800" },
+    ColumnSpec { col: CRX_VN_COL, name: "CRX_VN", units: "XXXXXX", range: None, sample: "2.623" },
+    ColumnSpec { col: LONGITUDE_COL, name: "LONGITUDE", units: "Decimal_degrees", range: Some((-180.0, 180.0)), sample: "-82.61" },
+    ColumnSpec { col: LATITUDE_COL, name: "LATITUDE", units: "Decimal_degrees", range: Some((-90.0, 90.0)), sample: "35.49" },
+    ColumnSpec { col: T_CALC_COL, name: "T_CALC", units: "Celsius", range: Some((-60.0, 60.0)), sample: "1.1" },
+    ColumnSpec { col: T_HR_AVG_COL, name: "T_HR_AVG", units: "Celsius", range: Some((-60.0, 60.0)), sample: "1.2" },
+    ColumnSpec { col: T_MAX_COL, name: "T_MAX", units: "Celsius", range: Some((-60.0, 60.0)), sample: "1.3" },
+    ColumnSpec { col: T_MIN_COL, name: "T_MIN", units: "Celsius", range: Some((-60.0, 60.0)), sample: "1.4" },
+    ColumnSpec { col: P_CALC_COL, name: "P_CALC", units: "mm", range: Some((0.0, 500.0)), sample: "1.5" },
+    ColumnSpec { col: SOLARAD_COL, name: "SOLARAD", units: "W/m^2", range: Some((0.0, 1500.0)), sample: "116" },
+    ColumnSpec { col: SOLARAD_FLAG_COL, name: "SOLARAD_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "0" },
+    ColumnSpec { col: SOLARAD_MAX_COL, name: "SOLARAD_MAX", units: "W/m^2", range: Some((0.0, 1500.0)), sample: "217" },
+    ColumnSpec { col: SOLARAD_MAX_FLAG_COL, name: "SOLARAD_MAX_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "3" },
+    ColumnSpec { col: SOLARAD_MIN_COL, name: "SOLARAD_MIN", units: "W/m^2", range: Some((0.0, 1500.0)), sample: "18" },
+    ColumnSpec { col: SOLARAD_MIN_FLAG_COL, name: "SOLARAD_MIN_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "1" },
+    ColumnSpec { col: SUR_TEMP_TYPE_COL, name: "SUR_TEMP_TYPE", units: "X", range: None, sample: "C" },
+    ColumnSpec { col: SUR_TEMP_COL, name: "SUR_TEMP", units: "Celsius", range: Some((-80.0, 80.0)), sample: "2.1" },
+    ColumnSpec { col: SUR_TEMP_FLAG_COL, name: "SUR_TEMP_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "2" },
+    ColumnSpec { col: SUR_TEMP_MAX_COL, name: "SUR_TEMP_MAX", units: "Celsius", range: Some((-80.0, 80.0)), sample: "2.2" },
+    ColumnSpec { col: SUR_TEMP_MAX_FLAG_COL, name: "SUR_TEMP_MAX_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "0" },
+    ColumnSpec { col: SUR_TEMP_MIN_COL, name: "SUR_TEMP_MIN", units: "Celsius", range: Some((-80.0, 80.0)), sample: "2.3" },
+    ColumnSpec { col: SUR_TEMP_MIN_FLAG_COL, name: "SUR_TEMP_MIN_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "3" },
+    ColumnSpec { col: RH_HR_AVG_COL, name: "RH_HR_AVG", units: "%", range: Some((0.0, 100.0)), sample: "64" },
+    ColumnSpec { col: RH_HR_AVG_FLAG_COL, name: "RH_HR_AVG_FLAG", units: "X", range: Some((0.0, 3.0)), sample: "1" },
+    ColumnSpec { col: SOIL_MOISTURE_5_COL, name: "SOIL_MOISTURE_5", units: "m^3/m^3", range: Some((0.0, 1.0)), sample: "0.105" },
+    ColumnSpec { col: SOIL_MOISTURE_10_COL, name: "SOIL_MOISTURE_10", units: "m^3/m^3", range: Some((0.0, 1.0)), sample: "0.110" },
+    ColumnSpec { col: SOIL_MOISTURE_20_COL, name: "SOIL_MOISTURE_20", units: "m^3/m^3", range: Some((0.0, 1.0)), sample: "0.120" },
+    ColumnSpec { col: SOIL_MOISTURE_50_COL, name: "SOIL_MOISTURE_50", units: "m^3/m^3", range: Some((0.0, 1.0)), sample: "0.150" },
+    ColumnSpec { col: SOIL_MOISTURE_100_COL, name: "SOIL_MOISTURE_100", units: "m^3/m^3", range: Some((0.0, 1.0)), sample: "0.200" },
+    ColumnSpec { col: SOIL_TEMP_5_COL, name: "SOIL_TEMP_5", units: "Celsius", range: Some((-40.0, 60.0)), sample: "3.5" },
+    ColumnSpec { col: SOIL_TEMP_10_COL, name: "SOIL_TEMP_10", units: "Celsius", range: Some((-40.0, 60.0)), sample: "3.6" },
+    ColumnSpec { col: SOIL_TEMP_20_COL, name: "SOIL_TEMP_20", units: "Celsius", range: Some((-40.0, 60.0)), sample: "3.7" },
+    ColumnSpec { col: SOIL_TEMP_50_COL, name: "SOIL_TEMP_50", units: "Celsius", range: Some((-40.0, 60.0)), sample: "3.8" },
+    ColumnSpec { col: SOIL_TEMP_100_COL, name: "SOIL_TEMP_100", units: "Celsius", range: Some((-40.0, 60.0)), sample: "3.9" },
+];
+
+type FloatField = fn(&NewObservation) -> Option<f32>;
+type FlagField = fn(&NewObservation) -> Option<i32>;
+
+/// Float columns and the `NewObservation` field each one populates
+const FLOAT_FIELDS: &[(usize, FloatField)] = &[
+    (T_CALC_COL, |o| o.t_calc),
+    (T_HR_AVG_COL, |o| o.t_hr_avg),
+    (T_MAX_COL, |o| o.t_max),
+    (T_MIN_COL, |o| o.t_min),
+    (P_CALC_COL, |o| o.p_calc),
+    (SOLARAD_COL, |o| o.solarad),
+    (SOLARAD_MAX_COL, |o| o.solarad_max),
+    (SOLARAD_MIN_COL, |o| o.solarad_min),
+    (SUR_TEMP_COL, |o| o.sur_temp),
+    (SUR_TEMP_MAX_COL, |o| o.sur_temp_max),
+    (SUR_TEMP_MIN_COL, |o| o.sur_temp_min),
+    (RH_HR_AVG_COL, |o| o.rh_hr_avg),
+    (SOIL_MOISTURE_5_COL, |o| o.soil_moisture_5),
+    (SOIL_MOISTURE_10_COL, |o| o.soil_moisture_10),
+    (SOIL_MOISTURE_20_COL, |o| o.soil_moisture_20),
+    (SOIL_MOISTURE_50_COL, |o| o.soil_moisture_50),
+    (SOIL_MOISTURE_100_COL, |o| o.soil_moisture_100),
+    (SOIL_TEMP_5_COL, |o| o.soil_temp_5),
+    (SOIL_TEMP_10_COL, |o| o.soil_temp_10),
+    (SOIL_TEMP_20_COL, |o| o.soil_temp_20),
+    (SOIL_TEMP_50_COL, |o| o.soil_temp_50),
+    (SOIL_TEMP_100_COL, |o| o.soil_temp_100),
+];
+
+/// Flag columns and the `NewObservation` field each one populates
+const FLAG_FIELDS: &[(usize, FlagField)] = &[
+    (SOLARAD_FLAG_COL, |o| o.solarad_flag),
+    (SOLARAD_MAX_FLAG_COL, |o| o.solarad_max_flag),
+    (SOLARAD_MIN_FLAG_COL, |o| o.solarad_min_flag),
+    (SUR_TEMP_FLAG_COL, |o| o.sur_temp_flag),
+    (SUR_TEMP_MAX_FLAG_COL, |o| o.sur_temp_max_flag),
+    (SUR_TEMP_MIN_FLAG_COL, |o| o.sur_temp_min_flag),
+    (RH_HR_AVG_FLAG_COL, |o| o.rh_hr_avg_flag),
+];
+
+fn spec_for(col: usize) -> &'static ColumnSpec {
+    SPEC.iter()
+        .find(|s| s.col == col)
+        .unwrap_or_else(|| panic!("No spec for column {}", col))
+}
+
+/// Build a data line from the spec samples, in column order
+fn sample_line() -> String {
+    SPEC.iter().map(|s| s.sample).collect::<Vec<_>>().join(" ")
+}
+
+fn parse_sample() -> NewObservation {
+    let (mut observations, stats) =
+        Parser::parse_file(&sample_line(), FieldSeparator::Whitespace).expect("Parse failed");
+    assert_eq!(stats.parse_failures, 0);
+    assert_eq!(observations.len(), 1);
+    observations.remove(0)
+}
+
+/// Test the spec table covers every column exactly once, in order
+#[test]
+fn test_spec_covers_all_columns() {
+    assert_eq!(SPEC.len(), TOTAL_FIELDS);
+    for (i, spec) in SPEC.iter().enumerate() {
+        assert_eq!(spec.col, i, "{} is out of order", spec.name);
+    }
+    assert_eq!(SOIL_TEMP_100_COL, 37);
+    assert_eq!(MIN_FIELDS, 28);
+}
+
+/// Test the sample values themselves fall inside the documented ranges
+#[test]
+fn test_spec_samples_within_range() {
+    for spec in SPEC {
+        if let Some((min, max)) = spec.range {
+            let value: f64 = spec.sample.parse().unwrap();
+            assert!(
+                (min..=max).contains(&value),
+                "{} sample {} {} outside {}..={}",
+                spec.name,
+                value,
+                spec.units,
+                min,
+                max
+            );
+        }
+    }
+}
+
+/// Test identifier and timestamp columns map to the right fields
+#[test]
+fn test_identifier_columns() {
+    let obs = parse_sample();
+
+    assert_eq!(
+        obs.wbanno,
+        spec_for(WBANNO_COL).sample.parse::<Wbanno>().unwrap()
+    );
+    assert_eq!(
+        obs.crx_version.as_deref(),
+        Some(spec_for(CRX_VN_COL).sample)
+    );
+    assert_eq!(
+        obs.utc_datetime.format("%Y%m%d%H%M").to_string(),
+        format!(
+            "{}{}",
+            spec_for(UTC_DATE_COL).sample,
+            spec_for(UTC_TIME_COL).sample
+        )
+    );
+    assert_eq!(
+        obs.lst_datetime.format("%Y%m%d%H%M").to_string(),
+        format!(
+            "{}{}",
+            spec_for(LST_DATE_COL).sample,
+            spec_for(LST_TIME_COL).sample
+        )
+    );
+    assert_eq!(
+        obs.sur_temp_type.as_deref(),
+        Some(spec_for(SUR_TEMP_TYPE_COL).sample)
+    );
+}
+
+/// Test every measurement column maps to its own field
+#[test]
+fn test_measurement_columns() {
+    let obs = parse_sample();
+
+    for (col, get) in FLOAT_FIELDS {
+        let spec = spec_for(*col);
+        let expected: f32 = spec.sample.parse().unwrap();
+        assert_eq!(get(&obs), Some(expected), "{} ({})", spec.name, spec.units);
+    }
+}
+
+/// Test every quality flag column maps to its own field
+#[test]
+fn test_flag_columns() {
+    let obs = parse_sample();
+
+    for (col, get) in FLAG_FIELDS {
+        let spec = spec_for(*col);
+        let expected: i32 = spec.sample.parse().unwrap();
+        assert_eq!(get(&obs), Some(expected), "{}", spec.name);
+    }
+}
@@ -0,0 +1,618 @@
+mod common;
+
+use chrono::Datelike;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::watch;
+use uscrn_ingest::backfill::{BackfillJob, BACKFILL_SOURCE};
+use uscrn_ingest::config::{Config, ConfigBuilder, DatabaseConfig, SourceConfig};
+use uscrn_ingest::db::models::{NewProcessedFile, ProcessingStatus};
+use uscrn_ingest::db::Repository;
+use uscrn_ingest::fetcher::content_hash;
+use uscrn_ingest::scheduler::Scheduler;
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::make_processed_file;
+
+const SAMPLE: &str = include_str!("fixtures/uscrn_samples/crx_v3_38_fields.txt");
+
+fn file_name(year: i32, station: &str) -> String {
+    format!("CRNH0203-{}-NC_{}.txt", year, station)
+}
+
+/// Serve a year directory listing linking to `files`
+async fn mount_listing(server: &MockServer, year: i32, files: &[String]) {
+    let links: String = files
+        .iter()
+        .map(|f| format!(r#"<a href="{0}">{0}</a>"#, f))
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/", year)))
+        .respond_with(ResponseTemplate::new(200).set_body_string(links))
+        .mount(server)
+        .await;
+}
+
+/// Serve the sample data file, expecting it to be downloaded exactly `times` times
+async fn mount_file(server: &MockServer, year: i32, name: &str, times: u64) {
+    mount_file_body(server, year, name, SAMPLE, times).await;
+}
+
+/// Serve `body` as a data file, expecting it to be downloaded exactly `times` times
+async fn mount_file_body(server: &MockServer, year: i32, name: &str, body: &str, times: u64) {
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/{}", year, name)))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body.to_string()))
+        .expect(times)
+        .named(name.to_string())
+        .mount(server)
+        .await;
+}
+
+async fn record_processed(repo: &Repository, year: i32, name: &str) {
+    repo.mark_file_processed(NewProcessedFile {
+        state: "NC".to_string(),
+        station_name: "Test".to_string(),
+        rows_processed: 2,
+        observations_inserted: 2,
+        processing_status: ProcessingStatus::Completed.to_string(),
+        ..make_processed_file(name, year)
+    })
+    .await
+    .expect("File insert failed");
+}
+
+/// Config for a mock NOAA server; tests pick years with `with_years_override`
+fn mock_config(base_url: &str) -> Config {
+    let database = DatabaseConfig::builder()
+        .host("localhost")
+        .name("test")
+        .user("test")
+        .password("test")
+        .build()
+        .expect("Invalid test database config");
+    ConfigBuilder::new()
+        .database(database)
+        .source(SourceConfig {
+            base_url: base_url.to_string(),
+            request_delay_ms: 0,
+            allow_http: true,
+            ..SourceConfig::default()
+        })
+        .build()
+        .expect("Invalid test config")
+}
+
+/// Test current-year files are always re-processed while processed historical files are skipped
+#[sqlx::test]
+async fn test_run_ingestion_reprocesses_current_year_only(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let past_year = current_year - 1;
+    let repo = Arc::new(Repository::new(pool));
+
+    let current_existing = file_name(current_year, "Existing");
+    let current_new = file_name(current_year, "New");
+    let past_existing = file_name(past_year, "Existing");
+
+    record_processed(&repo, current_year, &current_existing).await;
+    record_processed(&repo, past_year, &past_existing).await;
+
+    let server = MockServer::start().await;
+    mount_listing(
+        &server,
+        current_year,
+        &[current_existing.clone(), current_new.clone()],
+    )
+    .await;
+    mount_listing(&server, past_year, std::slice::from_ref(&past_existing)).await;
+
+    mount_file(&server, current_year, &current_existing, 1).await;
+    mount_file(&server, current_year, &current_new, 1).await;
+    mount_file(&server, past_year, &past_existing, 0).await;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(mock_config(&server.uri()), Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![past_year, current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    for name in [&current_existing, &current_new] {
+        let file = repo
+            .get_processed_file(name)
+            .await
+            .expect("Query failed")
+            .expect("File should be recorded");
+        assert_eq!(file.processing_status.as_deref(), Some("completed"));
+        // Both files hold the same sample rows, so whichever is stored second
+        // updates the first one's rows
+        let stored =
+            file.observations_inserted.unwrap_or(0) + file.observations_updated.unwrap_or(0);
+        assert_eq!(stored, 2, "{}", name);
+    }
+
+    // MockServer verifies each file's expected download count on drop
+}
+
+/// Test a years override limits ingestion to the given years
+#[sqlx::test]
+async fn test_run_ingestion_with_years_override(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let past_year = current_year - 1;
+    let repo = Arc::new(Repository::new(pool));
+
+    let current_file = file_name(current_year, "Current");
+    let past_file = file_name(past_year, "Past");
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&current_file)).await;
+    mount_listing(&server, past_year, std::slice::from_ref(&past_file)).await;
+
+    // The config asks for the current year, but only the override is processed
+    mount_file(&server, current_year, &current_file, 0).await;
+    mount_file(&server, past_year, &past_file, 1).await;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(mock_config(&server.uri()), Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![past_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    assert!(repo
+        .get_processed_file(&past_file)
+        .await
+        .expect("Query failed")
+        .is_some());
+    assert!(repo
+        .get_processed_file(&current_file)
+        .await
+        .expect("Query failed")
+        .is_none());
+}
+
+/// Test files in force_reprocess_years are re-processed even though already recorded
+#[sqlx::test]
+async fn test_run_ingestion_force_reprocess_years(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let forced_year = current_year - 1;
+    let other_year = current_year - 2;
+    let repo = Arc::new(Repository::new(pool));
+
+    let forced_file = file_name(forced_year, "Forced");
+    let other_file = file_name(other_year, "Other");
+
+    record_processed(&repo, forced_year, &forced_file).await;
+    record_processed(&repo, other_year, &other_file).await;
+
+    let server = MockServer::start().await;
+    mount_listing(&server, forced_year, std::slice::from_ref(&forced_file)).await;
+    mount_listing(&server, other_year, std::slice::from_ref(&other_file)).await;
+
+    mount_file(&server, forced_year, &forced_file, 1).await;
+    mount_file(&server, other_year, &other_file, 0).await;
+
+    let mut config = mock_config(&server.uri());
+    config.scheduler.force_reprocess_years = vec![forced_year];
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![other_year, forced_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let file = repo
+        .get_processed_file(&forced_file)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert!(
+        file.file_hash.is_some(),
+        "forced file should be re-recorded"
+    );
+}
+
+/// Serve `name` as unmodified (304) to conditional requests and as the sample otherwise
+async fn mount_conditional_file(server: &MockServer, year: i32, name: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/{}", year, name)))
+        .and(header_exists("if-modified-since"))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(server)
+        .await;
+    mount_file(server, year, name, 1).await;
+}
+
+/// Record `name` as completed with a Last-Modified, so it is fetched conditionally
+async fn record_processed_with_last_modified(repo: &Repository, year: i32, name: &str) {
+    repo.mark_file_processed(NewProcessedFile {
+        last_modified: Some(chrono::Utc::now() - chrono::Duration::days(1)),
+        processing_status: ProcessingStatus::Completed.to_string(),
+        ..make_processed_file(name, year)
+    })
+    .await
+    .expect("File insert failed");
+}
+
+/// Test forced years and `ingest_file` download unconditionally, so a server
+/// that would answer 304 still serves the file
+#[sqlx::test]
+async fn test_forced_and_explicit_ingest_ignore_not_modified(pool: PgPool) {
+    let forced_year = chrono::Utc::now().year() - 1;
+    let repo = Arc::new(Repository::new(pool.clone()));
+
+    let forced_file = file_name(forced_year, "Forced");
+    let explicit_file = file_name(forced_year - 1, "Explicit");
+    record_processed_with_last_modified(&repo, forced_year, &forced_file).await;
+    record_processed_with_last_modified(&repo, forced_year - 1, &explicit_file).await;
+
+    let server = MockServer::start().await;
+    mount_listing(&server, forced_year, std::slice::from_ref(&forced_file)).await;
+    mount_listing(
+        &server,
+        forced_year - 1,
+        std::slice::from_ref(&explicit_file),
+    )
+    .await;
+    mount_conditional_file(&server, forced_year, &forced_file).await;
+    mount_conditional_file(&server, forced_year - 1, &explicit_file).await;
+
+    let mut config = mock_config(&server.uri());
+    config.scheduler.force_reprocess_years = vec![forced_year];
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![forced_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+    let forced = repo
+        .get_processed_file(&forced_file)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(forced.file_hash, Some(content_hash(SAMPLE)));
+
+    let rows = scheduler
+        .ingest_file(&explicit_file)
+        .await
+        .expect("Ingest failed");
+    assert_eq!(rows, 2);
+}
+
+/// Test `ingest_file` stores a file that location filters, the reprocess
+/// window and a 304 would each otherwise exclude
+#[sqlx::test]
+async fn test_ingest_file_ignores_location_filters_and_window(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool));
+
+    let name = file_name(current_year, "Excluded");
+    record_processed_with_last_modified(&repo, current_year, &name).await;
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_conditional_file(&server, current_year, &name).await;
+
+    let mut config = mock_config(&server.uri());
+    config.locations.states = vec!["CA".to_string()];
+    config.locations.stations = vec![99999];
+    config.source.reprocess_window_hours = Some(1);
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx);
+
+    let rows = scheduler.ingest_file(&name).await.expect("Ingest failed");
+    assert_eq!(rows, 2);
+
+    let file = repo
+        .get_processed_file(&name)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(file.processing_status.as_deref(), Some("completed"));
+}
+
+/// Test rows older than the retention cutoff are not stored, so retention does
+/// not delete and re-insert them every run
+#[sqlx::test]
+async fn test_retention_cutoff_drops_expired_rows_before_insert(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool.clone()));
+
+    let name = file_name(current_year, "Expired");
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_file(&server, current_year, &name, 1).await;
+
+    // The sample rows are from 2024, well before the cutoff
+    let mut config = mock_config(&server.uri());
+    config.retention.enabled = true;
+    config.retention.retain_days = 30;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count query failed");
+    assert_eq!(count, 0);
+
+    let file = repo
+        .get_processed_file(&name)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(file.processing_status.as_deref(), Some("skipped"));
+}
+
+/// Test a file with no rows for the configured stations is recorded as skipped, not failed
+#[sqlx::test]
+async fn test_station_filter_mismatch_marks_file_skipped(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool));
+
+    let name = file_name(current_year, "Other");
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_file(&server, current_year, &name, 1).await;
+
+    let mut config = mock_config(&server.uri());
+    config.locations.stations = vec![99999];
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let file = repo
+        .get_processed_file(&name)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(file.processing_status.as_deref(), Some("skipped"));
+    assert!(repo
+        .get_failed_processed_files()
+        .await
+        .expect("Query failed")
+        .is_empty());
+}
+
+/// Test require_complete_observations drops hours missing a core reading
+#[sqlx::test]
+async fn test_require_complete_observations(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool.clone()));
+
+    let name = file_name(current_year, "Partial");
+    // Second row has no solar radiation reading
+    let body = SAMPLE.replacen("    52.3 0", " -9999.0 0", 1);
+    assert_ne!(body, SAMPLE);
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_file_body(&server, current_year, &name, &body, 1).await;
+
+    let mut config = mock_config(&server.uri());
+    config.source.require_complete_observations = true;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count query failed");
+    assert_eq!(count, 1);
+}
+
+/// Test reprocess_window_hours trims only files already stored in full
+#[sqlx::test]
+async fn test_reprocess_window_applies_only_to_completed_files(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool.clone()));
+
+    // The sample rows are older than any window
+    let new_file = file_name(current_year, "New");
+    let completed_file = file_name(current_year, "Completed");
+    record_processed(&repo, current_year, &completed_file).await;
+
+    let server = MockServer::start().await;
+    mount_listing(
+        &server,
+        current_year,
+        &[new_file.clone(), completed_file.clone()],
+    )
+    .await;
+    mount_file(&server, current_year, &new_file, 1).await;
+    mount_file(&server, current_year, &completed_file, 1).await;
+
+    let mut config = mock_config(&server.uri());
+    config.source.reprocess_window_hours = Some(1);
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count query failed");
+    assert_eq!(count, 2, "The new file should be ingested whole");
+
+    // The empty window still records the downloaded content
+    let completed = repo
+        .get_processed_file(&completed_file)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(completed.processing_status.as_deref(), Some("completed"));
+    assert_eq!(completed.file_hash, Some(content_hash(SAMPLE)));
+}
+
+/// Test parallel_years processes every year and merges their results
+#[sqlx::test]
+async fn test_run_ingestion_parallel_years(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let years: Vec<i32> = (current_year - 3..current_year).collect();
+    let repo = Arc::new(Repository::new(pool));
+
+    let server = MockServer::start().await;
+    let mut names = Vec::new();
+    for &year in &years {
+        let name = file_name(year, &format!("Station_{}", year));
+        mount_listing(&server, year, std::slice::from_ref(&name)).await;
+        mount_file(&server, year, &name, 1).await;
+        names.push(name);
+    }
+
+    let mut config = mock_config(&server.uri());
+    config.scheduler.parallel_years = Some(2);
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler =
+        Scheduler::new(config, Arc::clone(&repo), shutdown_rx).with_years_override(years.clone());
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    for name in &names {
+        let file = repo
+            .get_processed_file(name)
+            .await
+            .expect("Query failed")
+            .expect("File should be recorded");
+        assert_eq!(
+            file.processing_status.as_deref(),
+            Some("completed"),
+            "{}",
+            name
+        );
+        assert_eq!(file.file_size_bytes, Some(SAMPLE.len() as i64));
+    }
+}
+
+/// Test a file that fails to process is recorded in ingestion_errors
+#[sqlx::test]
+async fn test_file_error_is_recorded(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool));
+
+    let name = file_name(current_year, "Garbled");
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_file_body(&server, current_year, &name, "not a data file\n", 1).await;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(mock_config(&server.uri()), Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let errors = repo
+        .get_recent_ingestion_errors(chrono::DateTime::<chrono::Utc>::UNIX_EPOCH, 10)
+        .await
+        .expect("Query failed");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].file_name, name);
+    assert_eq!(errors[0].retry_count, 0);
+}
+
+/// Test a current-year file whose content hash is unchanged is not re-parsed or stored
+#[sqlx::test]
+async fn test_unchanged_current_year_file_skipped(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let repo = Arc::new(Repository::new(pool.clone()));
+
+    let name = file_name(current_year, "Unchanged");
+    repo.mark_file_processed(NewProcessedFile {
+        state: "NC".to_string(),
+        station_name: "Test".to_string(),
+        file_hash: Some(content_hash(SAMPLE)),
+        processing_status: ProcessingStatus::Completed.to_string(),
+        ..make_processed_file(&name, current_year)
+    })
+    .await
+    .expect("File insert failed");
+
+    let server = MockServer::start().await;
+    mount_listing(&server, current_year, std::slice::from_ref(&name)).await;
+    mount_file(&server, current_year, &name, 1).await;
+
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(mock_config(&server.uri()), Arc::clone(&repo), shutdown_rx)
+        .with_years_override(vec![current_year]);
+
+    scheduler.run_ingestion().await.expect("Ingestion failed");
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM observations")
+        .fetch_one(&pool)
+        .await
+        .expect("Count query failed");
+    assert_eq!(count, 0);
+}
+
+/// Test backfill stores listed files even when already processed or outside the
+/// station filter, and reports failures per file
+#[sqlx::test]
+async fn test_backfill_job(pool: PgPool) {
+    let current_year = chrono::Utc::now().year();
+    let past_year = current_year - 1;
+    let repo = Arc::new(Repository::new(pool));
+
+    // Already recorded as processed, so a normal run would skip it
+    let name = file_name(past_year, "Backfill");
+    record_processed(&repo, past_year, &name).await;
+    let missing = file_name(past_year, "Missing");
+
+    let server = MockServer::start().await;
+    mount_file(&server, past_year, &name, 1).await;
+    Mock::given(method("GET"))
+        .and(path(format!("/{}/{}", past_year, missing)))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let urls = vec![
+        format!("{}/{}/{}", server.uri(), past_year, name),
+        format!("{}/{}/{}", server.uri(), past_year, missing),
+        format!("{}/{}/not_a_data_file.txt", server.uri(), past_year),
+    ];
+    // No sample station matches, so a normal run would store nothing
+    let mut config = mock_config(&server.uri());
+    config.locations.stations = vec![99999];
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config, Arc::clone(&repo), shutdown_rx);
+
+    let report = BackfillJob::new(urls.clone())
+        .run(&scheduler)
+        .await
+        .expect("Backfill failed");
+
+    assert_eq!(report.succeeded(), 1);
+    assert_eq!(report.failed(), 2);
+    assert_eq!(report.files[0].url, urls[0]);
+    assert_eq!(report.files[0].result, Ok(2));
+    assert!(report.files[1].result.is_err());
+    assert!(report.files[2].result.is_err());
+
+    let file = repo
+        .get_processed_file(&name)
+        .await
+        .expect("Query failed")
+        .expect("File should be recorded");
+    assert_eq!(file.source, BACKFILL_SOURCE);
+    assert_eq!(file.processing_status.as_deref(), Some("completed"));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use async_trait::async_trait;
+use common::make_processed_file;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use uscrn_ingest::config::{Config, LocationFilter};
+use uscrn_ingest::db::Repository;
+use uscrn_ingest::error::Result;
+use uscrn_ingest::fetcher::{FetcherTrait, FileInfo};
+use uscrn_ingest::scheduler::Scheduler;
+
+/// Fetcher double that lists a fixed number of files per year
+struct StubFetcher {
+    files_per_year: usize,
+}
+
+#[async_trait]
+impl FetcherTrait for StubFetcher {
+    async fn list_files_for_year(
+        &self,
+        year: i32,
+        _filter: &LocationFilter,
+    ) -> Result<Vec<FileInfo>> {
+        Ok((0..self.files_per_year)
+            .map(|i| FileInfo {
+                name: format!("CRNH0203-{}-CA_Test_{}.txt", year, i),
+                url: format!("https://www.ncei.noaa.gov/{}/{}.txt", year, i),
+                year,
+                state: "CA".to_string(),
+                station_name: format!("Test_{}", i),
+            })
+            .collect())
+    }
+
+    async fn download_file(&self, _url: &str) -> Result<String> {
+        Ok(String::new())
+    }
+}
+
+fn test_config(extra_scheduler: &str) -> Config {
+    let yaml = format!(
+        r#"
+database:
+  host: localhost
+  name: test
+  user: test
+  password: test
+scheduler:
+  interval_minutes: 60
+{}
+source:
+  base_url: "https://www.ncei.noaa.gov/pub/data/uscrn/products/hourly02/"
+  years_to_fetch: [2023, 2024]
+  request_delay_ms: 100
+"#,
+        extra_scheduler
+    );
+    serde_yaml::from_str(&yaml).expect("Invalid test config")
+}
+
+/// Test the estimate leaves out historical files a run would skip as processed
+#[sqlx::test]
+async fn test_estimate_run_duration_excludes_processed_files(pool: PgPool) {
+    let config = test_config(
+        r#"
+  estimate:
+    download_latency_ms: 1000
+    parsing_ms: 100
+    db_insert_ms: 300
+"#,
+    );
+    let repo = Arc::new(Repository::new(pool));
+    for i in 0..2 {
+        repo.mark_file_processed(make_processed_file(
+            &format!("CRNH0203-2023-CA_Test_{}.txt", i),
+            2023,
+        ))
+        .await
+        .expect("File insert failed");
+    }
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let scheduler = Scheduler::new(config.clone(), Arc::clone(&repo), shutdown_rx.clone());
+
+    let fetcher = StubFetcher { files_per_year: 5 };
+    let estimate = scheduler
+        .estimate_run_duration(&fetcher)
+        .await
+        .expect("Estimate failed");
+
+    // (3 unprocessed 2023 files + 5 2024 files) * (1000 + 100 + 300 + 100 request delay) ms
+    assert_eq!(estimate, Duration::from_millis(12_000));
+
+    // A forced year counts every file again
+    let mut forced = config;
+    forced.scheduler.force_reprocess_years = vec![2023];
+    let scheduler = Scheduler::new(forced, repo, shutdown_rx);
+    let estimate = scheduler
+        .estimate_run_duration(&fetcher)
+        .await
+        .expect("Estimate failed");
+    assert_eq!(estimate, Duration::from_millis(15_000));
+}
+
+/// Test a years override replaces the configured years
+#[sqlx::test]
+async fn test_estimate_with_years_override(pool: PgPool) {
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let config = test_config(
+        r#"
+  estimate:
+    download_latency_ms: 1000
+    parsing_ms: 100
+    db_insert_ms: 300
+"#,
+    );
+    let scheduler = Scheduler::new(config, Arc::new(Repository::new(pool)), shutdown_rx)
+        .with_years_override(vec![2024]);
+
+    let fetcher = StubFetcher { files_per_year: 5 };
+    let estimate = scheduler
+        .estimate_run_duration(&fetcher)
+        .await
+        .expect("Estimate failed");
+
+    // Only 2024 of the configured [2023, 2024]: 1 year * 5 files * 1500 ms
+    assert_eq!(estimate, Duration::from_millis(7_500));
+}